@@ -0,0 +1,78 @@
+// Matching with precompiled byte-level DFAs on targets that have no `std`, and possibly no
+// allocator. `dfa-utils` builds and minimizes the DFAs, and `DFA::to_static_parts` or
+// `DFA::to_static_rust` turn them into the arrays that a `StaticDFA` reads; this crate holds only
+// what is needed to run them, and `dfa-utils` re-exports it.
+//
+// This is all the `no_std` support there is. `DFA`, `Table` and `Partition` stay in `dfa-utils`
+// and need `std`: they key every lookup by `HashMap` and `HashSet`, which `alloc` lacks, and the
+// `BTreeMap` it does have would put an `Ord` bound on every state and label type. Build and
+// minimize automata on a host, and ship the arrays to the target.
+
+#![no_std]
+
+// States are numbered from 0, the initial state. Row `s` of `transitions` holds the 256 states
+// that `s` moves to on each byte, and `accepting[s]` says whether `s` accepts.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct StaticDFA<'a> {
+    transitions: &'a [u32],
+    accepting: &'a [bool],
+}
+
+impl<'a> StaticDFA<'a> {
+    // Panics (or fails to compile, in a `const` or `static`) if `transitions` does not have a
+    // full row for every state.
+    pub const fn new(transitions: &'a [u32], accepting: &'a [bool]) -> StaticDFA<'a> {
+        assert!(
+            transitions.len() == 256 * accepting.len(),
+            "every state needs a row of 256 transitions"
+        );
+        StaticDFA {
+            transitions,
+            accepting,
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.accepting.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.accepting.is_empty()
+    }
+
+    pub fn next_state(&self, state: u32, b: u8) -> u32 {
+        self.transitions[256 * state as usize + b as usize]
+    }
+
+    pub fn is_accepting(&self, state: u32) -> bool {
+        self.accepting[state as usize]
+    }
+
+    pub fn accepts(&self, input: &[u8]) -> bool {
+        let state = input.iter().fold(0, |s, &b| self.next_state(s, b));
+        self.is_accepting(state)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::StaticDFA;
+
+    // Accepts "ab" and nothing else.
+    static AB_TRANSITIONS: [u32; 4 * 256] = {
+        let mut transitions = [3; 4 * 256];
+        transitions[b'a' as usize] = 1;
+        transitions[256 + b'b' as usize] = 2;
+        transitions
+    };
+    static AB: StaticDFA<'static> = StaticDFA::new(&AB_TRANSITIONS, &[false, false, true, false]);
+
+    #[test]
+    fn static_matching() {
+        assert_eq!(AB.len(), 4);
+        assert!(AB.accepts(b"ab"));
+        assert!(!AB.accepts(b"a"));
+        assert!(!AB.accepts(b"abb"));
+        assert_eq!(AB.next_state(0, b'b'), 3);
+    }
+}
@@ -0,0 +1,50 @@
+/* The C API of dfa-utils, enabled by its `capi` feature. States and labels are numbers. */
+
+#ifndef DFA_UTILS_H
+#define DFA_UTILS_H
+
+#include <stddef.h>
+#include <stdint.h>
+
+#ifdef __cplusplus
+extern "C" {
+#endif
+
+/* An opaque handle to a DFA, owned by the caller and released with dfa_destroy. */
+typedef struct DfaHandle DfaHandle;
+
+/* Creates a DFA. `transitions` holds n_transitions triples (src, label, dst) back to back.
+ * Returns NULL if two transitions leave the same state on the same label for different states,
+ * or if 3 * n_transitions overflows. */
+DfaHandle *dfa_create(uint32_t initial_state, const uint32_t *final_states,
+                      size_t n_final_states, const uint32_t *transitions,
+                      size_t n_transitions);
+
+/* Releases a DFA. Null is ignored. */
+void dfa_destroy(DfaHandle *dfa);
+
+/* Returns a new, pruned and minimized DFA. States keep the number of a state they stand for. */
+DfaHandle *dfa_minimize_new(const DfaHandle *dfa);
+
+/* Return new DFAs for the set operations, with states numbered from 0. */
+DfaHandle *dfa_union_new(const DfaHandle *lhs, const DfaHandle *rhs);
+DfaHandle *dfa_intersection_new(const DfaHandle *lhs, const DfaHandle *rhs);
+DfaHandle *dfa_difference_new(const DfaHandle *lhs, const DfaHandle *rhs);
+
+/* Returns 1 if the word of `len` labels is accepted, and 0 otherwise. */
+int32_t dfa_run(const DfaHandle *dfa, const uint32_t *word, size_t len);
+
+uint32_t dfa_initial_state(const DfaHandle *dfa);
+
+/* Returns 1 if `state` is accepting, and 0 otherwise. */
+int32_t dfa_is_accepting(const DfaHandle *dfa, uint32_t state);
+
+/* Copies up to `capacity` sorted transition triples into `out`, and returns the total number
+ * of transitions. */
+size_t dfa_transitions(const DfaHandle *dfa, uint32_t *out, size_t capacity);
+
+#ifdef __cplusplus
+}
+#endif
+
+#endif
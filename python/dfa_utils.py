@@ -0,0 +1,120 @@
+"""Python bindings for dfa-utils, over its C API and ctypes.
+
+Build the shared library with the `capi` feature:
+
+    cargo rustc --release --lib --features capi --crate-type cdylib
+
+and point DFA_UTILS_LIB at it, or leave it in target/release, where this module looks by
+default. Like the C API, this covers DFAs whose states and labels are non-negative integers:
+building them, minimizing them, running them, and their unions, intersections and
+differences. NFAs and regex compilation have no C API yet, so they are not exposed here.
+"""
+
+import ctypes
+import os
+import sys
+
+_u32 = ctypes.c_uint32
+_size = ctypes.c_size_t
+_handle = ctypes.c_void_p
+
+
+def _library_path():
+    path = os.environ.get("DFA_UTILS_LIB")
+    if path:
+        return path
+    name = {"darwin": "libdfa_utils.dylib", "win32": "dfa_utils.dll"}.get(
+        sys.platform, "libdfa_utils.so"
+    )
+    root = os.path.dirname(os.path.dirname(os.path.abspath(__file__)))
+    return os.path.join(root, "target", "release", name)
+
+
+def _load():
+    lib = ctypes.CDLL(_library_path())
+    signatures = {
+        "dfa_create": (_handle, [_u32, ctypes.POINTER(_u32), _size, ctypes.POINTER(_u32), _size]),
+        "dfa_destroy": (None, [_handle]),
+        "dfa_minimize_new": (_handle, [_handle]),
+        "dfa_union_new": (_handle, [_handle, _handle]),
+        "dfa_intersection_new": (_handle, [_handle, _handle]),
+        "dfa_difference_new": (_handle, [_handle, _handle]),
+        "dfa_run": (ctypes.c_int32, [_handle, ctypes.POINTER(_u32), _size]),
+        "dfa_initial_state": (_u32, [_handle]),
+        "dfa_is_accepting": (ctypes.c_int32, [_handle, _u32]),
+        "dfa_transitions": (_size, [_handle, ctypes.POINTER(_u32), _size]),
+    }
+    for name, (restype, argtypes) in signatures.items():
+        function = getattr(lib, name)
+        function.restype = restype
+        function.argtypes = argtypes
+    return lib
+
+
+_lib = _load()
+
+
+def _array(values):
+    values = list(values)
+    return (_u32 * len(values))(*values), len(values)
+
+
+class DFA:
+    """A DFA over integer states and labels, owned by the Rust library."""
+
+    def __init__(self, initial_state, final_states, transitions):
+        """`transitions` is an iterable of (src, label, dst) triples, with at most one per state
+        and label. Raises ValueError otherwise."""
+        finals, n_finals = _array(final_states)
+        triples = [value for transition in transitions for value in transition]
+        flat, n_values = _array(triples)
+        handle = _lib.dfa_create(initial_state, finals, n_finals, flat, n_values // 3)
+        if not handle:
+            raise ValueError("transitions are not deterministic")
+        self._handle = handle
+
+    @classmethod
+    def _wrap(cls, handle):
+        if not handle:
+            raise MemoryError("dfa-utils returned no DFA")
+        dfa = cls.__new__(cls)
+        dfa._handle = handle
+        return dfa
+
+    def __del__(self):
+        handle = getattr(self, "_handle", None)
+        if handle:
+            _lib.dfa_destroy(handle)
+            self._handle = None
+
+    def minimize(self):
+        """A pruned and minimized copy, whose states keep the number of a state they stand for."""
+        return DFA._wrap(_lib.dfa_minimize_new(self._handle))
+
+    def union(self, other):
+        return DFA._wrap(_lib.dfa_union_new(self._handle, other._handle))
+
+    def intersection(self, other):
+        return DFA._wrap(_lib.dfa_intersection_new(self._handle, other._handle))
+
+    def difference(self, other):
+        """The words this DFA accepts and `other` doesn't."""
+        return DFA._wrap(_lib.dfa_difference_new(self._handle, other._handle))
+
+    def accepts(self, word):
+        labels, n = _array(word)
+        return _lib.dfa_run(self._handle, labels, n) == 1
+
+    @property
+    def initial_state(self):
+        return _lib.dfa_initial_state(self._handle)
+
+    def is_accepting(self, state):
+        return _lib.dfa_is_accepting(self._handle, state) == 1
+
+    def transitions(self):
+        """The (src, label, dst) triples, sorted."""
+        n = _lib.dfa_transitions(self._handle, None, 0)
+        out = (_u32 * (3 * n))()
+        _lib.dfa_transitions(self._handle, out, n)
+        return [tuple(out[i : i + 3]) for i in range(0, 3 * n, 3)]
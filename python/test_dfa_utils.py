@@ -0,0 +1,43 @@
+"""Checks the bindings against a library built as described in dfa_utils.py.
+
+    python3 -m unittest discover python
+"""
+
+import unittest
+
+from dfa_utils import DFA
+
+
+class DfaTest(unittest.TestCase):
+    def test_minimize(self):
+        # Words over {0, 1} ending in 1, with a redundant copy of the accepting state.
+        dfa = DFA(
+            0,
+            [1, 2],
+            [(0, 0, 0), (0, 1, 1), (1, 0, 0), (1, 1, 2), (2, 0, 0), (2, 1, 1)],
+        )
+        minimal = dfa.minimize()
+        self.assertEqual(len(minimal.transitions()), 4)
+        self.assertTrue(minimal.accepts([0, 1, 1]))
+        self.assertFalse(minimal.accepts([1, 0]))
+        self.assertFalse(minimal.accepts([]))
+        self.assertFalse(minimal.is_accepting(minimal.initial_state))
+
+    def test_set_operations(self):
+        # Label 0 or 1, and label 1 or 2.
+        lhs = DFA(0, [1], [(0, 0, 1), (0, 1, 1)])
+        rhs = DFA(0, [1], [(0, 1, 1), (0, 2, 1)])
+        for dfa, expected in [
+            (lhs.union(rhs), [True, True, True]),
+            (lhs.intersection(rhs), [False, True, False]),
+            (lhs.difference(rhs), [True, False, False]),
+        ]:
+            self.assertEqual([dfa.accepts([label]) for label in range(3)], expected)
+
+    def test_nondeterministic(self):
+        with self.assertRaises(ValueError):
+            DFA(0, [1], [(0, 1, 1), (0, 1, 2)])
+
+
+if __name__ == "__main__":
+    unittest.main()
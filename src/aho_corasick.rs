@@ -0,0 +1,112 @@
+use std::collections::VecDeque;
+use std::fmt::Debug;
+use std::hash::Hash;
+
+use crate::hash::{HashMap, IntoStd};
+use crate::DFA;
+
+// Builds the Aho-Corasick automaton for `keywords` over `alphabet`, with the failure links
+// compiled away so that the result is a plain, complete DFA. It accepts exactly the words that
+// end with one of the keywords. The returned map labels each accepting state with the indices
+// (into `keywords`) of every keyword that ends there, in increasing order.
+pub fn aho_corasick_dfa<E>(
+    keywords: &[Vec<E>],
+    alphabet: &[E],
+) -> (DFA<u32, E>, std::collections::HashMap<u32, Vec<usize>>)
+where
+    E: Eq + Hash + Copy + Debug,
+{
+    // Build the trie of keywords. State 0 is the root.
+    let mut goto: HashMap<(u32, E), u32> = HashMap::default();
+    let mut outputs: Vec<Vec<usize>> = vec![Vec::new()];
+    for (i, keyword) in keywords.iter().enumerate() {
+        let mut q = 0;
+        for &label in keyword {
+            q = *goto.entry((q, label)).or_insert_with(|| {
+                outputs.push(Vec::new());
+                outputs.len() as u32 - 1
+            });
+        }
+        outputs[q as usize].push(i);
+    }
+
+    // Visit the trie in BFS order, so that each state's failure target is finished before it.
+    let mut fail = vec![0; outputs.len()];
+    let mut delta: HashMap<(u32, E), u32> = HashMap::default();
+    let mut queue = VecDeque::new();
+    queue.push_back(0);
+    while let Some(q) = queue.pop_front() {
+        let f = fail[q as usize];
+        if q != 0 {
+            let inherited = outputs[f as usize].clone();
+            outputs[q as usize].extend(inherited);
+        }
+        for &label in alphabet {
+            match goto.get(&(q, label)) {
+                Some(&child) => {
+                    fail[child as usize] = if q == 0 { 0 } else { delta[&(f, label)] };
+                    delta.insert((q, label), child);
+                    queue.push_back(child);
+                }
+                None => {
+                    let dst = if q == 0 { 0 } else { delta[&(f, label)] };
+                    delta.insert((q, label), dst);
+                }
+            }
+        }
+    }
+
+    let mut matches: HashMap<u32, Vec<usize>> = HashMap::default();
+    for (q, mut output) in outputs.into_iter().enumerate() {
+        if !output.is_empty() {
+            output.sort_unstable();
+            output.dedup();
+            matches.insert(q as u32, output);
+        }
+    }
+    let dfa = DFA::new(
+        0,
+        matches.keys().copied(),
+        delta
+            .into_iter()
+            .map(|((src, label), dst)| (src, label, dst)),
+    );
+    (dfa, matches.into_std())
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn run(dfa: &DFA<u32, char>, word: &str) -> u32 {
+        let delta: HashMap<(u32, char), u32> = dfa
+            .transitions
+            .clone()
+            .into_iter()
+            .map(|(src, label, dst)| ((src, label), dst))
+            .collect();
+        word.chars()
+            .fold(dfa.initial_state, |q, label| delta[&(q, label)])
+    }
+
+    #[test]
+    fn aho_corasick_classic() {
+        let keywords: Vec<Vec<char>> = ["he", "she", "his", "hers"]
+            .iter()
+            .map(|k| k.chars().collect())
+            .collect();
+        let alphabet: Vec<char> = "ehirsx".chars().collect();
+        let (dfa, matches) = aho_corasick_dfa(&keywords, &alphabet);
+
+        // The DFA is complete over the alphabet.
+        assert_eq!(
+            dfa.transitions.len(),
+            alphabet.len() * dfa.transitions.by_a().len()
+        );
+        assert_eq!(matches.get(&run(&dfa, "xxshe")), Some(&vec![0, 1]));
+        assert_eq!(matches.get(&run(&dfa, "shers")), Some(&vec![3]));
+        assert_eq!(matches.get(&run(&dfa, "hishe")), Some(&vec![0, 1]));
+        assert_eq!(matches.get(&run(&dfa, "hiss")), None);
+        assert_eq!(matches.get(&run(&dfa, "xhis")), Some(&vec![2]));
+    }
+}
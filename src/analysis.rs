@@ -0,0 +1,316 @@
+use std::collections::VecDeque;
+use std::fmt::Debug;
+use std::hash::Hash;
+
+use crate::hash::{HashMap, HashSet, IntoStd};
+use crate::DFA;
+
+// A witness that a language is infinite: `prefix`, followed by any number of repetitions of
+// `cycle`, followed by `suffix`, is always accepted. `cycle` is never empty.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Lasso<E> {
+    pub prefix: Vec<E>,
+    pub cycle: Vec<E>,
+    pub suffix: Vec<E>,
+}
+
+// A decomposition `x y z` of an accepted word such that `x y^n z` is accepted for every `n`, as in
+// the pumping lemma. `loop_states` are the states visited while reading `y`, starting with the
+// state that `y` returns to.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Pumping<S, E> {
+    pub x: Vec<E>,
+    pub y: Vec<E>,
+    pub z: Vec<E>,
+    pub loop_states: Vec<S>,
+}
+
+// A summary of a DFA's size and shape, from `DFA::stats`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Stats {
+    pub states: usize,
+    pub transitions: usize,
+    // The number of distinct transition labels.
+    pub alphabet_size: usize,
+    // The fraction of (state, label) pairs that have a transition. Complete DFAs have 1.0.
+    pub completeness: f64,
+    pub accepting_states: usize,
+    // The longest shortest path between any two states, where such a path exists.
+    pub diameter: usize,
+    pub sccs: usize,
+}
+
+impl<S, E> DFA<S, E>
+where
+    S: Eq + Hash + Copy + Debug,
+    E: Eq + Hash + Copy + Debug,
+{
+    // The states from which no accepting state can be reached. Once the DFA enters one of these,
+    // the input will be rejected no matter what follows.
+    pub fn dead_states(&self) -> std::collections::HashSet<S> {
+        let coaccessible = self.coaccessible();
+        self.states()
+            .into_iter()
+            .filter(|q| !coaccessible.contains(q))
+            .collect()
+    }
+
+    // The states whose transitions all lead back to themselves (including states with no
+    // transitions at all). Once the DFA enters one of these, it never leaves.
+    pub fn trap_states(&self) -> std::collections::HashSet<S> {
+        let mut traps = self.states();
+        for &(src, _, dst) in &self.transitions {
+            if src != dst {
+                traps.remove(&src);
+            }
+        }
+        traps.into_std()
+    }
+
+    // Returns a dead state reachable from the initial state, together with a shortest input
+    // that leads there. Returns `None` if every reachable state can still lead to acceptance.
+    // Note that this only considers explicit transitions: a missing transition also rejects.
+    pub fn dead_state_witness(&self) -> Option<(S, Vec<E>)> {
+        let dead = self.dead_states();
+        self.shortest_path(self.initial_state, |q| dead.contains(q))
+    }
+
+    // Splits an accepted `word` into `x y z` as in the pumping lemma, with `y` non-empty and
+    // `x y` as short as possible. This always succeeds for accepted words that are at least as
+    // long as the number of states. Returns `None` if `word` is rejected, or if it is too short
+    // to revisit any state.
+    pub fn pumping_decomposition(&self, word: &[E]) -> Option<Pumping<S, E>> {
+        let delta = self.delta();
+        let mut path = vec![self.initial_state];
+        for label in word {
+            path.push(*delta.get(&(*path.last().unwrap(), *label))?);
+        }
+        if !self.final_states.contains(path.last().unwrap()) {
+            return None;
+        }
+
+        let mut first_visit: HashMap<S, usize> = HashMap::default();
+        for (j, &q) in path.iter().enumerate() {
+            if let Some(&i) = first_visit.get(&q) {
+                return Some(Pumping {
+                    x: word[..i].to_vec(),
+                    y: word[i..j].to_vec(),
+                    z: word[j..].to_vec(),
+                    loop_states: path[i..j].to_vec(),
+                });
+            }
+            first_visit.insert(q, j);
+        }
+        None
+    }
+
+    // Maps every state reachable from the initial state to the length of the shortest input that
+    // reaches it.
+    pub fn depths(&self) -> std::collections::HashMap<S, usize> {
+        self.distances_from(self.initial_state).into_std()
+    }
+
+    // Gathers summary statistics. Computing the diameter requires a BFS from every state.
+    pub fn stats(&self) -> Stats {
+        let states = self.states();
+        let alphabet_size = self.transitions.by_b().len();
+        let pairs = states.len() * alphabet_size;
+        Stats {
+            states: states.len(),
+            transitions: self.transitions.len(),
+            alphabet_size,
+            completeness: if pairs == 0 {
+                1.0
+            } else {
+                self.transitions.len() as f64 / pairs as f64
+            },
+            accepting_states: self.final_states.len(),
+            diameter: states
+                .iter()
+                .flat_map(|&q| self.distances_from(q).into_values())
+                .max()
+                .unwrap_or(0),
+            sccs: self.sccs().len(),
+        }
+    }
+
+    // BFS distances from `start` to every state reachable from it.
+    fn distances_from(&self, start: S) -> HashMap<S, usize> {
+        let outflows = self.transitions.by_a();
+
+        let mut depths = HashMap::default();
+        let mut queue = VecDeque::new();
+        depths.insert(start, 0);
+        queue.push_back(start);
+        while let Some(src) = queue.pop_front() {
+            let d = depths[&src];
+            for &(_, dst) in outflows.get(&src).into_iter().flatten() {
+                depths.entry(dst).or_insert_with(|| {
+                    queue.push_back(dst);
+                    d + 1
+                });
+            }
+        }
+        depths
+    }
+
+    // Returns a witness that the language is infinite, or `None` if it is finite. The language is
+    // infinite exactly when some cycle is reachable and can lead to acceptance.
+    pub fn lasso(&self) -> Option<Lasso<E>> {
+        let reachable = self.reachable();
+        let coaccessible = self.coaccessible();
+        // Reachability and co-accessibility are the same for every state in a component.
+        let looping: HashSet<S> = self
+            .sccs()
+            .into_iter()
+            .filter(|scc| {
+                scc.cyclic
+                    && reachable.contains(&scc.states[0])
+                    && coaccessible.contains(&scc.states[0])
+            })
+            .flat_map(|scc| scc.states)
+            .collect();
+
+        let (q, prefix) = self.shortest_path(self.initial_state, |q| looping.contains(q))?;
+        let cycle = (&self.transitions)
+            .into_iter()
+            .filter(|&&(src, _, dst)| src == q && looping.contains(&dst))
+            .filter_map(|&(_, label, dst)| {
+                let (_, rest) = self.shortest_path(dst, |&r| r == q)?;
+                let mut cycle = vec![label];
+                cycle.extend(rest);
+                Some(cycle)
+            })
+            .min_by_key(|cycle| cycle.len())?;
+        let (_, suffix) = self.shortest_path(q, |r| self.final_states.contains(r))?;
+        Some(Lasso {
+            prefix,
+            cycle,
+            suffix,
+        })
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::collections::HashSet;
+
+    use crate::DFA;
+
+    #[test]
+    fn dead_and_trap_states() {
+        // Accepts words starting with 'a'. After a 'b', we're stuck in state 2.
+        let input: DFA<u32, char> = DFA::new(
+            0,
+            vec![1],
+            vec![
+                (0, 'a', 1),
+                (0, 'b', 2),
+                (1, 'a', 1),
+                (1, 'b', 1),
+                (2, 'a', 2),
+                (2, 'b', 2),
+            ],
+        );
+        assert_eq!(
+            input.dead_states(),
+            vec![2].into_iter().collect::<HashSet<u32>>()
+        );
+        assert_eq!(
+            input.trap_states(),
+            vec![1, 2].into_iter().collect::<HashSet<u32>>()
+        );
+        assert_eq!(input.dead_state_witness(), Some((2, vec!['b'])));
+
+        let live: DFA<u32, char> = DFA::new(0, vec![1], vec![(0, 'a', 1), (1, 'a', 0)]);
+        assert!(live.dead_states().is_empty());
+        assert!(live.trap_states().is_empty());
+        assert_eq!(live.dead_state_witness(), None);
+    }
+
+    #[test]
+    fn lasso_of_infinite_language() {
+        // Accepts x(ab)*c, with a dead-end cycle on 'z' that never leads to acceptance.
+        let input: DFA<u32, char> = DFA::new(
+            0,
+            vec![3],
+            vec![
+                (0, 'x', 1),
+                (1, 'a', 2),
+                (2, 'b', 1),
+                (1, 'c', 3),
+                (0, 'z', 4),
+                (4, 'z', 4),
+            ],
+        );
+        let lasso = input.lasso().unwrap();
+        assert_eq!(lasso.prefix, vec!['x']);
+        assert_eq!(lasso.cycle, vec!['a', 'b']);
+        assert_eq!(lasso.suffix, vec!['c']);
+    }
+
+    #[test]
+    fn pumping_decomposition_of_long_word() {
+        // Accepts x(ab)*c.
+        let input: DFA<u32, char> = DFA::new(
+            0,
+            vec![3],
+            vec![(0, 'x', 1), (1, 'a', 2), (2, 'b', 1), (1, 'c', 3)],
+        );
+        let word: Vec<char> = "xababc".chars().collect();
+        let pumping = input.pumping_decomposition(&word).unwrap();
+        assert_eq!(pumping.x, vec!['x']);
+        assert_eq!(pumping.y, vec!['a', 'b']);
+        assert_eq!(pumping.z, vec!['a', 'b', 'c']);
+        assert_eq!(pumping.loop_states, vec![1, 2]);
+
+        assert_eq!(input.pumping_decomposition(&['x', 'c']), None);
+        assert_eq!(input.pumping_decomposition(&['x', 'a']), None);
+    }
+
+    #[test]
+    fn depths_from_initial_state() {
+        let input: DFA<u32, char> = DFA::new(
+            0,
+            vec![3],
+            vec![
+                (0, 'x', 1),
+                (1, 'a', 2),
+                (2, 'b', 1),
+                (1, 'c', 3),
+                (5, 'c', 3),
+            ],
+        );
+        let depths = input.depths();
+        assert_eq!(depths.len(), 4);
+        assert_eq!(depths[&0], 0);
+        assert_eq!(depths[&1], 1);
+        assert_eq!(depths[&2], 2);
+        assert_eq!(depths[&3], 2);
+        assert!(!depths.contains_key(&5));
+    }
+
+    #[test]
+    fn stats_summary() {
+        let input: DFA<u32, char> = DFA::new(
+            0,
+            vec![3],
+            vec![(0, 'x', 1), (1, 'a', 2), (2, 'b', 1), (1, 'c', 3)],
+        );
+        let stats = input.stats();
+        assert_eq!(stats.states, 4);
+        assert_eq!(stats.transitions, 4);
+        assert_eq!(stats.alphabet_size, 4);
+        assert_eq!(stats.completeness, 0.25);
+        assert_eq!(stats.accepting_states, 1);
+        assert_eq!(stats.diameter, 2);
+        assert_eq!(stats.sccs, 3);
+    }
+
+    #[test]
+    fn lasso_of_finite_language() {
+        let input: DFA<u32, char> =
+            DFA::new(0, vec![1], vec![(0, 'a', 1), (1, 'b', 2), (2, 'b', 2)]);
+        assert_eq!(input.lasso(), None);
+    }
+}
@@ -0,0 +1,259 @@
+use std::collections::VecDeque;
+use std::fmt::Debug;
+use std::hash::Hash;
+
+use crate::hash::{HashMap, HashSet, IntoStd};
+use crate::DFA;
+
+// The metadata of a state of a product: the metadata of each half, if any.
+type Both<M, M2> = (Option<M>, Option<M2>);
+
+// A DFA carrying optional metadata on some of its states, which is kept in step with the states
+// through pruning, minimization and products.
+#[derive(Debug, Clone)]
+pub struct AnnotatedDFA<S, E, M> {
+    dfa: DFA<S, E>,
+    metadata: HashMap<S, M>,
+}
+
+impl<S, E, M> AnnotatedDFA<S, E, M>
+where
+    S: Eq + Hash + Copy + Debug,
+    E: Eq + Hash + Copy + Debug,
+    M: Clone,
+{
+    pub fn new(
+        dfa: DFA<S, E>,
+        metadata: impl IntoIterator<Item = (S, M)>,
+    ) -> AnnotatedDFA<S, E, M> {
+        AnnotatedDFA {
+            dfa,
+            metadata: metadata.into_iter().collect(),
+        }
+    }
+
+    pub fn dfa(&self) -> &DFA<S, E> {
+        &self.dfa
+    }
+
+    pub fn metadata(&self, state: S) -> Option<&M> {
+        self.metadata.get(&state)
+    }
+
+    pub fn into_parts(self) -> (DFA<S, E>, std::collections::HashMap<S, M>) {
+        (self.dfa, self.metadata.into_std())
+    }
+
+    // Prunes the DFA as `DFA::prune_unreachable` does. The metadata of removed states is dropped.
+    pub fn prune_unreachable(self) -> Option<AnnotatedDFA<S, E, M>> {
+        let dfa = self.dfa.prune_unreachable()?;
+        let states = dfa.states();
+        let mut metadata = self.metadata;
+        metadata.retain(|q, _| states.contains(q));
+        Some(AnnotatedDFA { dfa, metadata })
+    }
+
+    // Minimizes the DFA as `DFA::minimize` does. When several annotated states merge, their
+    // metadata is combined with `merge`, in no particular order. To keep differently annotated
+    // states apart instead, use `DFA::minimize_by` with the metadata as the tag.
+    pub fn minimize(&self, merge: impl Fn(&M, &M) -> M) -> AnnotatedDFA<S, E, M> {
+        let (dfa, classes) = self.dfa.minimize_with_classes(|_| (), |set| set[0]);
+        let mut metadata: HashMap<S, M> = HashMap::default();
+        for (q, m) in &self.metadata {
+            if let Some(&rep) = classes.get(q) {
+                let merged = match metadata.get(&rep) {
+                    Some(existing) => merge(existing, m),
+                    None => m.clone(),
+                };
+                metadata.insert(rep, merged);
+            }
+        }
+        AnnotatedDFA { dfa, metadata }
+    }
+
+    // The intersection of two annotated DFAs, whose states are pairs of states. Each pair is
+    // annotated with the metadata of both halves, if either half has any.
+    pub fn intersection<S2, M2>(
+        &self,
+        other: &AnnotatedDFA<S2, E, M2>,
+    ) -> AnnotatedDFA<(S, S2), E, Both<M, M2>>
+    where
+        S2: Eq + Hash + Copy + Debug,
+        M2: Clone,
+    {
+        let lhs = self.dfa.transitions.by_a();
+        let rhs = other.dfa.delta();
+
+        let start = (self.dfa.initial_state, other.dfa.initial_state);
+        let mut seen: HashSet<(S, S2)> = HashSet::default();
+        let mut queue = VecDeque::new();
+        seen.insert(start);
+        queue.push_back(start);
+        let mut transitions = Vec::new();
+        while let Some((p, q)) = queue.pop_front() {
+            for &(label, p2) in lhs.get(&p).into_iter().flatten() {
+                if let Some(&q2) = rhs.get(&(q, label)) {
+                    if seen.insert((p2, q2)) {
+                        queue.push_back((p2, q2));
+                    }
+                    transitions.push(((p, q), label, (p2, q2)));
+                }
+            }
+        }
+        let final_states = seen.iter().copied().filter(|(p, q)| {
+            self.dfa.final_states.contains(p) && other.dfa.final_states.contains(q)
+        });
+        let metadata = seen.iter().filter_map(|&(p, q)| {
+            let pair = (
+                self.metadata.get(&p).cloned(),
+                other.metadata.get(&q).cloned(),
+            );
+            match pair {
+                (None, None) => None,
+                _ => Some(((p, q), pair)),
+            }
+        });
+        AnnotatedDFA::new(DFA::new(start, final_states, transitions), metadata)
+    }
+
+    // The product of two annotated DFAs, built as `DFA::product` builds it with `accept`, with
+    // each state annotated by `combine` applied to the metadata of its two halves. A half's
+    // metadata is `None` where that DFA has no state, or no metadata on it.
+    pub fn product<S2, M2, M3>(
+        &self,
+        other: &AnnotatedDFA<S2, E, M2>,
+        accept: impl Fn(bool, bool) -> bool,
+        combine: impl Fn(Option<&M>, Option<&M2>) -> Option<M3>,
+    ) -> AnnotatedDFA<u32, E, M3>
+    where
+        S2: Eq + Hash + Copy + Debug,
+        M2: Clone,
+        M3: Clone,
+    {
+        let (dfa, pairs) = self.dfa.product_pairs(&other.dfa, accept);
+        let metadata = pairs.into_iter().enumerate().filter_map(|(i, (p, q))| {
+            let lhs = p.and_then(|p| self.metadata.get(&p));
+            let rhs = q.and_then(|q| other.metadata.get(&q));
+            combine(lhs, rhs).map(|m| (i as u32, m))
+        });
+        AnnotatedDFA::new(dfa, metadata)
+    }
+
+    // The union of two annotated DFAs, with states numbered as `DFA::product` numbers them. Each
+    // state is annotated with the metadata of both halves, as in `intersection`.
+    pub fn union<S2, M2>(
+        &self,
+        other: &AnnotatedDFA<S2, E, M2>,
+    ) -> AnnotatedDFA<u32, E, Both<M, M2>>
+    where
+        S2: Eq + Hash + Copy + Debug,
+        M2: Clone,
+    {
+        self.product(other, |a, b| a || b, both)
+    }
+
+    // The words `self` accepts and `other` doesn't, annotated as in `union`.
+    pub fn difference<S2, M2>(
+        &self,
+        other: &AnnotatedDFA<S2, E, M2>,
+    ) -> AnnotatedDFA<u32, E, Both<M, M2>>
+    where
+        S2: Eq + Hash + Copy + Debug,
+        M2: Clone,
+    {
+        self.product(other, |a, b| a && !b, both)
+    }
+}
+
+// Pairs up the metadata of both halves of a product state, if either has any.
+fn both<M: Clone, M2: Clone>(lhs: Option<&M>, rhs: Option<&M2>) -> Option<Both<M, M2>> {
+    match (lhs, rhs) {
+        (None, None) => None,
+        _ => Some((lhs.cloned(), rhs.cloned())),
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::AnnotatedDFA;
+    use crate::DFA;
+
+    #[test]
+    fn metadata_follows_states() {
+        // Accepts "ab" or "cb", through two equivalent middle states and an unreachable one.
+        let dfa: DFA<u32, char> = DFA::new(
+            0,
+            vec![3],
+            vec![
+                (0, 'a', 1),
+                (0, 'c', 2),
+                (1, 'b', 3),
+                (2, 'b', 3),
+                (9, 'b', 3),
+            ],
+        );
+        let annotated = AnnotatedDFA::new(
+            dfa,
+            vec![
+                (1, vec!["saw a"]),
+                (2, vec!["saw c"]),
+                (3, vec!["done"]),
+                (9, vec!["lost"]),
+            ],
+        );
+        let pruned = annotated.prune_unreachable().unwrap();
+        assert!(pruned.metadata(9).is_none());
+
+        let minimal = pruned.minimize(|a, b| {
+            let mut merged = [a.as_slice(), b].concat();
+            merged.sort_unstable();
+            merged
+        });
+        let (dfa, metadata) = minimal.into_parts();
+        assert_eq!(dfa.states().len(), 3);
+        assert!(metadata.values().any(|m| *m == vec!["saw a", "saw c"]));
+    }
+
+    #[test]
+    fn intersection_pairs_metadata() {
+        let ab: DFA<u32, char> = DFA::new(0, vec![2], vec![(0, 'a', 1), (1, 'b', 2)]);
+        let a_star_b: DFA<u32, char> = DFA::new(0, vec![1], vec![(0, 'a', 0), (0, 'b', 1)]);
+        let lhs = AnnotatedDFA::new(ab, vec![(2, "lhs accepts")]);
+        let rhs = AnnotatedDFA::new(a_star_b, vec![(1, 7)]);
+        let both = lhs.intersection(&rhs);
+        assert!(both.dfa().accepts(&['a', 'b']));
+        assert_eq!(both.metadata((2, 1)), Some(&(Some("lhs accepts"), Some(7))));
+        assert_eq!(both.metadata((0, 0)), None);
+    }
+
+    #[test]
+    fn union_and_difference_pair_metadata() {
+        let ab: DFA<u32, char> = DFA::new(0, vec![2], vec![(0, 'a', 1), (1, 'b', 2)]);
+        let a_star_b: DFA<u32, char> = DFA::new(0, vec![1], vec![(0, 'a', 0), (0, 'b', 1)]);
+        let lhs = AnnotatedDFA::new(ab, vec![(2, "lhs accepts")]);
+        let rhs = AnnotatedDFA::new(a_star_b, vec![(1, 7)]);
+        let end = |dfa: &DFA<u32, char>, word: &[char]| {
+            let delta = dfa.delta();
+            word.iter()
+                .fold(dfa.initial_state(), |q, &label| delta[&(q, label)])
+        };
+
+        let either = lhs.union(&rhs);
+        assert!(either.dfa().accepts(&['b']) && either.dfa().accepts(&['a', 'a', 'b']));
+        assert_eq!(
+            either.metadata(end(either.dfa(), &['a', 'b'])),
+            Some(&(Some("lhs accepts"), Some(7)))
+        );
+        // Only the right half has a state after "b".
+        assert_eq!(
+            either.metadata(end(either.dfa(), &['b'])),
+            Some(&(None, Some(7)))
+        );
+        assert_eq!(either.metadata(either.dfa().initial_state()), None);
+
+        // Every word of `ab` is also in `a_star_b`.
+        assert!(lhs.difference(&rhs).prune_unreachable().is_none());
+        let only_rhs = rhs.difference(&lhs);
+        assert!(only_rhs.dfa().accepts(&['b']) && !only_rhs.dfa().accepts(&['a', 'b']));
+    }
+}
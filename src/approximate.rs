@@ -0,0 +1,226 @@
+// Shrinking a DFA further than minimization can, at the cost of changing its language a little.
+// Two states are k-equivalent when they accept the same words of length up to k, and merging
+// k-equivalent states only changes what happens to longer words. DFAs learned from data often
+// have many states that differ only on long, rare words, so merging with a small k can remove most
+// of them while misclassifying few words.
+
+use std::fmt::Debug;
+use std::hash::Hash;
+
+use crate::hash::{HashMap, HashSet};
+use crate::DFA;
+
+// The result of `DFA::approximate_minimize`.
+#[derive(Debug, Clone)]
+pub struct Approximation<S, E> {
+    pub dfa: DFA<S, E>,
+    // The states that were merged accept the same words up to this length.
+    pub depth: usize,
+    // The language error, as measured by `language_error`.
+    pub error: f64,
+}
+
+impl<S, E> DFA<S, E>
+where
+    S: Eq + Hash + Copy + Debug,
+    E: Eq + Hash + Copy + Debug,
+{
+    // The fraction of words of each length up to `max_len`, over the labels of both DFAs and
+    // averaged over the lengths, that exactly one of `self` and `other` accepts.
+    pub fn language_error<S2>(&self, other: &DFA<S2, E>, max_len: usize) -> f64
+    where
+        S2: Eq + Hash + Copy + Debug,
+    {
+        let lhs = self.delta();
+        let rhs = other.delta();
+        let alphabet: Vec<E> = self
+            .transitions()
+            .map(|(_, label, _)| label)
+            .chain(other.transitions().map(|(_, label, _)| label))
+            .collect::<HashSet<E>>()
+            .into_iter()
+            .collect();
+        if alphabet.is_empty() {
+            let differs =
+                self.is_accepting(self.initial_state) != other.is_accepting(other.initial_state);
+            return f64::from(u8::from(differs)) / (max_len + 1) as f64;
+        }
+
+        // The probability of being in each pair of states after a uniformly random word, where
+        // `None` stands for a rejecting sink. Pairs where both are stuck can't differ any more.
+        let mut mass: HashMap<(Option<S>, Option<S2>), f64> = HashMap::default();
+        mass.insert((Some(self.initial_state), Some(other.initial_state)), 1.0);
+        let share = 1.0 / alphabet.len() as f64;
+        let mut total = 0.0;
+        for n in 0..=max_len {
+            total += mass
+                .iter()
+                .filter(|((p, q), _)| {
+                    p.is_some_and(|p| self.is_accepting(p))
+                        != q.is_some_and(|q| other.is_accepting(q))
+                })
+                .map(|(_, m)| m)
+                .sum::<f64>();
+            if n == max_len {
+                break;
+            }
+            let mut next: HashMap<(Option<S>, Option<S2>), f64> = HashMap::default();
+            for (&(p, q), &m) in &mass {
+                for &label in &alphabet {
+                    let pair = (
+                        p.and_then(|p| lhs.get(&(p, label)).copied()),
+                        q.and_then(|q| rhs.get(&(q, label)).copied()),
+                    );
+                    if pair != (None, None) {
+                        *next.entry(pair).or_default() += m * share;
+                    }
+                }
+            }
+            mass = next;
+        }
+        total / (max_len + 1) as f64
+    }
+}
+
+impl<S, E> DFA<S, E>
+where
+    S: Eq + Hash + Copy + Debug + Ord,
+    E: Eq + Hash + Copy + Debug + Ord,
+{
+    // Merges every set of states that accept the same words of length up to `depth`, and then
+    // prunes and minimizes. Each merged state keeps the transitions of its smallest member. Once
+    // `depth` reaches the number of states, this is plain minimization.
+    pub fn merge_k_equivalent(&self, depth: usize) -> DFA<S, E> {
+        let delta = self.delta();
+        let live = self.coaccessible();
+        let mut states: Vec<S> = self.states().into_iter().collect();
+        states.sort_unstable();
+        let mut labels: Vec<E> = self.transitions().map(|(_, label, _)| label).collect();
+        labels.sort_unstable();
+        labels.dedup();
+
+        // A transition into a dead state is no different from a missing one, as in `minimize`.
+        let number = |keys: Vec<(usize, Vec<Option<usize>>)>| -> (Vec<usize>, usize) {
+            let mut ids: HashMap<(usize, Vec<Option<usize>>), usize> = HashMap::default();
+            let class = keys
+                .into_iter()
+                .map(|key| {
+                    let n = ids.len();
+                    *ids.entry(key).or_insert(n)
+                })
+                .collect();
+            (class, ids.len())
+        };
+        let index: HashMap<S, usize> = states.iter().enumerate().map(|(i, &q)| (q, i)).collect();
+        let (mut class, mut count) = number(
+            states
+                .iter()
+                .map(|&q| {
+                    let key =
+                        2 * usize::from(self.is_accepting(q)) + usize::from(live.contains(&q));
+                    (key, Vec::new())
+                })
+                .collect(),
+        );
+        for _ in 0..depth {
+            let (next, next_count) = number(
+                states
+                    .iter()
+                    .map(|q| {
+                        let successors = labels
+                            .iter()
+                            .map(|&label| {
+                                delta
+                                    .get(&(*q, label))
+                                    .filter(|dst| live.contains(dst))
+                                    .map(|dst| class[index[dst]])
+                            })
+                            .collect();
+                        (class[index[q]], successors)
+                    })
+                    .collect(),
+            );
+            let stable = next_count == count;
+            class = next;
+            count = next_count;
+            if stable {
+                break;
+            }
+        }
+
+        // States come in increasing order, so the first member of each class is its smallest.
+        let mut reps: Vec<Option<S>> = vec![None; count];
+        for (i, &q) in states.iter().enumerate() {
+            reps[class[i]].get_or_insert(q);
+        }
+        let rep = |q: &S| reps[class[index[q]]].unwrap();
+        let quotient = DFA::with_states(
+            rep(&self.initial_state),
+            reps.iter().flatten().copied(),
+            self.final_states.iter().map(rep),
+            self.transitions()
+                .filter(|(src, _, _)| rep(src) == *src)
+                .map(|(src, label, dst)| (src, label, rep(&dst))),
+        );
+        quotient.prune_and_minimize()
+    }
+
+    // Finds the smallest `merge_k_equivalent` approximation whose `language_error` over words up
+    // to `max_len` is at most `budget`, trying each depth in turn. With a budget of 0 the result
+    // may still differ on words longer than `max_len`.
+    pub fn approximate_minimize(&self, budget: f64, max_len: usize) -> Approximation<S, E> {
+        let mut depth = 0;
+        loop {
+            let dfa = self.merge_k_equivalent(depth);
+            let error = self.language_error(&dfa, max_len);
+            // Depths beyond the number of states change nothing.
+            if error <= budget || depth >= self.state_count() {
+                return Approximation { dfa, depth, error };
+            }
+            depth += 1;
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::DFA;
+
+    #[test]
+    fn approximate_minimize() {
+        // Every word of a's except the one of length 10, which takes 12 states.
+        let transitions = (0..11).map(|q| (q, 'a', q + 1)).chain(Some((11, 'a', 11)));
+        let input: DFA<u32, char> = DFA::new(0, (0..12).filter(|&q| q != 10), transitions);
+        assert_eq!(input.minimize().state_count(), 12);
+
+        // Forgetting the exception gets a*, which is wrong about 1 length in 31.
+        let rough = input.approximate_minimize(0.1, 30);
+        assert_eq!(rough.depth, 0);
+        assert_eq!(rough.dfa.state_count(), 1);
+        assert!((rough.error - 1.0 / 31.0).abs() < 1e-12);
+
+        // Up to length 20, it is enough to send the last state back to the first.
+        let looped = input.approximate_minimize(0.0, 20);
+        assert_eq!(looped.dfa.state_count(), 11);
+        assert!(looped.dfa.accepts(&['a'; 11]) && !looped.dfa.accepts(&['a'; 21]));
+
+        let exact = input.approximate_minimize(0.0, 30);
+        assert_eq!(exact.error, 0.0);
+        assert!(exact.dfa.language_eq(&input));
+        assert_eq!(exact.dfa.state_count(), 12);
+    }
+
+    #[test]
+    fn language_error() {
+        // Words over {a, b} ending in a, and all words.
+        let ends_a: DFA<u32, char> = DFA::new(
+            0,
+            vec![1],
+            vec![(0, 'a', 1), (0, 'b', 0), (1, 'a', 1), (1, 'b', 0)],
+        );
+        let all: DFA<u32, char> = DFA::new(0, vec![0], vec![(0, 'a', 0), (0, 'b', 0)]);
+        // They differ on the empty word, and then on half the words of each length.
+        assert!((ends_a.language_error(&all, 3) - 2.5 / 4.0).abs() < 1e-12);
+        assert_eq!(all.language_error(&all, 3), 0.0);
+    }
+}
@@ -0,0 +1,186 @@
+use std::rc::Rc;
+
+use crate::hash::HashMap;
+use crate::sfa::Predicate;
+
+// A set of `u32` symbols, as a reduced ordered binary decision diagram over their bits. Bit 0
+// (the least significant) is tested first. Because the diagram is reduced, two `Bdd`s are equal
+// exactly when they describe the same set. Sets like "every even 16-bit value" or "every value
+// with bit 7 set" stay tiny here, while listing them out would take thousands of intervals.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum Bdd {
+    False,
+    True,
+    // Tests bit `var`, continuing with the first diagram if it is clear and the second if it is
+    // set. Both branches only test later bits.
+    Node(Rc<(u8, Bdd, Bdd)>),
+}
+
+impl Bdd {
+    // The symbols with bit `var` set.
+    pub fn bit(var: u8) -> Bdd {
+        assert!(var < 32, "symbols only have 32 bits");
+        Bdd::Node(Rc::new((var, Bdd::False, Bdd::True)))
+    }
+
+    // The symbols whose lowest `width` bits spell out `value`.
+    pub fn low_bits(value: u32, width: u8) -> Bdd {
+        (0..width).rev().fold(Bdd::True, |rest, var| {
+            if value >> var & 1 == 1 {
+                Bdd::node(var, Bdd::False, rest)
+            } else {
+                Bdd::node(var, rest, Bdd::False)
+            }
+        })
+    }
+
+    // The number of decision nodes, counting shared nodes once.
+    pub fn node_count(&self) -> usize {
+        let mut seen = crate::hash::HashSet::default();
+        let mut stack = vec![self];
+        while let Some(bdd) = stack.pop() {
+            if let Bdd::Node(node) = bdd {
+                if seen.insert(Rc::as_ptr(node)) {
+                    stack.push(&node.1);
+                    stack.push(&node.2);
+                }
+            }
+        }
+        seen.len()
+    }
+
+    fn node(var: u8, lo: Bdd, hi: Bdd) -> Bdd {
+        if lo == hi {
+            lo
+        } else {
+            Bdd::Node(Rc::new((var, lo, hi)))
+        }
+    }
+
+    // Combines two diagrams pointwise with `op`, memoizing on the identity of the subdiagrams so
+    // that shared structure is only visited once.
+    fn apply(&self, other: &Bdd, op: fn(bool, bool) -> bool) -> Bdd {
+        fn go(
+            a: &Bdd,
+            b: &Bdd,
+            op: fn(bool, bool) -> bool,
+            memo: &mut HashMap<(usize, usize), Bdd>,
+        ) -> Bdd {
+            let var = match (a, b) {
+                (Bdd::Node(x), Bdd::Node(y)) => x.0.min(y.0),
+                (Bdd::Node(x), _) | (_, Bdd::Node(x)) => x.0,
+                _ => {
+                    let result = op(*a == Bdd::True, *b == Bdd::True);
+                    return if result { Bdd::True } else { Bdd::False };
+                }
+            };
+            let key = (a.id(), b.id());
+            if let Some(result) = memo.get(&key) {
+                return result.clone();
+            }
+            let (a_lo, a_hi) = a.branches(var);
+            let (b_lo, b_hi) = b.branches(var);
+            let lo = go(a_lo, b_lo, op, memo);
+            let hi = go(a_hi, b_hi, op, memo);
+            let result = Bdd::node(var, lo, hi);
+            memo.insert(key, result.clone());
+            result
+        }
+        go(self, other, op, &mut HashMap::default())
+    }
+
+    // The diagrams to follow when bit `var` is clear and set. A diagram that doesn't test `var`
+    // at its root goes the same way either way.
+    fn branches(&self, var: u8) -> (&Bdd, &Bdd) {
+        match self {
+            Bdd::Node(node) if node.0 == var => (&node.1, &node.2),
+            _ => (self, self),
+        }
+    }
+
+    fn id(&self) -> usize {
+        match self {
+            Bdd::False => 0,
+            Bdd::True => 1,
+            Bdd::Node(node) => Rc::as_ptr(node) as usize,
+        }
+    }
+}
+
+impl Predicate for Bdd {
+    type Symbol = u32;
+
+    fn top() -> Self {
+        Bdd::True
+    }
+    fn bottom() -> Self {
+        Bdd::False
+    }
+    fn and(&self, other: &Self) -> Self {
+        self.apply(other, |a, b| a && b)
+    }
+    fn or(&self, other: &Self) -> Self {
+        self.apply(other, |a, b| a || b)
+    }
+    fn not(&self) -> Self {
+        self.apply(&Bdd::True, |a, _| !a)
+    }
+    fn contains(&self, &symbol: &u32) -> bool {
+        let mut bdd = self;
+        while let Bdd::Node(node) = bdd {
+            bdd = if symbol >> node.0 & 1 == 1 {
+                &node.2
+            } else {
+                &node.1
+            };
+        }
+        *bdd == Bdd::True
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::Bdd;
+    use crate::sfa::{Predicate, SFA};
+
+    #[test]
+    fn boolean_algebra() {
+        let odd = Bdd::bit(0);
+        let even = odd.not();
+        assert_eq!(odd.or(&even), Bdd::True);
+        assert_eq!(odd.and(&even), Bdd::False);
+        // The same set built two different ways is the same diagram.
+        let high = Bdd::bit(15);
+        assert_eq!(odd.and(&high).not(), even.or(&high.not()));
+
+        let letter_a = Bdd::low_bits(0x61, 16);
+        assert!(letter_a.contains(&0x61));
+        // Higher bits are unconstrained.
+        assert!(letter_a.contains(&0x10061));
+        assert!(!letter_a.contains(&0x62));
+        assert_eq!(letter_a.node_count(), 16);
+    }
+
+    #[test]
+    fn symbolic_product() {
+        // Even 16-bit symbols, then symbols with the top bit set.
+        let even_then_high: SFA<u32, Bdd> = SFA::new(
+            0,
+            vec![2],
+            vec![(0, Bdd::bit(0).not(), 1), (1, Bdd::bit(15), 2)],
+        );
+        // Any symbol below 256, repeated.
+        let small = (8..32)
+            .map(|var| Bdd::bit(var).not())
+            .fold(Bdd::True, |a, b| a.and(&b));
+        let smalls: SFA<u32, Bdd> = SFA::new(0, vec![0], vec![(0, small, 0)]);
+
+        assert!(even_then_high.accepts(&[4, 0x8001]));
+        let both = even_then_high.intersection(&smalls);
+        assert!(!both.accepts(&[4, 0x8001]));
+        let either = even_then_high.union(&smalls).minimize();
+        assert!(either.accepts(&[4, 0x8001]));
+        assert!(either.accepts(&[3, 5, 255]));
+        assert!(!either.accepts(&[3, 0x8001]));
+    }
+}
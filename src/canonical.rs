@@ -0,0 +1,232 @@
+use std::collections::{hash_map::Entry, VecDeque};
+use std::fmt::Debug;
+use std::hash::{Hash, Hasher};
+
+use crate::hash::{HashMap, IntoStd};
+use crate::DFA;
+
+impl<S, E> DFA<S, E>
+where
+    S: Eq + Hash + Copy + Debug,
+    E: Eq + Hash + Copy + Debug,
+{
+    // Checks whether `other` is literally the same machine as this one, up to renaming states,
+    // and if so returns the renaming. This is stronger than accepting the same language.
+    // Since the renaming is found by walking both machines from their initial states, DFAs with
+    // unreachable states are never considered isomorphic; prune them first.
+    pub fn isomorphic<S2>(&self, other: &DFA<S2, E>) -> Option<std::collections::HashMap<S, S2>>
+    where
+        S2: Eq + Hash + Copy + Debug,
+    {
+        let lhs_outflows = self.transitions.by_a();
+        let rhs_outflows = other.transitions.by_a();
+
+        let mut forward: HashMap<S, S2> = HashMap::default();
+        let mut backward: HashMap<S2, S> = HashMap::default();
+        let mut queue = VecDeque::new();
+        forward.insert(self.initial_state, other.initial_state);
+        backward.insert(other.initial_state, self.initial_state);
+        queue.push_back((self.initial_state, other.initial_state));
+        while let Some((lhs, rhs)) = queue.pop_front() {
+            if self.final_states.contains(&lhs) != other.final_states.contains(&rhs) {
+                return None;
+            }
+            let lhs_edges: HashMap<E, S> = lhs_outflows
+                .get(&lhs)
+                .into_iter()
+                .flatten()
+                .copied()
+                .collect();
+            let rhs_edges: HashMap<E, S2> = rhs_outflows
+                .get(&rhs)
+                .into_iter()
+                .flatten()
+                .copied()
+                .collect();
+            if lhs_edges.len() != rhs_edges.len() {
+                return None;
+            }
+            for (label, lhs_dst) in lhs_edges {
+                let rhs_dst = *rhs_edges.get(&label)?;
+                match (forward.entry(lhs_dst), backward.entry(rhs_dst)) {
+                    (Entry::Occupied(f), Entry::Occupied(b)) => {
+                        if *f.get() != rhs_dst || *b.get() != lhs_dst {
+                            return None;
+                        }
+                    }
+                    (Entry::Vacant(f), Entry::Vacant(b)) => {
+                        f.insert(rhs_dst);
+                        b.insert(lhs_dst);
+                        queue.push_back((lhs_dst, rhs_dst));
+                    }
+                    _ => return None,
+                }
+            }
+        }
+
+        if forward.len() != self.states().len() || backward.len() != other.states().len() {
+            return None;
+        }
+        Some(forward.into_std())
+    }
+}
+
+impl<S, E> DFA<S, E>
+where
+    S: Eq + Hash + Copy + Debug,
+    E: Eq + Hash + Copy + Debug + Ord,
+{
+    // Renumbers the states reachable from the initial state in BFS order, visiting each state's
+    // transitions in increasing order of label. Transitions are sorted by source, then label.
+    // Two isomorphic DFAs (e.g., two minimal DFAs for the same language) always produce exactly
+    // the same result. Unreachable states are dropped.
+    pub fn canonicalize(&self) -> DFA<u32, E> {
+        let mut outflows = self.transitions.by_a();
+        for edges in outflows.values_mut() {
+            edges.sort_by_key(|&(label, _)| label);
+        }
+
+        let mut ids: HashMap<S, u32> = HashMap::default();
+        let mut queue = VecDeque::new();
+        let mut transitions = Vec::new();
+        ids.insert(self.initial_state, 0);
+        queue.push_back(self.initial_state);
+        while let Some(src) = queue.pop_front() {
+            let id = ids[&src];
+            for &(label, dst) in outflows.get(&src).into_iter().flatten() {
+                let n = ids.len() as u32;
+                let dst = *ids.entry(dst).or_insert_with(|| {
+                    queue.push_back(dst);
+                    n
+                });
+                transitions.push((id, label, dst));
+            }
+        }
+        DFA::new(
+            0,
+            self.final_states.iter().filter_map(|q| ids.get(q).copied()),
+            transitions,
+        )
+    }
+
+    // Fingerprints the language accepted by this DFA by hashing its canonical minimal DFA. DFAs
+    // that accept the same language always have the same fingerprint, and different languages
+    // collide only by (64-bit) chance. The hash function is fixed, so fingerprints are stable
+    // across runs, as long as `E`'s `Hash` implementation is.
+    pub fn language_hash(&self) -> u64 {
+        let mut hasher = Fnv1a::default();
+        match self.clone().prune_unreachable() {
+            None => hasher.write_u8(0),
+            Some(pruned) => {
+                hasher.write_u8(1);
+                let canonical = pruned.minimize().canonicalize();
+                let mut final_states: Vec<u32> = canonical.final_states.into_iter().collect();
+                final_states.sort_unstable();
+                final_states.hash(&mut hasher);
+                for (src, label, dst) in canonical.transitions {
+                    src.hash(&mut hasher);
+                    label.hash(&mut hasher);
+                    dst.hash(&mut hasher);
+                }
+            }
+        }
+        hasher.finish()
+    }
+}
+
+// 64-bit FNV-1a. Unlike `DefaultHasher`, its output is specified and will not change.
+struct Fnv1a(u64);
+
+impl Default for Fnv1a {
+    fn default() -> Self {
+        Fnv1a(0xcbf2_9ce4_8422_2325)
+    }
+}
+
+impl Hasher for Fnv1a {
+    fn finish(&self) -> u64 {
+        self.0
+    }
+
+    fn write(&mut self, bytes: &[u8]) {
+        for &b in bytes {
+            self.0 ^= u64::from(b);
+            self.0 = self.0.wrapping_mul(0x0100_0000_01b3);
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::DFA;
+
+    #[test]
+    fn isomorphic_renaming() {
+        let lhs: DFA<u32, u8> =
+            DFA::new(0, vec![2], vec![(0, 0, 1), (0, 1, 2), (1, 0, 2), (2, 1, 2)]);
+        let rhs: DFA<char, u8> = DFA::new(
+            'x',
+            vec!['z'],
+            vec![('x', 0, 'y'), ('x', 1, 'z'), ('y', 0, 'z'), ('z', 1, 'z')],
+        );
+        let mapping = lhs.isomorphic(&rhs).unwrap();
+        assert_eq!(mapping[&0], 'x');
+        assert_eq!(mapping[&1], 'y');
+        assert_eq!(mapping[&2], 'z');
+    }
+
+    #[test]
+    fn canonicalize_equivalent_minimal_dfas() {
+        let lhs: DFA<u32, u8> =
+            DFA::new(7, vec![3], vec![(7, 1, 3), (7, 0, 5), (5, 0, 3), (3, 1, 3)]);
+        let rhs: DFA<char, u8> = DFA::new(
+            'a',
+            vec!['c'],
+            vec![('c', 1, 'c'), ('b', 0, 'c'), ('a', 0, 'b'), ('a', 1, 'c')],
+        );
+        let lhs = lhs.canonicalize();
+        let rhs = rhs.canonicalize();
+        assert_eq!(
+            format!("{:?}", lhs.transitions),
+            format!("{:?}", rhs.transitions)
+        );
+        assert_eq!(
+            lhs.transitions.clone().into_iter().collect::<Vec<_>>(),
+            vec![(0, 0, 1), (0, 1, 2), (1, 0, 2), (2, 1, 2)]
+        );
+        assert_eq!(lhs.final_states, rhs.final_states);
+    }
+
+    #[test]
+    fn language_hash_ignores_representation() {
+        // Both accept (00)*, one with redundant states.
+        let lhs: DFA<u32, u8> = DFA::new(0, vec![0], vec![(0, 0, 1), (1, 0, 0)]);
+        let rhs: DFA<u32, u8> = DFA::new(
+            0,
+            vec![0, 2],
+            vec![(0, 0, 1), (1, 0, 2), (2, 0, 3), (3, 0, 0), (0, 1, 4)],
+        );
+        assert_eq!(lhs.language_hash(), rhs.language_hash());
+
+        let odd: DFA<u32, u8> = DFA::new(0, vec![1], vec![(0, 0, 1), (1, 0, 0)]);
+        assert_ne!(lhs.language_hash(), odd.language_hash());
+
+        let epsilon: DFA<u32, u8> = DFA::new(0, vec![0], vec![]);
+        let empty: DFA<u32, u8> = DFA::new(0, vec![], vec![]);
+        assert_ne!(epsilon.language_hash(), empty.language_hash());
+        assert_ne!(epsilon.language_hash(), lhs.language_hash());
+    }
+
+    #[test]
+    fn isomorphic_requires_same_structure() {
+        let lhs: DFA<u32, u8> = DFA::new(0, vec![0], vec![(0, 0, 0)]);
+        // Accepts the same language, but with a redundant state.
+        let rhs: DFA<u32, u8> = DFA::new(0, vec![0, 1], vec![(0, 0, 1), (1, 0, 0)]);
+        assert!(lhs.isomorphic(&rhs).is_none());
+        assert!(rhs.isomorphic(&lhs).is_none());
+        assert!(lhs.minimize().isomorphic(&rhs.minimize()).is_some());
+
+        let unreachable: DFA<u32, u8> = DFA::new(0, vec![0], vec![(0, 0, 0), (1, 0, 0)]);
+        assert!(lhs.isomorphic(&unreachable).is_none());
+    }
+}
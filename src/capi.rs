@@ -0,0 +1,222 @@
+// A C API over `DFA<u32, u32>`, declared in `include/dfa_utils.h`. DFAs are passed around as
+// opaque handles, which the caller owns and must release with `dfa_destroy`. Build a library to
+// link against with `cargo rustc --release --features capi --crate-type staticlib` (or
+// `cdylib`). `python/dfa_utils.py` wraps the `cdylib` for Python with ctypes.
+
+use std::{ptr, slice};
+
+use crate::hash::HashMap;
+use crate::DFA;
+
+pub struct DfaHandle(DFA<u32, u32>);
+
+// Reads `len` values from `ptr`, which may be null when `len` is 0.
+//
+// Safety: unless `len` is 0, `ptr` must point to `len` initialized values.
+unsafe fn values<'a>(ptr: *const u32, len: usize) -> &'a [u32] {
+    if len == 0 {
+        &[]
+    } else {
+        slice::from_raw_parts(ptr, len)
+    }
+}
+
+// Creates a DFA from its accepting states and its transitions, given as `n_transitions` triples
+// `src, label, dst` laid out one after another. Returns null if two transitions leave the same
+// state on the same label for different states, or if `3 * n_transitions` overflows.
+//
+// Safety: `final_states` must point to `n_final_states` values, and `transitions` to
+// `3 * n_transitions`.
+#[no_mangle]
+pub unsafe extern "C" fn dfa_create(
+    initial_state: u32,
+    final_states: *const u32,
+    n_final_states: usize,
+    transitions: *const u32,
+    n_transitions: usize,
+) -> *mut DfaHandle {
+    let n_values = match n_transitions.checked_mul(3) {
+        Some(n_values) => n_values,
+        None => return ptr::null_mut(),
+    };
+    let final_states = values(final_states, n_final_states);
+    let transitions = values(transitions, n_values);
+    let mut delta: HashMap<(u32, u32), u32> = HashMap::default();
+    for t in transitions.chunks(3) {
+        if *delta.entry((t[0], t[1])).or_insert(t[2]) != t[2] {
+            return ptr::null_mut();
+        }
+    }
+    let dfa = DFA::new(
+        initial_state,
+        final_states.iter().copied(),
+        transitions.chunks(3).map(|t| (t[0], t[1], t[2])),
+    );
+    Box::into_raw(Box::new(DfaHandle(dfa)))
+}
+
+// Safety: `dfa` must come from this API and not have been destroyed. Null is ignored.
+#[no_mangle]
+pub unsafe extern "C" fn dfa_destroy(dfa: *mut DfaHandle) {
+    if !dfa.is_null() {
+        drop(Box::from_raw(dfa));
+    }
+}
+
+// Returns a new DFA with unreachable and dead states pruned and equivalent states merged. Each
+// remaining state keeps the number of one of the states it stands for.
+//
+// Safety: `dfa` must be a live handle.
+#[no_mangle]
+pub unsafe extern "C" fn dfa_minimize_new(dfa: *const DfaHandle) -> *mut DfaHandle {
+    Box::into_raw(Box::new(DfaHandle((*dfa).0.prune_and_minimize())))
+}
+
+// Returns a new DFA accepting the words that either DFA accepts, with states numbered from 0.
+//
+// Safety: `lhs` and `rhs` must be live handles.
+#[no_mangle]
+pub unsafe extern "C" fn dfa_union_new(
+    lhs: *const DfaHandle,
+    rhs: *const DfaHandle,
+) -> *mut DfaHandle {
+    Box::into_raw(Box::new(DfaHandle((*lhs).0.union(&(*rhs).0))))
+}
+
+// Returns a new DFA accepting the words that both DFAs accept, with states numbered from 0.
+//
+// Safety: `lhs` and `rhs` must be live handles.
+#[no_mangle]
+pub unsafe extern "C" fn dfa_intersection_new(
+    lhs: *const DfaHandle,
+    rhs: *const DfaHandle,
+) -> *mut DfaHandle {
+    Box::into_raw(Box::new(DfaHandle((*lhs).0.intersection(&(*rhs).0))))
+}
+
+// Returns a new DFA accepting the words that `lhs` accepts and `rhs` doesn't, with states
+// numbered from 0.
+//
+// Safety: `lhs` and `rhs` must be live handles.
+#[no_mangle]
+pub unsafe extern "C" fn dfa_difference_new(
+    lhs: *const DfaHandle,
+    rhs: *const DfaHandle,
+) -> *mut DfaHandle {
+    Box::into_raw(Box::new(DfaHandle((*lhs).0.difference(&(*rhs).0))))
+}
+
+// Returns 1 if the word of `len` labels is accepted, and 0 otherwise.
+//
+// Safety: `dfa` must be a live handle, and `word` must point to `len` values.
+#[no_mangle]
+pub unsafe extern "C" fn dfa_run(dfa: *const DfaHandle, word: *const u32, len: usize) -> i32 {
+    i32::from((*dfa).0.accepts(values(word, len)))
+}
+
+// Safety: `dfa` must be a live handle.
+#[no_mangle]
+pub unsafe extern "C" fn dfa_initial_state(dfa: *const DfaHandle) -> u32 {
+    (*dfa).0.initial_state
+}
+
+// Returns 1 if `state` is accepting, and 0 otherwise.
+//
+// Safety: `dfa` must be a live handle.
+#[no_mangle]
+pub unsafe extern "C" fn dfa_is_accepting(dfa: *const DfaHandle, state: u32) -> i32 {
+    i32::from((*dfa).0.is_accepting(state))
+}
+
+// Copies up to `capacity` transitions into `out` as triples, sorted, and returns the total
+// number of transitions. Call it with a `capacity` of 0 to find out how much room is needed.
+//
+// Safety: `dfa` must be a live handle, and `out` must have room for `3 * capacity` values.
+#[no_mangle]
+pub unsafe extern "C" fn dfa_transitions(
+    dfa: *const DfaHandle,
+    out: *mut u32,
+    capacity: usize,
+) -> usize {
+    let mut transitions: Vec<(u32, u32, u32)> = (*dfa).0.transitions().collect();
+    transitions.sort_unstable();
+    for (i, &(src, label, dst)) in transitions.iter().take(capacity).enumerate() {
+        *out.add(3 * i) = src;
+        *out.add(3 * i + 1) = label;
+        *out.add(3 * i + 2) = dst;
+    }
+    transitions.len()
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn minimize_through_handles() {
+        // Accepts words over {0, 1} ending in 1, with a redundant copy of the accepting state.
+        let transitions = [0, 0, 0, 0, 1, 1, 1, 0, 0, 1, 1, 2, 2, 0, 0, 2, 1, 1];
+        let finals = [1, 2];
+        unsafe {
+            let dfa = dfa_create(0, finals.as_ptr(), 2, transitions.as_ptr(), 6);
+            let minimal = dfa_minimize_new(dfa);
+            dfa_destroy(dfa);
+
+            assert_eq!(dfa_run(minimal, [0, 1, 1].as_ptr(), 3), 1);
+            assert_eq!(dfa_run(minimal, [1, 0].as_ptr(), 2), 0);
+            assert_eq!(dfa_run(minimal, std::ptr::null(), 0), 0);
+            assert_eq!(dfa_initial_state(minimal), 0);
+
+            let n = dfa_transitions(minimal, std::ptr::null_mut(), 0);
+            assert_eq!(n, 4);
+            let mut out = vec![0; 3 * n];
+            dfa_transitions(minimal, out.as_mut_ptr(), n);
+            let accepting = out[5];
+            assert_eq!(&out[..6], &[0, 0, 0, 0, 1, accepting]);
+            assert_eq!(dfa_is_accepting(minimal, accepting), 1);
+            dfa_destroy(minimal);
+        }
+    }
+
+    #[test]
+    fn invalid_input() {
+        unsafe {
+            // Two transitions from 0 on label 1.
+            let nondeterministic = [0, 1, 1, 0, 1, 2];
+            let dfa = dfa_create(0, [1].as_ptr(), 1, nondeterministic.as_ptr(), 2);
+            assert!(dfa.is_null());
+            // The same transition twice is fine.
+            let repeated = [0, 1, 1, 0, 1, 1];
+            let dfa = dfa_create(0, [1].as_ptr(), 1, repeated.as_ptr(), 2);
+            assert_eq!(dfa_run(dfa, [1].as_ptr(), 1), 1);
+            dfa_destroy(dfa);
+            // Too many triples to address, rejected before anything is read.
+            let dfa = dfa_create(0, std::ptr::null(), 0, std::ptr::null(), usize::MAX / 2);
+            assert!(dfa.is_null());
+        }
+    }
+
+    #[test]
+    fn set_operations_through_handles() {
+        // "a" and "b", and "b" and "c".
+        let ab = [0, 0, 1, 0, 1, 1];
+        let bc = [0, 1, 1, 0, 2, 1];
+        unsafe {
+            let lhs = dfa_create(0, [1].as_ptr(), 1, ab.as_ptr(), 2);
+            let rhs = dfa_create(0, [1].as_ptr(), 1, bc.as_ptr(), 2);
+            let results = [
+                (dfa_union_new(lhs, rhs), [1, 1, 1]),
+                (dfa_intersection_new(lhs, rhs), [0, 1, 0]),
+                (dfa_difference_new(lhs, rhs), [1, 0, 0]),
+            ];
+            for (dfa, expected) in results {
+                for (label, &accepted) in expected.iter().enumerate() {
+                    assert_eq!(dfa_run(dfa, &(label as u32), 1), accepted);
+                }
+                dfa_destroy(dfa);
+            }
+            dfa_destroy(lhs);
+            dfa_destroy(rhs);
+        }
+    }
+}
@@ -0,0 +1,296 @@
+// Generating source code for byte-level DFAs, so that a matcher built with this crate can be
+// compiled into another program with no dependency on it. States are renumbered from 0 (the
+// initial state) as `canonicalize` does, and every missing transition goes to a dead state that
+// is numbered last.
+
+use std::fmt::{Debug, Write};
+use std::hash::Hash;
+
+use crate::DFA;
+
+// A DFA over bytes with its states numbered densely.
+pub(crate) struct Dense {
+    // Whether each state accepts, with the dead state last.
+    pub(crate) accepting: Vec<bool>,
+    // For each live state, the runs of bytes `(lo, hi, dst)` that lead to the same state, in
+    // order.
+    pub(crate) ranges: Vec<Vec<(u8, u8, u32)>>,
+}
+
+impl Dense {
+    pub(crate) fn dead_state(&self) -> u32 {
+        self.ranges.len() as u32
+    }
+
+    // The whole transition table, with a row of 256 entries for every state.
+    pub(crate) fn table(&self) -> Vec<u32> {
+        let dead = self.dead_state();
+        let mut table = vec![dead; 256 * self.accepting.len()];
+        for (src, ranges) in self.ranges.iter().enumerate() {
+            for &(lo, hi, dst) in ranges {
+                for b in lo..=hi {
+                    table[256 * src + b as usize] = dst;
+                }
+            }
+        }
+        table
+    }
+}
+
+impl<S> DFA<S, u8>
+where
+    S: Eq + Hash + Copy + Debug,
+{
+    pub(crate) fn dense(&self) -> Dense {
+        let canonical = self.canonicalize();
+        let n = canonical.state_count();
+        let mut ranges: Vec<Vec<(u8, u8, u32)>> = vec![Vec::new(); n];
+        // Transitions come sorted by source and then byte.
+        for (src, b, dst) in canonical.transitions() {
+            let runs = &mut ranges[src as usize];
+            match runs.last_mut() {
+                Some((_, hi, last)) if *last == dst && u16::from(*hi) + 1 == u16::from(b) => {
+                    *hi = b
+                }
+                _ => runs.push((b, b, dst)),
+            }
+        }
+        let mut accepting: Vec<bool> = (0..n as u32).map(|q| canonical.is_accepting(q)).collect();
+        accepting.push(false);
+        Dense { accepting, ranges }
+    }
+
+    // Rust source for a module named `module` with a `next_state` function, an `ACCEPTING`
+    // table, and an `is_match` function that runs the whole DFA over some bytes.
+    pub fn to_rust(&self, module: &str) -> String {
+        let dense = self.dense();
+        let mut out = String::new();
+        let n = dense.accepting.len();
+        writeln!(out, "// Generated by dfa-utils. Do not edit.").unwrap();
+        writeln!(out, "#[allow(dead_code)]").unwrap();
+        writeln!(out, "pub mod {} {{", module).unwrap();
+        writeln!(out, "    pub const INITIAL_STATE: u32 = 0;").unwrap();
+        writeln!(out, "    // Never accepting, and never left.").unwrap();
+        writeln!(
+            out,
+            "    pub const DEAD_STATE: u32 = {};",
+            dense.dead_state()
+        )
+        .unwrap();
+        writeln!(out).unwrap();
+        writeln!(out, "    pub static ACCEPTING: [bool; {}] = [", n).unwrap();
+        for chunk in dense.accepting.chunks(8) {
+            let row: Vec<String> = chunk.iter().map(bool::to_string).collect();
+            writeln!(out, "        {},", row.join(", ")).unwrap();
+        }
+        writeln!(out, "    ];").unwrap();
+        writeln!(out).unwrap();
+        writeln!(out, "    pub fn next_state(s: u32, b: u8) -> u32 {{").unwrap();
+        writeln!(out, "        match s {{").unwrap();
+        for (src, ranges) in dense.ranges.iter().enumerate() {
+            if ranges.is_empty() {
+                continue;
+            }
+            writeln!(out, "            {} => match b {{", src).unwrap();
+            for &(lo, hi, dst) in ranges {
+                let pattern = if lo == hi {
+                    rust_byte(lo)
+                } else {
+                    format!("{}..={}", rust_byte(lo), rust_byte(hi))
+                };
+                writeln!(out, "                {} => {},", pattern, dst).unwrap();
+            }
+            // A catch-all after ranges that cover every byte would be an unreachable pattern.
+            let covered: u32 = ranges
+                .iter()
+                .map(|&(lo, hi, _)| u32::from(hi) - u32::from(lo) + 1)
+                .sum();
+            if covered < 0x100 {
+                writeln!(out, "                _ => DEAD_STATE,").unwrap();
+            }
+            writeln!(out, "            }},").unwrap();
+        }
+        writeln!(out, "            _ => DEAD_STATE,").unwrap();
+        writeln!(out, "        }}").unwrap();
+        writeln!(out, "    }}").unwrap();
+        writeln!(out).unwrap();
+        writeln!(out, "    pub fn is_match(input: &[u8]) -> bool {{").unwrap();
+        writeln!(out, "        let mut s = INITIAL_STATE;").unwrap();
+        writeln!(out, "        for &b in input {{").unwrap();
+        writeln!(out, "            s = next_state(s, b);").unwrap();
+        writeln!(out, "            if s == DEAD_STATE {{").unwrap();
+        writeln!(out, "                return false;").unwrap();
+        writeln!(out, "            }}").unwrap();
+        writeln!(out, "        }}").unwrap();
+        writeln!(out, "        ACCEPTING[s as usize]").unwrap();
+        writeln!(out, "    }}").unwrap();
+        writeln!(out, "}}").unwrap();
+        out
+    }
+
+    // A C header with a static transition table, using the smallest integer type that fits
+    // every state, and a `{prefix}_match` function that runs the whole DFA over some bytes.
+    // Everything is `static`, so the header can be included in several files.
+    pub fn to_c_header(&self, prefix: &str) -> String {
+        let dense = self.dense();
+        let n = dense.accepting.len();
+        let state = match n {
+            0..=0x100 => "uint8_t",
+            0x101..=0x1_0000 => "uint16_t",
+            _ => "uint32_t",
+        };
+        let guard = format!("{}_DFA_H", prefix.to_ascii_uppercase());
+        let mut out = String::new();
+        writeln!(out, "// Generated by dfa-utils. Do not edit.").unwrap();
+        writeln!(out, "#ifndef {}", guard).unwrap();
+        writeln!(out, "#define {}", guard).unwrap();
+        writeln!(out).unwrap();
+        writeln!(out, "#include <stddef.h>").unwrap();
+        writeln!(out, "#include <stdint.h>").unwrap();
+        writeln!(out).unwrap();
+        writeln!(
+            out,
+            "#define {}_INITIAL_STATE 0",
+            prefix.to_ascii_uppercase()
+        )
+        .unwrap();
+        writeln!(
+            out,
+            "#define {}_DEAD_STATE {}",
+            prefix.to_ascii_uppercase(),
+            dense.dead_state()
+        )
+        .unwrap();
+        writeln!(out).unwrap();
+        writeln!(out, "static const uint8_t {}_accepting[{}] = {{", prefix, n).unwrap();
+        for chunk in dense.accepting.chunks(16) {
+            let row: Vec<String> = chunk.iter().map(|&a| u8::from(a).to_string()).collect();
+            writeln!(out, "    {},", row.join(", ")).unwrap();
+        }
+        writeln!(out, "}};").unwrap();
+        writeln!(out).unwrap();
+        writeln!(
+            out,
+            "static const {} {}_transitions[{}][256] = {{",
+            state, prefix, n
+        )
+        .unwrap();
+        for row in dense.table().chunks(256) {
+            writeln!(out, "    {{").unwrap();
+            for chunk in row.chunks(16) {
+                let line: Vec<String> = chunk.iter().map(u32::to_string).collect();
+                writeln!(out, "        {},", line.join(", ")).unwrap();
+            }
+            writeln!(out, "    }},").unwrap();
+        }
+        writeln!(out, "}};").unwrap();
+        writeln!(out).unwrap();
+        writeln!(
+            out,
+            "// Returns 1 if the DFA accepts the `len` bytes at `input`, and 0 otherwise."
+        )
+        .unwrap();
+        writeln!(
+            out,
+            "static inline int {}_match(const uint8_t *input, size_t len) {{",
+            prefix
+        )
+        .unwrap();
+        writeln!(
+            out,
+            "    {} s = {}_INITIAL_STATE;",
+            state,
+            prefix.to_ascii_uppercase()
+        )
+        .unwrap();
+        writeln!(out, "    for (size_t i = 0; i < len; i++) {{").unwrap();
+        writeln!(out, "        s = {}_transitions[s][input[i]];", prefix).unwrap();
+        writeln!(out, "    }}").unwrap();
+        writeln!(out, "    return {}_accepting[s];", prefix).unwrap();
+        writeln!(out, "}}").unwrap();
+        writeln!(out).unwrap();
+        writeln!(out, "#endif").unwrap();
+        out
+    }
+}
+
+// A byte as a Rust literal, readable when it is a printable character.
+fn rust_byte(b: u8) -> String {
+    match b {
+        b'\'' | b'\\' => format!("b'\\{}'", b as char),
+        b' '..=b'~' => format!("b'{}'", b as char),
+        _ => format!("0x{:02x}", b),
+    }
+}
+
+// Whether `source` builds with no warnings as a library crate named `name`, checked by the
+// `rustc` that builds this crate.
+#[cfg(test)]
+pub(crate) fn compiles(name: &str, source: &str) -> bool {
+    let dir = std::env::temp_dir().join(format!("dfa-utils-{}-{}", name, std::process::id()));
+    std::fs::create_dir_all(&dir).unwrap();
+    let path = dir.join(format!("{}.rs", name));
+    std::fs::write(&path, source).unwrap();
+    let status = std::process::Command::new(std::env::var("RUSTC").unwrap_or("rustc".into()))
+        .args([
+            "--crate-type=lib",
+            "--edition=2018",
+            "--emit=metadata",
+            "-D",
+            "warnings",
+        ])
+        .arg("--out-dir")
+        .arg(&dir)
+        .arg(&path)
+        .status()
+        .unwrap();
+    std::fs::remove_dir_all(&dir).unwrap();
+    status.success()
+}
+
+#[cfg(test)]
+mod test {
+    use crate::DFA;
+
+    #[test]
+    fn rust_matcher() {
+        // Decimal numbers without leading zeros.
+        let digits = (b'1'..=b'9').map(|b| (0, b, 1));
+        let more = (b'0'..=b'9').map(|b| (1, b, 1));
+        let zero = Some((0, b'0', 2));
+        let dfa: DFA<u32, u8> = DFA::new(0, vec![1, 2], digits.chain(more).chain(zero));
+        let code = dfa.to_rust("number");
+        assert!(code.contains("pub mod number {"));
+        assert!(code.contains("pub const DEAD_STATE: u32 = 3;"));
+        assert!(code.contains("        false, true, true, false,\n"));
+        assert!(code.contains("                b'0' => 1,\n"));
+        assert!(code.contains("                b'1'..=b'9' => 2,\n"));
+        assert!(code.contains("                b'0'..=b'9' => 2,\n"));
+    }
+
+    #[test]
+    fn rust_matcher_compiles() {
+        // Any byte and then an 'a': the initial state covers every byte, so it needs no `_` arm.
+        let any = (0..=u8::MAX).map(|b| (0, b, 1));
+        let dfa: DFA<u32, u8> = DFA::new(0, vec![2], any.chain(Some((1, b'a', 2))));
+        let code = dfa.to_rust("any_then_a");
+        assert!(code.contains("                0x00..=0xff => 1,\n            },"));
+
+        assert!(super::compiles("any_then_a", &code));
+    }
+
+    #[test]
+    fn c_header() {
+        let dfa: DFA<u32, u8> = DFA::new(0, vec![1], vec![(0, b'a', 1), (1, b'a', 1)]);
+        let header = dfa.to_c_header("as");
+        assert!(header.contains("#ifndef AS_DFA_H"));
+        assert!(header.contains("#define AS_DEAD_STATE 2"));
+        assert!(header.contains("static const uint8_t as_accepting[3] = {\n    0, 1, 0,\n};"));
+        assert!(header.contains("static const uint8_t as_transitions[3][256] = {"));
+        // Row 0 sends 'a' (97) to 1, and everything else to the dead state.
+        let row = &dfa.dense().table()[..256];
+        assert_eq!(row[usize::from(b'a')], 1);
+        assert_eq!(row.iter().filter(|&&dst| dst == 2).count(), 255);
+        assert!(header.contains("static inline int as_match(const uint8_t *input, size_t len) {"));
+    }
+}
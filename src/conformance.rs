@@ -0,0 +1,193 @@
+use std::collections::BTreeSet;
+use std::fmt::Debug;
+use std::hash::Hash;
+
+use crate::hash::{HashMap, HashSet};
+use crate::DFA;
+
+impl<S, E> DFA<S, E>
+where
+    S: Eq + Hash + Copy + Debug,
+    E: Ord + Hash + Copy + Debug,
+{
+    // Generates the W-method conformance test suite for this DFA, which should be minimal. Any
+    // implementation with at most `extra_states` more states than this DFA that classifies every
+    // returned word the same way accepts exactly the same language. The suite is every
+    // transition-cover word, followed by every word of at most `extra_states` symbols, followed
+    // by every word of a characterization set. Words come out in lexicographic order.
+    pub fn w_method(&self, extra_states: usize) -> Vec<Vec<E>> {
+        let alphabet: BTreeSet<E> = self.transitions.by_b().into_keys().collect();
+
+        // A shortest access word for every reachable state, and every one-symbol extension.
+        let mut cover: BTreeSet<Vec<E>> = BTreeSet::new();
+        let states: Vec<S> = self.reachable().into_iter().collect();
+        for &q in &states {
+            let (_, access) = self.shortest_path(self.initial_state, |r| *r == q).unwrap();
+            for &label in &alphabet {
+                let mut extension = access.clone();
+                extension.push(label);
+                cover.insert(extension);
+            }
+            cover.insert(access);
+        }
+
+        // Words that tell every pair of states apart, including the rejecting sink that missing
+        // transitions lead to.
+        let table = self.distinguishability_table();
+        let mut characterization: BTreeSet<Vec<E>> = BTreeSet::new();
+        characterization.insert(Vec::new());
+        for (i, &p) in states.iter().enumerate() {
+            for &q in &states[i + 1..] {
+                if let Some(word) = table.get(&(p, q)) {
+                    characterization.insert(word.clone());
+                }
+            }
+            if let Some((_, word)) = self.shortest_path(p, |r| self.final_states.contains(r)) {
+                characterization.insert(word);
+            }
+        }
+
+        let mut middles: Vec<Vec<E>> = vec![Vec::new()];
+        let mut layer: Vec<Vec<E>> = vec![Vec::new()];
+        for _ in 0..extra_states {
+            layer = layer
+                .iter()
+                .flat_map(|w| alphabet.iter().map(move |&a| [w.as_slice(), &[a]].concat()))
+                .collect();
+            middles.extend(layer.iter().cloned());
+        }
+
+        let mut suite: BTreeSet<Vec<E>> = BTreeSet::new();
+        for prefix in &cover {
+            for middle in &middles {
+                for suffix in &characterization {
+                    suite.insert([prefix.as_slice(), middle, suffix].concat());
+                }
+            }
+        }
+        suite.into_iter().collect()
+    }
+
+    // Generates a few input words that together take every reachable transition at least once.
+    // Each word greedily walks to the nearest state with an untaken transition and takes it,
+    // and a new word starts from the initial state only when no untaken transition can be
+    // reached from where the current word ends.
+    pub fn transition_tour(&self) -> Vec<Vec<E>> {
+        let delta = self.delta();
+        let reachable = self.reachable();
+        // States are numbered so that the untaken transitions can be kept in order.
+        let states: Vec<S> = reachable.into_iter().collect();
+        let ids: HashMap<S, usize> = states.iter().enumerate().map(|(i, &q)| (q, i)).collect();
+        let mut untaken: BTreeSet<(usize, E)> = BTreeSet::new();
+        for &(src, label, _) in &self.transitions {
+            if let Some(&i) = ids.get(&src) {
+                untaken.insert((i, label));
+            }
+        }
+
+        let mut words = Vec::new();
+        let mut word = Vec::new();
+        let mut q = self.initial_state;
+        while !untaken.is_empty() {
+            let pending: HashSet<S> = untaken.iter().map(|&(i, _)| states[i]).collect();
+            match self.shortest_path(q, |r| pending.contains(r)) {
+                Some((target, path)) => {
+                    let i = ids[&target];
+                    let &(_, label) = untaken.iter().find(|&&(j, _)| j == i).unwrap();
+                    for &step in path.iter().chain(Some(&label)) {
+                        untaken.remove(&(ids[&q], step));
+                        word.push(step);
+                        q = delta[&(q, step)];
+                    }
+                }
+                None => {
+                    words.push(std::mem::take(&mut word));
+                    q = self.initial_state;
+                }
+            }
+        }
+        if !word.is_empty() || words.is_empty() {
+            words.push(word);
+        }
+        words
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::DFA;
+
+    // A faulty copy of `dfa` in which one transition is redirected to `dst`, possibly a new
+    // state.
+    fn mutate(dfa: &DFA<u32, u8>, index: usize, dst: u32) -> DFA<u32, u8> {
+        let transitions: Vec<(u32, u8, u32)> = (&dfa.transitions).into_iter().copied().collect();
+        let mut mutated = transitions.clone();
+        mutated[index].2 = dst;
+        let mut final_states: Vec<u32> = dfa.final_states.iter().copied().collect();
+        // The new state 99 is an accepting sink that loops on every label.
+        if dst == 99 {
+            final_states.push(99);
+            mutated.extend(transitions.iter().map(|&(_, label, _)| (99, label, 99)));
+        }
+        DFA::new(dfa.initial_state, final_states, mutated)
+    }
+
+    #[test]
+    fn transition_tour_covers_everything() {
+        // Two branches that cannot return to the start, so the tour needs two words.
+        let dfa: DFA<u32, u8> = DFA::new(
+            0,
+            vec![2, 4],
+            vec![
+                (0, 0, 1),
+                (1, 0, 1),
+                (1, 1, 2),
+                (2, 1, 1),
+                (0, 1, 3),
+                (3, 0, 4),
+                (4, 1, 3),
+            ],
+        );
+        let tour = dfa.transition_tour();
+        assert_eq!(tour.len(), 2);
+        let mut taken = crate::hash::HashSet::default();
+        for word in &tour {
+            let mut q = 0;
+            for &label in word {
+                let &(_, _, dst) = (&dfa.transitions)
+                    .into_iter()
+                    .find(|&&(src, l, _)| src == q && l == label)
+                    .unwrap();
+                taken.insert((q, label));
+                q = dst;
+            }
+        }
+        assert_eq!(taken.len(), 7);
+        assert_eq!(tour.iter().map(|w| w.len()).sum::<usize>(), 7);
+    }
+
+    #[test]
+    fn w_method_catches_mutants() {
+        // Binary numbers divisible by 3.
+        let dfa: DFA<u32, u8> = DFA::new(
+            0,
+            vec![0],
+            (0..3u32).flat_map(|r| (0..2u8).map(move |b| (r, b, (2 * r + b as u32) % 3))),
+        );
+        let suite = dfa.w_method(1);
+        assert!(suite.contains(&vec![]));
+        for index in 0..6 {
+            for dst in [0, 1, 2, 99] {
+                let mutant = mutate(&dfa, index, dst);
+                let differs = suite.iter().any(|w| mutant.accepts(w) != dfa.accepts(w));
+                let equivalent = (0..=8).all(|len| {
+                    (0..1u32 << len).all(|n| {
+                        let word: Vec<u8> = (0..len).map(|i| (n >> i & 1) as u8).collect();
+                        mutant.accepts(&word) == dfa.accepts(&word)
+                    })
+                });
+                assert_eq!(differs, !equivalent, "mutant {} -> {}", index, dst);
+            }
+        }
+    }
+}
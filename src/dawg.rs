@@ -0,0 +1,148 @@
+use std::collections::VecDeque;
+use std::fmt::Debug;
+use std::hash::Hash;
+
+use crate::hash::HashMap;
+use crate::DFA;
+
+#[derive(Clone, PartialEq, Eq, Hash)]
+struct Node<E> {
+    accepting: bool,
+    // Sorted by label, since words arrive in sorted order.
+    edges: Vec<(E, usize)>,
+}
+
+impl<E> DFA<u32, E>
+where
+    E: Eq + Hash + Copy + Debug + Ord,
+{
+    // Builds the minimal acyclic DFA accepting exactly `words`, using the incremental algorithm of
+    // Daciuk et al. Only the path for the most recent word is ever left unminimized, so this never
+    // materializes the full prefix tree.
+    // Panics if `words` is not sorted in lexicographic order. Duplicate words are fine.
+    pub fn from_sorted_words<W>(words: impl IntoIterator<Item = W>) -> DFA<u32, E>
+    where
+        W: IntoIterator<Item = E>,
+    {
+        let mut nodes = vec![Node {
+            accepting: false,
+            edges: Vec::new(),
+        }];
+        let mut free: Vec<usize> = Vec::new();
+        let mut register: HashMap<Node<E>, usize> = HashMap::default();
+        // The not-yet-registered path for the previous word, as (parent, child) pairs.
+        let mut unchecked: Vec<(usize, usize)> = Vec::new();
+        let mut prev: Vec<E> = Vec::new();
+
+        for word in words {
+            let word: Vec<E> = word.into_iter().collect();
+            assert!(
+                word >= prev,
+                "words are not sorted: {:?} after {:?}",
+                word,
+                prev
+            );
+            let common = word.iter().zip(&prev).take_while(|(a, b)| a == b).count();
+            replace_or_register(&mut nodes, &mut free, &mut register, &mut unchecked, common);
+
+            let mut q = unchecked.last().map_or(0, |&(_, child)| child);
+            for &label in &word[common..] {
+                let child = match free.pop() {
+                    Some(id) => id,
+                    None => {
+                        nodes.push(Node {
+                            accepting: false,
+                            edges: Vec::new(),
+                        });
+                        nodes.len() - 1
+                    }
+                };
+                nodes[child] = Node {
+                    accepting: false,
+                    edges: Vec::new(),
+                };
+                nodes[q].edges.push((label, child));
+                unchecked.push((q, child));
+                q = child;
+            }
+            nodes[q].accepting = true;
+            prev = word;
+        }
+        replace_or_register(&mut nodes, &mut free, &mut register, &mut unchecked, 0);
+
+        // Renumber the surviving nodes in BFS order.
+        let mut ids: HashMap<usize, u32> = HashMap::default();
+        let mut queue = VecDeque::new();
+        let mut final_states = Vec::new();
+        let mut transitions = Vec::new();
+        ids.insert(0, 0);
+        queue.push_back(0);
+        while let Some(q) = queue.pop_front() {
+            let src = ids[&q];
+            if nodes[q].accepting {
+                final_states.push(src);
+            }
+            for &(label, child) in &nodes[q].edges {
+                let n = ids.len() as u32;
+                let dst = *ids.entry(child).or_insert_with(|| {
+                    queue.push_back(child);
+                    n
+                });
+                transitions.push((src, label, dst));
+            }
+        }
+        DFA::new(0, final_states, transitions)
+    }
+}
+
+// Registers (or merges into an equivalent registered node) every unchecked node deeper than
+// `depth`, deepest first.
+fn replace_or_register<E>(
+    nodes: &mut [Node<E>],
+    free: &mut Vec<usize>,
+    register: &mut HashMap<Node<E>, usize>,
+    unchecked: &mut Vec<(usize, usize)>,
+    depth: usize,
+) where
+    E: Eq + Hash + Copy,
+{
+    while unchecked.len() > depth {
+        let (parent, child) = unchecked.pop().unwrap();
+        match register.get(&nodes[child]) {
+            Some(&existing) => {
+                nodes[parent].edges.last_mut().unwrap().1 = existing;
+                free.push(child);
+            }
+            None => {
+                register.insert(nodes[child].clone(), child);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::DFA;
+
+    #[test]
+    fn from_sorted_words_is_minimal() {
+        let words = ["cat", "cats", "dog", "dogs", "dot", "dots", "fact", "facts"];
+        let dawg = DFA::from_sorted_words(words.iter().map(|w| w.chars()));
+        let trie = DFA::from_words(words.iter().map(|w| w.chars()))
+            .prune_unreachable()
+            .unwrap()
+            .minimize();
+        assert_eq!(dawg.transitions.len(), trie.transitions.len());
+        assert_eq!(dawg.transitions.by_a().len(), trie.transitions.by_a().len());
+
+        let mut expected: Vec<Vec<char>> = words.iter().map(|w| w.chars().collect()).collect();
+        expected.sort_by(|a, b| a.len().cmp(&b.len()).then(a.cmp(b)));
+        assert_eq!(dawg.words(None).collect::<Vec<_>>(), expected);
+    }
+
+    #[test]
+    #[should_panic]
+    fn from_sorted_words_rejects_unsorted() {
+        DFA::from_sorted_words(vec!["b", "a"].into_iter().map(|w| w.chars()));
+    }
+}
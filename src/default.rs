@@ -0,0 +1,268 @@
+use std::collections::VecDeque;
+use std::fmt::Debug;
+use std::hash::Hash;
+
+use crate::hash::{HashMap, HashSet};
+use crate::table::Table;
+use crate::DFA;
+
+// A DFA in which each state may have a default transition, taken on any symbol that the state
+// has no explicit transition for. A state without a default rejects unlisted symbols. This keeps
+// DFAs over large alphabets small when most states treat most symbols alike.
+#[derive(Debug, Clone)]
+pub struct DefaultDFA<S, E> {
+    initial_state: S,
+    final_states: HashSet<S>,
+    transitions: Table<S, E, S>,
+    defaults: HashMap<S, S>,
+}
+
+impl<S, E> DefaultDFA<S, E>
+where
+    S: Eq + Hash + Copy + Debug,
+    E: Eq + Hash + Copy + Debug,
+{
+    pub fn new(
+        initial_state: S,
+        final_states: impl IntoIterator<Item = S>,
+        transitions: impl IntoIterator<Item = (S, E, S)>,
+        defaults: impl IntoIterator<Item = (S, S)>,
+    ) -> DefaultDFA<S, E> {
+        DefaultDFA {
+            initial_state,
+            final_states: final_states.into_iter().collect(),
+            transitions: transitions.into_iter().collect(),
+            defaults: defaults.into_iter().collect(),
+        }
+    }
+
+    pub fn accepts(&self, word: &[E]) -> bool {
+        let delta: HashMap<(S, E), S> = (&self.transitions)
+            .into_iter()
+            .map(|&(src, label, dst)| ((src, label), dst))
+            .collect();
+        let mut q = self.initial_state;
+        for &label in word {
+            q = match delta.get(&(q, label)).or_else(|| self.defaults.get(&q)) {
+                Some(&dst) => dst,
+                None => return false,
+            };
+        }
+        self.final_states.contains(&q)
+    }
+
+    // The minimal equivalent automaton, using defaults wherever they save transitions.
+    pub fn minimize(&self) -> DefaultDFA<u32, E> {
+        compress(self.complete(&self.alphabet()).minimize())
+    }
+
+    // Accepts exactly the words that this rejects.
+    pub fn complement(&self) -> DefaultDFA<u32, E> {
+        let mut complete = self.complete(&self.alphabet());
+        let states = complete.states();
+        complete.final_states = states.difference(&complete.final_states).copied().collect();
+        compress(complete)
+    }
+
+    // Runs both automata side by side, accepting a word when `accept` says so given whether each
+    // automaton accepts it.
+    pub fn product<S2>(
+        &self,
+        other: &DefaultDFA<S2, E>,
+        accept: impl Fn(bool, bool) -> bool,
+    ) -> DefaultDFA<u32, E>
+    where
+        S2: Eq + Hash + Copy + Debug,
+    {
+        let mut alphabet = self.alphabet();
+        for label in other.alphabet() {
+            if !alphabet.contains(&label) {
+                alphabet.push(label);
+            }
+        }
+        let lhs = self.complete(&alphabet);
+        let rhs = other.complete(&alphabet);
+        let (lhs_delta, rhs_delta) = (lhs.delta(), rhs.delta());
+
+        let start = (lhs.initial_state, rhs.initial_state);
+        let mut ids: HashMap<(u32, u32), u32> = HashMap::default();
+        let mut queue = VecDeque::new();
+        let mut final_states = Vec::new();
+        let mut transitions = Vec::new();
+        ids.insert(start, 0);
+        queue.push_back(start);
+        while let Some((p, q)) = queue.pop_front() {
+            let src = ids[&(p, q)];
+            if accept(lhs.final_states.contains(&p), rhs.final_states.contains(&q)) {
+                final_states.push(src);
+            }
+            for &label in &alphabet {
+                let next = (lhs_delta[&(p, label)], rhs_delta[&(q, label)]);
+                let n = ids.len() as u32;
+                let dst = *ids.entry(next).or_insert_with(|| {
+                    queue.push_back(next);
+                    n
+                });
+                transitions.push((src, label, dst));
+            }
+        }
+        compress(DFA::new(0, final_states, transitions))
+    }
+
+    pub fn intersection<S2>(&self, other: &DefaultDFA<S2, E>) -> DefaultDFA<u32, E>
+    where
+        S2: Eq + Hash + Copy + Debug,
+    {
+        self.product(other, |a, b| a && b)
+    }
+
+    pub fn union<S2>(&self, other: &DefaultDFA<S2, E>) -> DefaultDFA<u32, E>
+    where
+        S2: Eq + Hash + Copy + Debug,
+    {
+        self.product(other, |a, b| a || b)
+    }
+
+    // Every symbol with an explicit transition somewhere, plus `None`, which stands for all the
+    // symbols that only ever take defaults. Those symbols are interchangeable.
+    fn alphabet(&self) -> Vec<Option<E>> {
+        let mut alphabet: Vec<Option<E>> = self.transitions.by_b().into_keys().map(Some).collect();
+        alphabet.push(None);
+        alphabet
+    }
+
+    // Spells out every transition over `alphabet` as an ordinary complete DFA, numbered from 0.
+    // A rejecting sink state stands in for missing transitions. Only reachable states are kept.
+    fn complete(&self, alphabet: &[Option<E>]) -> DFA<u32, Option<E>> {
+        let delta: HashMap<(S, E), S> = (&self.transitions)
+            .into_iter()
+            .map(|&(src, label, dst)| ((src, label), dst))
+            .collect();
+        let step = |q: Option<S>, label: Option<E>| -> Option<S> {
+            let q = q?;
+            label
+                .and_then(|label| delta.get(&(q, label)))
+                .or_else(|| self.defaults.get(&q))
+                .copied()
+        };
+
+        let start = Some(self.initial_state);
+        let mut ids: HashMap<Option<S>, u32> = HashMap::default();
+        let mut queue = VecDeque::new();
+        let mut final_states = Vec::new();
+        let mut transitions = Vec::new();
+        ids.insert(start, 0);
+        queue.push_back(start);
+        while let Some(q) = queue.pop_front() {
+            let src = ids[&q];
+            if q.is_some_and(|q| self.final_states.contains(&q)) {
+                final_states.push(src);
+            }
+            for &label in alphabet {
+                let next = step(q, label);
+                let n = ids.len() as u32;
+                let dst = *ids.entry(next).or_insert_with(|| {
+                    queue.push_back(next);
+                    n
+                });
+                transitions.push((src, label, dst));
+            }
+        }
+        DFA::new(0, final_states, transitions)
+    }
+}
+
+// The inverse of `DefaultDFA::complete`: each state's `None` transition becomes its default, and
+// explicit transitions that agree with the default are dropped. Dead states are dropped too,
+// except where an explicit transition must steer a symbol away from a live default.
+fn compress<E>(dfa: DFA<u32, Option<E>>) -> DefaultDFA<u32, E>
+where
+    E: Eq + Hash + Copy + Debug,
+{
+    let dead = dfa.dead_states();
+    let delta = dfa.delta();
+    let mut transitions = Vec::new();
+    let mut defaults = HashMap::default();
+    for &(src, label, dst) in &dfa.transitions {
+        let default = delta.get(&(src, None)).filter(|d| !dead.contains(d));
+        match label {
+            None => {
+                if let Some(&default) = default {
+                    defaults.insert(src, default);
+                }
+            }
+            Some(label) => {
+                let implied =
+                    default.is_some_and(|&d| d == dst) || default.is_none() && dead.contains(&dst);
+                if !implied {
+                    transitions.push((src, label, dst));
+                }
+            }
+        }
+    }
+    DefaultDFA::new(dfa.initial_state, dfa.final_states, transitions, defaults)
+}
+
+#[cfg(test)]
+mod test {
+    use super::DefaultDFA;
+
+    fn chars(s: &str) -> Vec<char> {
+        s.chars().collect()
+    }
+
+    // Accepts any string containing "ab".
+    fn contains_ab() -> DefaultDFA<u32, char> {
+        DefaultDFA::new(
+            0,
+            vec![2],
+            vec![(0, 'a', 1), (1, 'a', 1), (1, 'b', 2)],
+            vec![(0, 0), (1, 0), (2, 2)],
+        )
+    }
+
+    #[test]
+    fn minimize_keeps_defaults() {
+        let dfa = contains_ab();
+        assert!(dfa.accepts(&chars("xxabx")));
+        assert!(!dfa.accepts(&chars("xxbax")));
+
+        // A redundant copy of the accepting state, and an explicit transition that matches the
+        // default, both disappear.
+        let bloated: DefaultDFA<u32, char> = DefaultDFA::new(
+            0,
+            vec![2, 3],
+            vec![
+                (0, 'a', 1),
+                (0, 'z', 0),
+                (1, 'a', 1),
+                (1, 'b', 2),
+                (2, 'q', 3),
+            ],
+            vec![(0, 0), (1, 0), (2, 2), (3, 3)],
+        );
+        let minimal = bloated.minimize();
+        assert_eq!(minimal.transitions.len(), 3);
+        assert_eq!(minimal.defaults.len(), 3);
+        assert!(minimal.accepts(&chars("zabq")));
+    }
+
+    #[test]
+    fn boolean_operations() {
+        let dfa = contains_ab();
+        let complement = dfa.complement();
+        assert!(complement.accepts(&chars("bbba")));
+        assert!(!complement.accepts(&chars("bab")));
+
+        // Accepts strings of a's only, rejecting anything else without a default.
+        let only_a: DefaultDFA<u32, char> = DefaultDFA::new(0, vec![0], vec![(0, 'a', 0)], vec![]);
+        let either = dfa.union(&only_a);
+        assert!(either.accepts(&chars("aaa")));
+        assert!(either.accepts(&chars("xab")));
+        assert!(!either.accepts(&chars("x")));
+        let neither = dfa.complement().intersection(&only_a.complement());
+        assert!(neither.accepts(&chars("x")));
+        assert!(!neither.accepts(&chars("")));
+        assert!(dfa.intersection(&only_a).minimize().final_states.is_empty());
+    }
+}
@@ -0,0 +1,234 @@
+// Comparing how fast languages grow. The number of accepted words of length n is, up to
+// periodic fluctuation, about `coefficient * n^degree * rate^n`, where `rate` is the dominant
+// eigenvalue of the trimmed DFA's adjacency matrix and `degree + 1` is the largest number of
+// components with that eigenvalue on a single path. Comparing these tells which of two languages
+// is asymptotically larger, and when they grow alike, by what factor.
+
+use std::cmp::Ordering;
+use std::fmt::Debug;
+use std::hash::Hash;
+
+use crate::hash::HashMap;
+use crate::matrix::spectral_radius;
+use crate::DFA;
+
+// Eigenvalues within this relative distance of each other count as equal.
+const TOLERANCE: f64 = 1e-9;
+// `coefficient` is averaged over the counts for lengths between half this and this, which
+// smooths out periodic DFAs.
+const HORIZON: usize = 4096;
+
+// The asymptotic number of accepted words of length n, from `DFA::growth`.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Growth {
+    // The exponential growth rate. Finite languages have rate 0, and then `degree` and
+    // `coefficient` are 0 as well.
+    pub rate: f64,
+    pub degree: usize,
+    // An estimate, averaged over long lengths, so it is only accurate to a few digits.
+    pub coefficient: f64,
+}
+
+impl Growth {
+    pub fn is_finite(&self) -> bool {
+        self.rate == 0.0
+    }
+}
+
+// How the numbers of accepted words of two DFAs compare for long lengths, from
+// `DFA::compare_growth`.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct DensityComparison {
+    pub lhs: Growth,
+    pub rhs: Growth,
+    // `Greater` if the left language has asymptotically more words of each length.
+    pub ordering: Ordering,
+    // The limit of the ratio of the left count to the right count: 0 or infinity if one grows
+    // faster, and the ratio of the coefficients if they grow alike. `None` if both languages are
+    // finite.
+    pub ratio: Option<f64>,
+}
+
+impl<S, E> DFA<S, E>
+where
+    S: Eq + Hash + Copy + Debug,
+    E: Eq + Hash + Copy + Debug,
+{
+    // Estimates the asymptotic number of accepted words of length n. This takes a power iteration
+    // per cyclic component, and then `HORIZON` steps over every transition.
+    pub fn growth(&self) -> Growth {
+        const FINITE: Growth = Growth {
+            rate: 0.0,
+            degree: 0,
+            coefficient: 0.0,
+        };
+        let trimmed = match self.clone().prune_unreachable() {
+            None => return FINITE,
+            Some(trimmed) => trimmed,
+        };
+        let sccs = trimmed.sccs();
+        let mut component: HashMap<S, usize> = HashMap::default();
+        for (i, scc) in sccs.iter().enumerate() {
+            for &q in &scc.states {
+                component.insert(q, i);
+            }
+        }
+        let radii: Vec<f64> = sccs
+            .iter()
+            .map(|scc| {
+                if !scc.cyclic {
+                    return 0.0;
+                }
+                let mut adjacency = vec![vec![0.0; scc.states.len()]; scc.states.len()];
+                let index: HashMap<S, usize> = scc
+                    .states
+                    .iter()
+                    .enumerate()
+                    .map(|(i, &q)| (q, i))
+                    .collect();
+                for (src, _, dst) in trimmed.transitions() {
+                    if let (Some(&i), Some(&j)) = (index.get(&src), index.get(&dst)) {
+                        adjacency[i][j] += 1.0;
+                    }
+                }
+                spectral_radius(&adjacency)
+            })
+            .collect();
+        let rate = radii.iter().copied().fold(0.0, f64::max);
+        if rate == 0.0 {
+            return FINITE;
+        }
+
+        // Components come in reverse topological order, so every successor is counted before
+        // the components that lead to it.
+        let mut chain = vec![0; sccs.len()];
+        let outflows = trimmed.transitions.by_a();
+        for (i, scc) in sccs.iter().enumerate() {
+            let longest = scc
+                .states
+                .iter()
+                .flat_map(|q| outflows.get(q).into_iter().flatten())
+                .map(|(_, dst)| component[dst])
+                .filter(|&j| j != i)
+                .map(|j| chain[j])
+                .max()
+                .unwrap_or(0);
+            chain[i] = longest + usize::from(radii[i] >= rate * (1.0 - TOLERANCE));
+        }
+        let degree = chain[component[&trimmed.initial_state]] - 1;
+
+        // Counts words per state, divided by `rate^n` at each step so nothing overflows.
+        let index: HashMap<S, usize> = trimmed
+            .states()
+            .into_iter()
+            .enumerate()
+            .map(|(i, q)| (q, i))
+            .collect();
+        let mut counts = vec![0.0; index.len()];
+        counts[index[&trimmed.initial_state]] = 1.0;
+        let mut total = 0.0;
+        for n in 1..=HORIZON {
+            let mut next = vec![0.0; index.len()];
+            for (src, _, dst) in trimmed.transitions() {
+                next[index[&dst]] += counts[index[&src]] / rate;
+            }
+            counts = next;
+            if n > HORIZON / 2 {
+                let accepted: f64 = trimmed.final_states.iter().map(|q| counts[index[q]]).sum();
+                total += accepted / (n as f64).powi(degree as i32);
+            }
+        }
+        Growth {
+            rate,
+            degree,
+            coefficient: total / (HORIZON - HORIZON / 2) as f64,
+        }
+    }
+
+    // Compares the asymptotic numbers of words of each length that this and `other` accept.
+    // For example, if a relaxed validator accepts a superset of a strict one, a ratio near 1
+    // means that it only adds a negligible fraction of the words.
+    pub fn compare_growth<S2>(&self, other: &DFA<S2, E>) -> DensityComparison
+    where
+        S2: Eq + Hash + Copy + Debug,
+    {
+        let (lhs, rhs) = (self.growth(), other.growth());
+        let close = |a: f64, b: f64| (a - b).abs() <= TOLERANCE * a.max(b);
+        let (ordering, ratio) = if lhs.is_finite() && rhs.is_finite() {
+            (Ordering::Equal, None)
+        } else if !close(lhs.rate, rhs.rate) {
+            if lhs.rate > rhs.rate {
+                (Ordering::Greater, Some(f64::INFINITY))
+            } else {
+                (Ordering::Less, Some(0.0))
+            }
+        } else if lhs.degree != rhs.degree {
+            if lhs.degree > rhs.degree {
+                (Ordering::Greater, Some(f64::INFINITY))
+            } else {
+                (Ordering::Less, Some(0.0))
+            }
+        } else {
+            let ratio = lhs.coefficient / rhs.coefficient;
+            let ordering = if (ratio - 1.0).abs() < 1e-6 {
+                Ordering::Equal
+            } else if ratio > 1.0 {
+                Ordering::Greater
+            } else {
+                Ordering::Less
+            };
+            (ordering, Some(ratio))
+        };
+        DensityComparison {
+            lhs,
+            rhs,
+            ordering,
+            ratio,
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::cmp::Ordering;
+
+    use crate::DFA;
+
+    #[test]
+    fn growth() {
+        let all: DFA<u32, u8> = DFA::new(0, vec![0], vec![(0, 0, 0), (0, 1, 0)]);
+        let growth = all.growth();
+        assert!((growth.rate - 2.0).abs() < 1e-9);
+        assert_eq!(growth.degree, 0);
+        assert!((growth.coefficient - 1.0).abs() < 1e-6);
+
+        // a*b*, with n + 1 words of length n.
+        let ab: DFA<u32, char> =
+            DFA::new(0, vec![0, 1], vec![(0, 'a', 0), (0, 'b', 1), (1, 'b', 1)]);
+        let growth = ab.growth();
+        assert!((growth.rate - 1.0).abs() < 1e-9);
+        assert_eq!(growth.degree, 1);
+        assert!((growth.coefficient - 1.0).abs() < 1e-3);
+
+        let finite: DFA<u32, u8> = DFA::new(0, vec![1], vec![(0, 0, 1)]);
+        assert!(finite.growth().is_finite());
+    }
+
+    #[test]
+    fn compare_growth() {
+        let all: DFA<u32, u8> = DFA::new(0, vec![0], vec![(0, 0, 0), (0, 1, 0)]);
+        // No two consecutive 1s: the golden ratio, which is less than 2.
+        let golden: DFA<u32, u8> = DFA::new(0, vec![0, 1], vec![(0, 0, 0), (0, 1, 1), (1, 0, 0)]);
+        let comparison = golden.compare_growth(&all);
+        assert_eq!(comparison.ordering, Ordering::Less);
+        assert_eq!(comparison.ratio, Some(0.0));
+
+        // Words of even length: half of all words, on average.
+        let even: DFA<u32, u8> =
+            DFA::new(0, vec![0], vec![(0, 0, 1), (0, 1, 1), (1, 0, 0), (1, 1, 0)]);
+        let comparison = all.compare_growth(&even);
+        assert_eq!(comparison.ordering, Ordering::Greater);
+        assert!((comparison.ratio.unwrap() - 2.0).abs() < 1e-3);
+        assert_eq!(all.compare_growth(&all).ordering, Ordering::Equal);
+    }
+}
@@ -0,0 +1,132 @@
+// Showing how two languages differ as a picture rather than a list of counterexamples. `diff`
+// runs both DFAs side by side, as `product` does, and tags every pair of states with which of the
+// two accept there. Drawn with `to_dot`, the words only one side accepts lead to colored states.
+
+use std::fmt::{Debug, Display, Write};
+use std::hash::Hash;
+
+use crate::annotated::AnnotatedDFA;
+use crate::DFA;
+
+// Which of two DFAs accept at a state of their `diff`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum DiffTag {
+    Both,
+    OnlyLhs,
+    OnlyRhs,
+    Neither,
+}
+
+impl DiffTag {
+    fn new(lhs: bool, rhs: bool) -> DiffTag {
+        match (lhs, rhs) {
+            (true, true) => DiffTag::Both,
+            (true, false) => DiffTag::OnlyLhs,
+            (false, true) => DiffTag::OnlyRhs,
+            (false, false) => DiffTag::Neither,
+        }
+    }
+}
+
+impl<S, E> DFA<S, E>
+where
+    S: Eq + Hash + Copy + Debug,
+    E: Eq + Hash + Copy + Debug,
+{
+    // The product of `self` and `other` that accepts the words either of them accepts, with every
+    // state tagged by which of them accept there. States are numbered as `product` numbers them.
+    pub fn diff<S2>(&self, other: &DFA<S2, E>) -> AnnotatedDFA<u32, E, DiffTag>
+    where
+        S2: Eq + Hash + Copy + Debug,
+    {
+        let (dfa, pairs) = self.product_pairs(other, |a, b| a || b);
+        let tags = pairs.into_iter().enumerate().map(|(i, (p, q))| {
+            let lhs = p.is_some_and(|p| self.is_accepting(p));
+            let rhs = q.is_some_and(|q| other.is_accepting(q));
+            (i as u32, DiffTag::new(lhs, rhs))
+        });
+        AnnotatedDFA::new(dfa, tags)
+    }
+}
+
+impl<E> AnnotatedDFA<u32, E, DiffTag>
+where
+    E: Eq + Hash + Copy + Debug + Display,
+{
+    // Draws a `diff` in Graphviz DOT. States where only the left DFA accepts are red, those where
+    // only the right one accepts are green, and those where both accept are gray; all of them are
+    // double circles. States are listed in order, and the edges between two states are merged
+    // into one, labeled with every symbol in order.
+    pub fn to_dot(&self) -> String {
+        let dfa = self.dfa();
+        let mut states: Vec<u32> = dfa.states().into_iter().collect();
+        states.sort_unstable();
+        let mut out = String::from("digraph diff {\n  rankdir=LR;\n  node [shape=circle];\n");
+        for &q in &states {
+            let style = match self.metadata(q) {
+                Some(DiffTag::Both) => " [shape=doublecircle, style=filled, fillcolor=lightgray]",
+                Some(DiffTag::OnlyLhs) => {
+                    " [shape=doublecircle, style=filled, fillcolor=lightcoral]"
+                }
+                Some(DiffTag::OnlyRhs) => {
+                    " [shape=doublecircle, style=filled, fillcolor=palegreen]"
+                }
+                Some(DiffTag::Neither) | None => "",
+            };
+            writeln!(out, "  {}{};", q, style).unwrap();
+        }
+        out += "  __start [shape=point];\n";
+        writeln!(out, "  __start -> {};", dfa.initial_state()).unwrap();
+
+        let mut edges: Vec<((u32, u32), String)> = dfa
+            .transitions()
+            .map(|(src, label, dst)| ((src, dst), label.to_string()))
+            .collect();
+        edges.sort();
+        for group in edges.chunk_by(|a, b| a.0 == b.0) {
+            let ((src, dst), _) = group[0];
+            let labels: Vec<&str> = group.iter().map(|(_, label)| label.as_str()).collect();
+            let label: String = labels
+                .join(", ")
+                .chars()
+                .flat_map(char::escape_default)
+                .collect();
+            writeln!(out, "  {} -> {} [label=\"{}\"];", src, dst, label).unwrap();
+        }
+        out + "}\n"
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::DiffTag;
+    use crate::DFA;
+
+    #[test]
+    fn diff() {
+        // "ab" and "abc" before, "ab" and "abd" after.
+        let before: DFA<u32, char> =
+            DFA::new(0, vec![2, 3], vec![(0, 'a', 1), (1, 'b', 2), (2, 'c', 3)]);
+        let after: DFA<u32, char> =
+            DFA::new(0, vec![2, 3], vec![(0, 'a', 1), (1, 'b', 2), (2, 'd', 3)]);
+        let diff = before.diff(&after);
+        let delta = diff.dfa().delta();
+        let tag = |word: &[char]| {
+            let end = word
+                .iter()
+                .fold(diff.dfa().initial_state(), |q, &label| delta[&(q, label)]);
+            *diff.metadata(end).unwrap()
+        };
+        assert_eq!(tag(&[]), DiffTag::Neither);
+        assert_eq!(tag(&['a', 'b']), DiffTag::Both);
+        assert_eq!(tag(&['a', 'b', 'c']), DiffTag::OnlyLhs);
+        assert_eq!(tag(&['a', 'b', 'd']), DiffTag::OnlyRhs);
+
+        let dot = diff.to_dot();
+        assert!(dot.contains("  0;\n"));
+        assert!(dot.contains("  2 [shape=doublecircle, style=filled, fillcolor=lightgray];\n"));
+        assert!(dot.contains("fillcolor=lightcoral"));
+        assert!(dot.contains("fillcolor=palegreen"));
+        assert!(dot.contains("  0 -> 1 [label=\"a\"];\n"));
+    }
+}
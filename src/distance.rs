@@ -0,0 +1,135 @@
+use std::collections::VecDeque;
+use std::fmt::Debug;
+use std::hash::Hash;
+
+use crate::hash::HashMap;
+use crate::DFA;
+
+// Builds a DFA over `alphabet` that accepts exactly the words within edit (Levenshtein) distance
+// `k` of `word`. Every state can still reach an accepting state, but the result is not
+// necessarily minimal; call `minimize` on it if that matters.
+pub fn levenshtein_dfa<E>(word: &[E], k: usize, alphabet: &[E]) -> DFA<u32, E>
+where
+    E: Eq + Hash + Copy + Debug,
+{
+    // Each state is a row of the Levenshtein table, with entries capped at `k + 1`.
+    let cap = |x: usize| x.min(k + 1);
+    let initial: Vec<usize> = (0..=word.len()).map(cap).collect();
+
+    let mut ids: HashMap<Vec<usize>, u32> = HashMap::default();
+    let mut queue = VecDeque::new();
+    let mut final_states = Vec::new();
+    let mut transitions = Vec::new();
+    ids.insert(initial.clone(), 0);
+    queue.push_back(initial);
+    while let Some(row) = queue.pop_front() {
+        let src = ids[&row];
+        if row[word.len()] <= k {
+            final_states.push(src);
+        }
+        for &label in alphabet {
+            let mut next = Vec::with_capacity(row.len());
+            next.push(cap(row[0] + 1));
+            for j in 1..row.len() {
+                let substitute = row[j - 1] + if word[j - 1] == label { 0 } else { 1 };
+                next.push(cap(substitute.min(row[j] + 1).min(next[j - 1] + 1)));
+            }
+            if next.iter().all(|&x| x > k) {
+                continue;
+            }
+            let n = ids.len() as u32;
+            let dst = *ids.entry(next.clone()).or_insert_with(|| {
+                queue.push_back(next);
+                n
+            });
+            transitions.push((src, label, dst));
+        }
+    }
+    DFA::new(0, final_states, transitions)
+}
+
+// Builds a DFA over `alphabet` that accepts exactly the words of the same length as `word` that
+// differ from it in at most `k` positions.
+pub fn hamming_dfa<E>(word: &[E], k: usize, alphabet: &[E]) -> DFA<u32, E>
+where
+    E: Eq + Hash + Copy + Debug,
+{
+    // The state `(i, errors)` has read `i` symbols, `errors` of which differed from `word`.
+    let id = |i: usize, errors: usize| (i * (k + 1) + errors) as u32;
+    let mut transitions = Vec::new();
+    for (i, &expected) in word.iter().enumerate() {
+        for errors in 0..=k.min(i) {
+            for &label in alphabet {
+                if label == expected {
+                    transitions.push((id(i, errors), label, id(i + 1, errors)));
+                } else if errors < k {
+                    transitions.push((id(i, errors), label, id(i + 1, errors + 1)));
+                }
+            }
+        }
+    }
+    let final_states = (0..=k.min(word.len())).map(|errors| id(word.len(), errors));
+    DFA::new(id(0, 0), final_states, transitions)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn edit_distance(a: &[char], b: &[char]) -> usize {
+        let mut row: Vec<usize> = (0..=b.len()).collect();
+        for i in 1..=a.len() {
+            let mut next = vec![i];
+            for j in 1..=b.len() {
+                let substitute = row[j - 1] + if a[i - 1] == b[j - 1] { 0 } else { 1 };
+                next.push(substitute.min(row[j] + 1).min(next[j - 1] + 1));
+            }
+            row = next;
+        }
+        row[b.len()]
+    }
+
+    #[test]
+    fn hamming_accepts_close_words() {
+        let dfa = hamming_dfa(&[0u8, 1, 1, 0], 1, &[0, 1]);
+        let accepted: Vec<Vec<u8>> = dfa.words(None).collect();
+        assert_eq!(
+            accepted,
+            vec![
+                vec![0, 0, 1, 0],
+                vec![0, 1, 0, 0],
+                vec![0, 1, 1, 0],
+                vec![0, 1, 1, 1],
+                vec![1, 1, 1, 0],
+            ]
+        );
+        assert_eq!(hamming_dfa(&[0u8, 1], 5, &[0, 1]).count_words_at(2), 4);
+    }
+
+    #[test]
+    fn levenshtein_matches_brute_force() {
+        let alphabet = ['a', 'b', 'c'];
+        let word = ['a', 'b', 'a'];
+        let dfa = levenshtein_dfa(&word, 1, &alphabet);
+        let accepted: Vec<Vec<char>> = dfa.words(None).collect();
+
+        let mut expected = vec![vec![]];
+        let mut all = vec![vec![]];
+        for _ in 0..4 {
+            all = all
+                .iter()
+                .flat_map(|w: &Vec<char>| {
+                    alphabet.iter().map(move |&c| {
+                        let mut w = w.clone();
+                        w.push(c);
+                        w
+                    })
+                })
+                .collect();
+            expected.extend(all.iter().cloned());
+        }
+        expected.retain(|w| edit_distance(w, &word) <= 1);
+        expected.sort_by(|a, b| a.len().cmp(&b.len()).then(a.cmp(b)));
+        assert_eq!(accepted, expected);
+    }
+}
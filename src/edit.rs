@@ -0,0 +1,173 @@
+use std::collections::VecDeque;
+use std::fmt::Debug;
+use std::hash::Hash;
+
+use crate::hash::HashMap;
+use crate::DFA;
+
+// A position in the search: a state of the DFA, and the number of input symbols consumed so far.
+type Node<S> = (S, usize);
+
+impl<S, E> DFA<S, E>
+where
+    S: Eq + Hash + Copy + Debug,
+    E: Eq + Hash + Copy + Debug,
+{
+    // Finds an accepted word with the smallest edit (Levenshtein) distance to `input`, and
+    // returns it alongside that distance. Returns `None` if the language is empty.
+    pub fn repair(&self, input: &[E]) -> Option<(Vec<E>, usize)> {
+        let outflows = self.transitions.by_a();
+
+        // Matching an input symbol is free, while substitutions, insertions, and deletions each
+        // cost 1, so a 0-1 BFS finds the cheapest path to an accepting state.
+        let mut dist: HashMap<Node<S>, usize> = HashMap::default();
+        // Each node remembers its predecessor and the symbol it emitted, if any.
+        let mut parents: HashMap<Node<S>, (Node<S>, Option<E>)> = HashMap::default();
+        let mut queue = VecDeque::new();
+        let start = (self.initial_state, 0);
+        dist.insert(start, 0);
+        queue.push_back(start);
+        while let Some(node) = queue.pop_front() {
+            let (src, i) = node;
+            let d = dist[&node];
+            if i == input.len() && self.final_states.contains(&src) {
+                let mut word = Vec::new();
+                let mut cur = node;
+                while let Some(&(prev, emitted)) = parents.get(&cur) {
+                    word.extend(emitted);
+                    cur = prev;
+                }
+                word.reverse();
+                return Some((word, d));
+            }
+
+            let mut moves = Vec::new();
+            if i < input.len() {
+                // Delete input[i].
+                moves.push(((src, i + 1), None, 1));
+            }
+            if let Some(neighbors) = outflows.get(&src) {
+                for &(label, dst) in neighbors {
+                    // Insert `label`.
+                    moves.push(((dst, i), Some(label), 1));
+                    if i < input.len() {
+                        // Keep or substitute input[i].
+                        let cost = if label == input[i] { 0 } else { 1 };
+                        moves.push(((dst, i + 1), Some(label), cost));
+                    }
+                }
+            }
+            for (next, emitted, cost) in moves {
+                if dist.get(&next).is_none_or(|&old| d + cost < old) {
+                    dist.insert(next, d + cost);
+                    parents.insert(next, (node, emitted));
+                    if cost == 0 {
+                        queue.push_front(next);
+                    } else {
+                        queue.push_back(next);
+                    }
+                }
+            }
+        }
+        None
+    }
+
+    // Returns every accepted word within edit (Levenshtein) distance `k` of `word`, each paired
+    // with its distance. The search walks the DFA while maintaining a row of the Levenshtein
+    // table, abandoning a path as soon as every entry in the row exceeds `k`.
+    pub fn within_edit_distance(&self, word: &[E], k: usize) -> Vec<(Vec<E>, usize)> {
+        let outflows = self.transitions.by_a();
+
+        let mut found = Vec::new();
+        let row: Vec<usize> = (0..=word.len()).collect();
+        let mut stack = vec![(self.initial_state, Vec::new(), row)];
+        while let Some((src, prefix, row)) = stack.pop() {
+            let d = row[word.len()];
+            if d <= k && self.final_states.contains(&src) {
+                found.push((prefix.clone(), d));
+            }
+            if let Some(neighbors) = outflows.get(&src) {
+                for &(label, dst) in neighbors {
+                    let mut next = Vec::with_capacity(row.len());
+                    next.push(row[0] + 1);
+                    for j in 1..row.len() {
+                        let substitute = row[j - 1] + if word[j - 1] == label { 0 } else { 1 };
+                        next.push(substitute.min(row[j] + 1).min(next[j - 1] + 1));
+                    }
+                    if next.iter().all(|&x| x > k) {
+                        continue;
+                    }
+                    let mut prefix = prefix.clone();
+                    prefix.push(label);
+                    stack.push((dst, prefix, next));
+                }
+            }
+        }
+        found
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::DFA;
+
+    #[test]
+    fn repair_identifier() {
+        // Accepts 'a' followed by any number of 'a's and '1's.
+        let input: DFA<u32, char> =
+            DFA::new(0, vec![1], vec![(0, 'a', 1), (1, 'a', 1), (1, '1', 1)]);
+        assert_eq!(input.repair(&['a', '1']), Some((vec!['a', '1'], 0)));
+        assert_eq!(input.repair(&['1', 'a']).map(|(_, d)| d), Some(1));
+        assert_eq!(input.repair(&[]), Some((vec!['a'], 1)));
+        let (word, d) = input.repair(&['1', '1', 'x', 'a']).unwrap();
+        assert_eq!(d, 2);
+        assert_eq!(word[0], 'a');
+    }
+
+    #[test]
+    fn within_edit_distance_dictionary() {
+        // Accepts "cat", "car", and "cart".
+        let input: DFA<u32, char> = DFA::new(
+            0,
+            vec![3, 4, 5],
+            vec![
+                (0, 'c', 1),
+                (1, 'a', 2),
+                (2, 't', 3),
+                (2, 'r', 4),
+                (4, 't', 5),
+            ],
+        );
+        let mut found = input.within_edit_distance(&['c', 'a', 't'], 1);
+        found.sort();
+        assert_eq!(
+            found,
+            vec![
+                (vec!['c', 'a', 'r'], 1),
+                (vec!['c', 'a', 'r', 't'], 1),
+                (vec!['c', 'a', 't'], 0)
+            ]
+        );
+        assert_eq!(input.within_edit_distance(&['c', 'a', 't'], 0).len(), 1);
+        assert!(input.within_edit_distance(&['d', 'o', 'g'], 2).is_empty());
+    }
+
+    #[test]
+    fn within_edit_distance_infinite_language() {
+        // Accepts a*. Only finitely many words are within distance 2 of "aa".
+        let input: DFA<u32, char> = DFA::new(0, vec![0], vec![(0, 'a', 0)]);
+        let mut found: Vec<usize> = input
+            .within_edit_distance(&['a', 'a'], 2)
+            .into_iter()
+            .map(|(w, _)| w.len())
+            .collect();
+        found.sort();
+        assert_eq!(found, vec![0, 1, 2, 3, 4]);
+    }
+
+    #[test]
+    fn repair_empty_language() {
+        let input: DFA<u32, char> = DFA::new(0, vec![], vec![(0, 'a', 1)]);
+        assert_eq!(input.repair(&['a']), None);
+    }
+}
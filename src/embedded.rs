@@ -0,0 +1,106 @@
+// Byte-level DFAs laid out in static arrays, for matching on targets without `std` or an
+// allocator. The arrays are usually generated ahead of time by `DFA::to_static_rust`, from a
+// build script or by hand. The generated module has its own `accepts` and needs nothing but
+// `core` at run time; the `StaticDFA` that reads the same arrays lives in the `no_std`
+// `dfa-utils-core` crate, so a target can depend on that alone.
+
+use std::fmt::{Debug, Write};
+use std::hash::Hash;
+
+use crate::DFA;
+
+pub use dfa_utils_core::StaticDFA;
+
+impl<S> DFA<S, u8>
+where
+    S: Eq + Hash + Copy + Debug,
+{
+    // The arrays behind a `StaticDFA` for this DFA, for building one at run time. Missing
+    // transitions go to a rejecting dead state, numbered last.
+    pub fn to_static_parts(&self) -> (Vec<u32>, Vec<bool>) {
+        let dense = self.dense();
+        (dense.table(), dense.accepting)
+    }
+
+    // Rust source for a module named `module` holding this DFA's `TRANSITIONS` and `ACCEPTING`
+    // as static arrays, with an `accepts` function that matches against them. It uses neither
+    // `std` nor this crate, so e.g. a build script can write it into `OUT_DIR` for a `no_std`
+    // target. `StaticDFA::new(&TRANSITIONS, &ACCEPTING)` matches the same words.
+    pub fn to_static_rust(&self, module: &str) -> String {
+        let (transitions, accepting) = self.to_static_parts();
+        let mut out = String::new();
+        writeln!(out, "// Generated by dfa-utils. Do not edit.").unwrap();
+        writeln!(out, "#[allow(dead_code)]").unwrap();
+        writeln!(out, "pub mod {} {{", module).unwrap();
+        writeln!(
+            out,
+            "    // Row `s` holds the states that `s` moves to on each byte."
+        )
+        .unwrap();
+        writeln!(
+            out,
+            "    pub static TRANSITIONS: [u32; {}] = [",
+            transitions.len()
+        )
+        .unwrap();
+        for chunk in transitions.chunks(16) {
+            let line: Vec<String> = chunk.iter().map(u32::to_string).collect();
+            writeln!(out, "        {},", line.join(", ")).unwrap();
+        }
+        writeln!(out, "    ];").unwrap();
+        writeln!(
+            out,
+            "    pub static ACCEPTING: [bool; {}] = [",
+            accepting.len()
+        )
+        .unwrap();
+        for chunk in accepting.chunks(8) {
+            let line: Vec<String> = chunk.iter().map(bool::to_string).collect();
+            writeln!(out, "        {},", line.join(", ")).unwrap();
+        }
+        writeln!(out, "    ];").unwrap();
+        writeln!(out).unwrap();
+        writeln!(out, "    pub fn accepts(input: &[u8]) -> bool {{").unwrap();
+        writeln!(out, "        let mut s = 0;").unwrap();
+        writeln!(out, "        for &b in input {{").unwrap();
+        writeln!(
+            out,
+            "            s = TRANSITIONS[256 * s as usize + b as usize];"
+        )
+        .unwrap();
+        writeln!(out, "        }}").unwrap();
+        writeln!(out, "        ACCEPTING[s as usize]").unwrap();
+        writeln!(out, "    }}").unwrap();
+        writeln!(out, "}}").unwrap();
+        out
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::StaticDFA;
+    use crate::DFA;
+
+    #[test]
+    fn static_matching() {
+        // Matching itself is tested in `dfa-utils-core`; this checks the layout fed to it.
+        let dfa: DFA<char, u8> = DFA::new('x', vec!['z'], vec![('x', b'a', 'y'), ('y', b'b', 'z')]);
+        let (transitions, accepting) = dfa.to_static_parts();
+        assert_eq!(accepting, vec![false, false, true, false]);
+        let built = StaticDFA::new(&transitions, &accepting);
+        assert_eq!(
+            (built.next_state(0, b'a'), built.next_state(1, b'b')),
+            (1, 2)
+        );
+        assert_eq!(built.next_state(0, b'b'), 3);
+        assert!(built.accepts(b"ab") && !built.accepts(b"abb"));
+
+        let code = dfa.to_static_rust("ab");
+        assert!(code.contains("pub mod ab {"));
+        assert!(code.contains("    pub static TRANSITIONS: [u32; 1024] = [\n"));
+        assert!(code.contains("        false, false, true, false,\n"));
+        // The generated module stands alone, even without `std`.
+        let crate_root = format!("#![no_std]\n{}", code);
+        assert!(crate::codegen::compiles("static_ab", &crate_root));
+    }
+}
@@ -0,0 +1,313 @@
+use std::collections::{hash_map::Entry, VecDeque};
+use std::fmt::{self, Debug, Display};
+use std::hash::Hash;
+
+use crate::hash::{HashMap, HashSet};
+use crate::DFA;
+
+// A pair of states of two DFAs, where `None` stands for a rejecting sink.
+type Pair<S, S2> = (Option<S>, Option<S2>);
+// For a discovered pair, the pair and label that we came from.
+type Parent<S, S2, E> = Option<(Pair<S, S2>, E)>;
+
+// Returned by `DFA::minimize_verified` when the minimized DFA does not accept the same language
+// as the original. `word` is accepted by exactly one of them.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct VerificationError<E> {
+    pub word: Vec<E>,
+}
+
+impl<E: Debug> Display for VerificationError<E> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "minimization changed the language: {:?}", self.word)
+    }
+}
+
+impl<E: Debug> std::error::Error for VerificationError<E> {}
+
+// From `DFA::distinguishing_length`: how long a word may have to be to tell two DFAs apart, and
+// how long it actually has to be for these two.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DistinguishingLength<E> {
+    // Any two DFAs with as many reachable states as these that accept different languages are
+    // told apart by a word no longer than this. For complete DFAs with n1 and n2 states it is
+    // n1 + n2 - 2; a partial DFA counts its missing transitions' rejecting sink as one more state.
+    pub bound: usize,
+    // A shortest word accepted by exactly one of the DFAs, or `None` if they accept the same
+    // language.
+    pub witness: Option<Vec<E>>,
+}
+
+impl<E> DistinguishingLength<E> {
+    // The length of a shortest distinguishing word, if there is one.
+    pub fn shortest(&self) -> Option<usize> {
+        self.witness.as_ref().map(Vec::len)
+    }
+}
+
+impl<S, E> DFA<S, E>
+where
+    S: Eq + Hash + Copy + Debug,
+    E: Eq + Hash + Copy + Debug,
+{
+    // Minimizes as `minimize` does, and then checks that the result accepts exactly the same
+    // words, returning a counterexample if it doesn't.
+    pub fn minimize_verified(&self) -> Result<DFA<S, E>, VerificationError<E>> {
+        let minimal = self.minimize();
+        match self.difference_witness(&minimal) {
+            None => Ok(minimal),
+            Some(word) => Err(VerificationError { word }),
+        }
+    }
+
+    // Returns a shortest word accepted by exactly one of `self` and `other`, or `None` if they
+    // accept the same language. This runs both DFAs side by side, without building the product.
+    pub fn difference_witness<S2>(&self, other: &DFA<S2, E>) -> Option<Vec<E>>
+    where
+        S2: Eq + Hash + Copy + Debug,
+    {
+        self.pair_search(other, |a, b| a != b)
+    }
+
+    // Compares the worst case length of a shortest word that tells `self` and `other` apart, which
+    // follows from their sizes alone, with the length of one that actually does. For example, a
+    // bounded check of an implementation against a specification with a known number of states
+    // only needs inputs up to `bound`.
+    pub fn distinguishing_length<S2>(&self, other: &DFA<S2, E>) -> DistinguishingLength<E>
+    where
+        S2: Eq + Hash + Copy + Debug,
+    {
+        let alphabet: HashSet<E> = self
+            .transitions()
+            .map(|(_, label, _)| label)
+            .chain(other.transitions().map(|(_, label, _)| label))
+            .collect();
+        let (n1, n2) = (
+            self.completed_size(&alphabet),
+            other.completed_size(&alphabet),
+        );
+        DistinguishingLength {
+            bound: n1 + n2 - 2,
+            witness: self.difference_witness(other),
+        }
+    }
+
+    // The number of reachable states, plus one for a rejecting sink if any of them is missing a
+    // transition for some label in `alphabet`.
+    fn completed_size(&self, alphabet: &HashSet<E>) -> usize {
+        let reachable = self.reach_order();
+        let outflows = self.transitions.by_a();
+        let complete = reachable.keys().all(|q| {
+            outflows
+                .get(q)
+                .is_some_and(|edges| edges.len() == alphabet.len())
+        });
+        reachable.len() + usize::from(!complete)
+    }
+
+    // Whether `self` and `other` accept the same words, however their states are named or
+    // arranged. For a comparison of the automata themselves, use `==`.
+    pub fn language_eq<S2>(&self, other: &DFA<S2, E>) -> bool
+    where
+        S2: Eq + Hash + Copy + Debug,
+    {
+        self.difference_witness(other).is_none()
+    }
+
+    // Returns a shortest word accepted by `self` but not by `other`, or `None` if every word that
+    // `self` accepts is also accepted by `other`.
+    pub fn inclusion_witness<S2>(&self, other: &DFA<S2, E>) -> Option<Vec<E>>
+    where
+        S2: Eq + Hash + Copy + Debug,
+    {
+        self.pair_search(other, |a, b| a && !b)
+    }
+
+    // Returns a shortest word accepted by both `self` and `other`, or `None` if no word is. This
+    // explores pairs of states on demand, without building the whole product.
+    pub fn intersects<S2>(&self, other: &DFA<S2, E>) -> Option<Vec<E>>
+    where
+        S2: Eq + Hash + Copy + Debug,
+    {
+        self.pair_search(other, |a, b| a && b)
+    }
+
+    // Searches the pairs of states reachable on the same input, and returns a shortest input
+    // leading to a pair for which `is_target` holds, given whether each state accepts.
+    pub(crate) fn pair_search<S2>(
+        &self,
+        other: &DFA<S2, E>,
+        is_target: impl Fn(bool, bool) -> bool,
+    ) -> Option<Vec<E>>
+    where
+        S2: Eq + Hash + Copy + Debug,
+    {
+        let lhs = self.delta();
+        let rhs = other.delta();
+        let alphabet: HashSet<E> = self
+            .transitions
+            .by_b()
+            .into_keys()
+            .chain(other.transitions.by_b().into_keys())
+            .collect();
+
+        let start = (Some(self.initial_state), Some(other.initial_state));
+        let mut parents: HashMap<Pair<S, S2>, Parent<S, S2, E>> = HashMap::default();
+        let mut queue = VecDeque::new();
+        parents.insert(start, None);
+        queue.push_back(start);
+        while let Some((p, q)) = queue.pop_front() {
+            let accepts_p = p.is_some_and(|p| self.final_states.contains(&p));
+            let accepts_q = q.is_some_and(|q| other.final_states.contains(&q));
+            if is_target(accepts_p, accepts_q) {
+                let mut word = Vec::new();
+                let mut cur = (p, q);
+                while let Some((prev, label)) = parents[&cur] {
+                    word.push(label);
+                    cur = prev;
+                }
+                word.reverse();
+                return Some(word);
+            }
+            for &label in &alphabet {
+                let next = (
+                    p.and_then(|p| lhs.get(&(p, label)).copied()),
+                    q.and_then(|q| rhs.get(&(q, label)).copied()),
+                );
+                // Once both sides are stuck, nothing more can be accepted.
+                if next == (None, None) {
+                    continue;
+                }
+                if let Entry::Vacant(e) = parents.entry(next) {
+                    e.insert(Some(((p, q), label)));
+                    queue.push_back(next);
+                }
+            }
+        }
+        None
+    }
+}
+
+impl<S, E> DFA<S, E>
+where
+    S: Eq + Hash + Copy + Debug,
+    E: Eq + Hash + Copy + Debug + Ord,
+{
+    // Returns the `k` first words accepted by exactly one of `self` and `other`, in the order
+    // that `words` produces them: shortest first, and then ordered by `E`. Fewer are returned if
+    // there are fewer differences.
+    pub fn differences<S2>(&self, other: &DFA<S2, E>, k: usize) -> Vec<Vec<E>>
+    where
+        S2: Eq + Hash + Copy + Debug,
+    {
+        self.product(other, |a, b| a != b)
+            .words(None)
+            .take(k)
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::DFA;
+
+    #[test]
+    fn distinguishing_length() {
+        // Words of a's whose length is a multiple of 3, and of 4: they first differ at "aaa".
+        let cycle = |n: u32| -> DFA<u32, char> {
+            DFA::new(0, vec![0], (0..n).map(|q| (q, 'a', (q + 1) % n)))
+        };
+        let result = cycle(3).distinguishing_length(&cycle(4));
+        assert_eq!(result.bound, 5);
+        assert_eq!(result.shortest(), Some(3));
+
+        // Partial DFAs get a sink each: "a" and "aa" with 2 + 1 and 3 + 1 states.
+        let a: DFA<u32, char> = DFA::new(0, vec![1], vec![(0, 'a', 1)]);
+        let aa: DFA<u32, char> = DFA::new(0, vec![2], vec![(0, 'a', 1), (1, 'a', 2)]);
+        let result = a.distinguishing_length(&aa);
+        assert_eq!(result.bound, 5);
+        assert_eq!(result.witness, Some(vec!['a']));
+        assert_eq!(a.distinguishing_length(&a).shortest(), None);
+    }
+
+    #[test]
+    fn minimize_verified() {
+        let input: DFA<u32, u8> = DFA::new(
+            0,
+            vec![2, 4],
+            vec![
+                (0, 0, 1),
+                (1, 0, 2),
+                (1, 1, 2),
+                (2, 0, 2),
+                (0, 1, 3),
+                (3, 0, 4),
+                (3, 1, 4),
+                (4, 0, 4),
+            ],
+        );
+        let minimal = input.minimize_verified().unwrap();
+        assert_eq!(minimal.states().len(), 3);
+        assert!(input.language_eq(&minimal));
+        assert_ne!(input, minimal);
+        assert!(!input.language_eq(&DFA::new(0, vec![2], vec![(0, 0, 1), (1, 0, 2)])));
+    }
+
+    #[test]
+    fn difference_witness() {
+        let ab: DFA<u32, char> = DFA::new(0, vec![2], vec![(0, 'a', 1), (1, 'b', 2)]);
+        let abb: DFA<u32, char> = DFA::new(0, vec![2], vec![(0, 'a', 1), (1, 'b', 2), (2, 'b', 2)]);
+        assert_eq!(ab.difference_witness(&ab), None);
+        assert_eq!(ab.difference_witness(&abb), Some(vec!['a', 'b', 'b']));
+        assert_eq!(abb.difference_witness(&ab), Some(vec!['a', 'b', 'b']));
+        assert_eq!(ab.inclusion_witness(&abb), None);
+        assert_eq!(abb.inclusion_witness(&ab), Some(vec!['a', 'b', 'b']));
+    }
+
+    #[test]
+    fn intersects() {
+        // Words over {a, b} with an even number of a's, and words ending in "ab".
+        let even: DFA<u32, char> = DFA::new(
+            0,
+            vec![0],
+            vec![(0, 'a', 1), (0, 'b', 0), (1, 'a', 0), (1, 'b', 1)],
+        );
+        let ends_ab: DFA<u32, char> = DFA::new(
+            0,
+            vec![2],
+            vec![
+                (0, 'a', 1),
+                (0, 'b', 0),
+                (1, 'a', 1),
+                (1, 'b', 2),
+                (2, 'a', 1),
+                (2, 'b', 0),
+            ],
+        );
+        let word = even.intersects(&ends_ab).unwrap();
+        assert_eq!(word.len(), 3);
+        assert!(even.accepts(&word) && ends_ab.accepts(&word));
+
+        let only_b: DFA<u32, char> = DFA::new(0, vec![0], vec![(0, 'b', 0)]);
+        let needs_a: DFA<u32, char> = DFA::new(0, vec![1], vec![(0, 'a', 1)]);
+        assert_eq!(only_b.intersects(&needs_a), None);
+    }
+
+    #[test]
+    fn differences() {
+        // Words of a's of length at most 3, and of even length.
+        let short: DFA<u32, char> = DFA::new(
+            0,
+            vec![0, 1, 2, 3],
+            vec![(0, 'a', 1), (1, 'a', 2), (2, 'a', 3)],
+        );
+        let even: DFA<u32, char> = DFA::new(0, vec![0], vec![(0, 'a', 1), (1, 'a', 0)]);
+        let words: Vec<String> = short
+            .differences(&even, 4)
+            .into_iter()
+            .map(|w| w.into_iter().collect())
+            .collect();
+        assert_eq!(words, vec!["a", "aaa", "aaaa", "aaaaaa"]);
+        assert!(short.differences(&short, 10).is_empty());
+    }
+}
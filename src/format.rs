@@ -0,0 +1,886 @@
+// Reading and writing DFAs as text: Graphviz DOT, JSON, and the plain `.dfa` format of the
+// reference implementation. Parsed states and labels are interned as `u32`s, with their names
+// kept alongside.
+
+use std::collections::VecDeque;
+use std::fmt::{self, Debug, Display};
+use std::hash::Hash;
+
+use crate::hash::HashMap;
+use crate::sfa::{Predicate, SFA};
+use crate::DFA;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Format {
+    // A `digraph` with one edge per transition, labeled by its symbol. Accepting states are drawn
+    // as double circles, and an edge from a point-shaped node marks the initial state.
+    Dot,
+    // `{"initial": q, "accepting": [q, ...], "transitions": [[src, label, dst], ...]}`, where
+    // states and labels are strings or numbers.
+    Json,
+    // Whitespace-separated integers: a header `states transitions initial accepting`, then a
+    // `src label dst` triple per transition, then the accepting states.
+    Dfa,
+}
+
+impl Format {
+    // Guesses the format from a file name's extension.
+    pub fn from_path(path: &str) -> Option<Format> {
+        match path.rsplit('.').next()? {
+            "dot" | "gv" => Some(Format::Dot),
+            "json" => Some(Format::Json),
+            "dfa" => Some(Format::Dfa),
+            _ => None,
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<Format> {
+        match name {
+            "dot" => Some(Format::Dot),
+            "json" => Some(Format::Json),
+            "dfa" => Some(Format::Dfa),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParseError {
+    pub message: String,
+}
+
+impl ParseError {
+    pub(crate) fn new(message: impl Into<String>) -> ParseError {
+        ParseError {
+            message: message.into(),
+        }
+    }
+}
+
+impl Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "parse error: {}", self.message)
+    }
+}
+
+impl std::error::Error for ParseError {}
+
+// A DFA whose states and labels are numbered, with the name of each state at its number in
+// `states` and the name of each label at its number in `labels`.
+#[derive(Debug, Clone)]
+pub struct NamedDFA {
+    pub dfa: DFA<u32, u32>,
+    pub states: Vec<String>,
+    pub labels: Vec<String>,
+}
+
+impl NamedDFA {
+    // Names every state and label by its `Display` form.
+    pub fn from_dfa<S, E>(dfa: &DFA<S, E>) -> NamedDFA
+    where
+        S: Eq + Hash + Copy + Debug + Display,
+        E: Eq + Hash + Copy + Debug + Display,
+    {
+        let name = |q: &S| q.to_string();
+        Builder::ordered(
+            name(&dfa.initial_state),
+            dfa.states.iter().map(name).collect(),
+            dfa.final_states.iter().map(name).collect(),
+            (&dfa.transitions)
+                .into_iter()
+                .map(|(src, label, dst)| (name(src), label.to_string(), name(dst)))
+                .collect(),
+        )
+    }
+
+    // Names every state of an SFA by its `Display` form, and every predicate likewise. Each
+    // predicate becomes a single label.
+    pub fn from_sfa<S, P>(sfa: &SFA<S, P>) -> NamedDFA
+    where
+        S: Eq + Hash + Copy + Debug + Display,
+        P: Predicate + Display,
+    {
+        let name = |q: &S| q.to_string();
+        Builder::ordered(
+            name(&sfa.initial_state),
+            Vec::new(),
+            sfa.final_states.iter().map(name).collect(),
+            sfa.transitions
+                .iter()
+                .map(|(src, pred, dst)| (name(src), pred.to_string(), name(dst)))
+                .collect(),
+        )
+    }
+
+    // Prunes and minimizes the DFA, keeping the names of the states that remain.
+    pub fn minimize(&self) -> NamedDFA {
+        NamedDFA {
+            dfa: self.dfa.prune_and_minimize(),
+            states: self.states.clone(),
+            labels: self.labels.clone(),
+        }
+    }
+
+    // Whether the word spelled by these label names is accepted.
+    pub fn accepts(&self, word: &[&str]) -> bool {
+        let word: Option<Vec<u32>> = word
+            .iter()
+            .map(|&name| self.labels.iter().position(|l| l == name).map(|i| i as u32))
+            .collect();
+        word.is_some_and(|word| self.dfa.accepts(&word))
+    }
+
+    // Renumbers `other`'s labels so that labels with the same name get the same number in both,
+    // adding any names that only `other` has to `self.labels`. Parsing two files separately
+    // numbers their labels independently, so this is needed before comparing them.
+    pub fn align_labels(&mut self, other: &mut NamedDFA) {
+        let mut ids: HashMap<String, u32> = self
+            .labels
+            .iter()
+            .enumerate()
+            .map(|(i, name)| (name.clone(), i as u32))
+            .collect();
+        let renumbered: Vec<u32> = other
+            .labels
+            .iter()
+            .map(|name| {
+                let n = ids.len() as u32;
+                *ids.entry(name.clone()).or_insert_with(|| {
+                    self.labels.push(name.clone());
+                    n
+                })
+            })
+            .collect();
+        other.dfa.transitions = (&other.dfa.transitions)
+            .into_iter()
+            .map(|&(src, e, dst)| (src, renumbered[e as usize], dst))
+            .collect();
+        other.labels = self.labels.clone();
+    }
+
+    // Writes a word over this DFA's labels, separated by spaces, or `ε` for the empty word.
+    pub fn render_word(&self, word: &[u32]) -> String {
+        if word.is_empty() {
+            return String::from("ε");
+        }
+        let names: Vec<&str> = word
+            .iter()
+            .map(|&e| self.labels[e as usize].as_str())
+            .collect();
+        names.join(" ")
+    }
+
+    pub fn parse(text: &str, format: Format) -> Result<NamedDFA, ParseError> {
+        match format {
+            Format::Dot => parse_dot(text),
+            Format::Json => parse_json(text),
+            Format::Dfa => parse_dfa(text),
+        }
+    }
+
+    // Writes the DFA out. States and transitions come out in numeric order, so the output is the
+    // same every time.
+    pub fn render(&self, format: Format) -> String {
+        let mut transitions: Vec<(u32, u32, u32)> =
+            (&self.dfa.transitions).into_iter().copied().collect();
+        transitions.sort_unstable();
+        let mut accepting: Vec<u32> = self.dfa.final_states.iter().copied().collect();
+        accepting.sort_unstable();
+        let state = |q: u32| &self.states[q as usize];
+        let label = |e: u32| &self.labels[e as usize];
+        match format {
+            Format::Dot => {
+                let mut out =
+                    String::from("digraph dfa {\n  rankdir=LR;\n  node [shape=circle];\n");
+                for &q in &accepting {
+                    out += &format!("  {} [shape=doublecircle];\n", quote(state(q)));
+                }
+                out += "  __start [shape=point];\n";
+                out += &format!("  __start -> {};\n", quote(state(self.dfa.initial_state)));
+                for &(src, e, dst) in &transitions {
+                    out += &format!(
+                        "  {} -> {} [label={}];\n",
+                        quote(state(src)),
+                        quote(state(dst)),
+                        quote(label(e))
+                    );
+                }
+                out + "}\n"
+            }
+            Format::Json => {
+                let accepting: Vec<String> = accepting.iter().map(|&q| quote(state(q))).collect();
+                let transitions: Vec<String> = transitions
+                    .iter()
+                    .map(|&(src, e, dst)| {
+                        format!(
+                            "[{}, {}, {}]",
+                            quote(state(src)),
+                            quote(label(e)),
+                            quote(state(dst))
+                        )
+                    })
+                    .collect();
+                format!(
+                    "{{\"initial\": {}, \"accepting\": [{}], \"transitions\": [{}]}}\n",
+                    quote(state(self.dfa.initial_state)),
+                    accepting.join(", "),
+                    transitions.join(", "),
+                )
+            }
+            Format::Dfa => {
+                // This format can only name things by numbers. States can take our own, but labels
+                // are part of the language, so they keep their names when those are all numbers,
+                // as they are when the DFA was read from this format.
+                let numeric: Option<Vec<u32>> =
+                    self.labels.iter().map(|name| name.parse().ok()).collect();
+                let label = |e: u32| numeric.as_ref().map_or(e, |names| names[e as usize]);
+                let mut out = format!(
+                    "{} {} {} {}\n\n",
+                    self.states.len(),
+                    transitions.len(),
+                    self.dfa.initial_state,
+                    accepting.len()
+                );
+                for &(src, e, dst) in &transitions {
+                    out += &format!("{} {} {}\n", src, label(e), dst);
+                }
+                let accepting: Vec<String> = accepting.iter().map(|q| q.to_string()).collect();
+                out + "\n" + &accepting.join(" ") + "\n"
+            }
+        }
+    }
+}
+
+// Interns names as they are parsed.
+#[derive(Default)]
+struct Builder {
+    states: HashMap<String, u32>,
+    labels: HashMap<String, u32>,
+    accepting: Vec<u32>,
+    transitions: Vec<(u32, u32, u32)>,
+}
+
+impl Builder {
+    fn state(&mut self, name: &str) -> u32 {
+        let n = self.states.len() as u32;
+        *self.states.entry(name.to_string()).or_insert(n)
+    }
+
+    fn transition(&mut self, src: &str, label: &str, dst: &str) {
+        let (src, dst) = (self.state(src), self.state(dst));
+        let n = self.labels.len() as u32;
+        let label = *self.labels.entry(label.to_string()).or_insert(n);
+        self.transitions.push((src, label, dst));
+    }
+
+    // Numbers the states in the order a breadth-first search from `initial` reaches them, taking
+    // labels in name order, then any other states in name order, and numbers the labels in name
+    // order. Only the names matter, so an automaton comes out the same whatever order its hash
+    // sets happen to iterate in.
+    fn ordered(
+        initial: String,
+        mut states: Vec<String>,
+        accepting: Vec<String>,
+        mut transitions: Vec<(String, String, String)>,
+    ) -> NamedDFA {
+        transitions.sort_unstable_by(|a, b| (&a.1, &a.0, &a.2).cmp(&(&b.1, &b.0, &b.2)));
+        let mut builder = Builder::default();
+        for (_, label, _) in &transitions {
+            let n = builder.labels.len() as u32;
+            builder.labels.entry(label.clone()).or_insert(n);
+        }
+        let mut outflows: HashMap<&str, Vec<&str>> = HashMap::default();
+        for (src, _, dst) in &transitions {
+            outflows.entry(src).or_default().push(dst);
+        }
+        let mut queue = VecDeque::from([initial.as_str()]);
+        builder.state(&initial);
+        while let Some(src) = queue.pop_front() {
+            for &dst in outflows.get(src).into_iter().flatten() {
+                if !builder.states.contains_key(dst) {
+                    builder.state(dst);
+                    queue.push_back(dst);
+                }
+            }
+        }
+        states.extend(accepting.iter().cloned());
+        states.sort_unstable();
+        for q in &states {
+            builder.state(q);
+        }
+        for q in &accepting {
+            let q = builder.state(q);
+            builder.accepting.push(q);
+        }
+        for (src, label, dst) in &transitions {
+            builder.transition(src, label, dst);
+        }
+        let initial = builder.state(&initial);
+        builder.finish(initial)
+    }
+
+    fn finish(self, initial: u32) -> NamedDFA {
+        let names = |map: HashMap<String, u32>| {
+            let mut names = vec![String::new(); map.len()];
+            for (name, i) in map {
+                names[i as usize] = name;
+            }
+            names
+        };
+        NamedDFA {
+            dfa: DFA::with_states(
+                initial,
+                0..self.states.len() as u32,
+                self.accepting,
+                self.transitions,
+            ),
+            states: names(self.states),
+            labels: names(self.labels),
+        }
+    }
+}
+
+// Quotes a name for DOT or JSON, with JSON's escapes. DOT reads `\"`, `\\` and `\n` the same
+// way, and its parser here reads the rest as JSON does.
+fn quote(name: &str) -> String {
+    let mut out = String::from("\"");
+    for c in name.chars() {
+        match c {
+            '"' => out += "\\\"",
+            '\\' => out += "\\\\",
+            '\n' => out += "\\n",
+            '\r' => out += "\\r",
+            '\t' => out += "\\t",
+            '\u{8}' => out += "\\b",
+            '\u{c}' => out += "\\f",
+            c if c.is_control() => out += &format!("\\u{:04x}", c as u32),
+            c => out.push(c),
+        }
+    }
+    out + "\""
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Token {
+    // An identifier, number, or quoted string, unquoted.
+    Id(String),
+    Arrow,
+    Punct(char),
+}
+
+fn tokenize_dot(text: &str) -> Result<Vec<Token>, ParseError> {
+    let mut tokens = Vec::new();
+    let mut chars = text.chars().peekable();
+    while let Some(c) = chars.next() {
+        match c {
+            c if c.is_whitespace() => {}
+            '#' => {
+                chars.by_ref().find(|&c| c == '\n');
+            }
+            '/' if chars.peek() == Some(&'/') => {
+                chars.by_ref().find(|&c| c == '\n');
+            }
+            '/' if chars.peek() == Some(&'*') => {
+                chars.next();
+                let mut prev = ' ';
+                chars
+                    .by_ref()
+                    .find(|&c| std::mem::replace(&mut prev, c) == '*' && c == '/');
+            }
+            '-' if chars.peek() == Some(&'>') => {
+                chars.next();
+                tokens.push(Token::Arrow);
+            }
+            '"' => tokens.push(Token::Id(string_literal(&mut chars)?)),
+            '{' | '}' | '[' | ']' | ';' | ',' | '=' => tokens.push(Token::Punct(c)),
+            c if c.is_alphanumeric() || c == '_' || c == '.' || c == '-' => {
+                let mut id = c.to_string();
+                while let Some(&c) = chars.peek() {
+                    if !(c.is_alphanumeric() || c == '_' || c == '.') {
+                        break;
+                    }
+                    id.push(c);
+                    chars.next();
+                }
+                tokens.push(Token::Id(id));
+            }
+            c => return Err(ParseError::new(format!("unexpected character {:?}", c))),
+        }
+    }
+    Ok(tokens)
+}
+
+// Reads the rest of a string literal whose opening quote has been consumed, decoding JSON's
+// escapes. Any other escaped character, such as DOT's `\l`, stands for itself.
+fn string_literal(chars: &mut impl Iterator<Item = char>) -> Result<String, ParseError> {
+    let mut s = String::new();
+    loop {
+        match chars.next() {
+            None => return Err(ParseError::new("unterminated string")),
+            Some('"') => return Ok(s),
+            Some('\\') => match chars.next() {
+                Some('n') => s.push('\n'),
+                Some('r') => s.push('\r'),
+                Some('t') => s.push('\t'),
+                Some('b') => s.push('\u{8}'),
+                Some('f') => s.push('\u{c}'),
+                Some('u') => s.push(unicode_escape(chars)?),
+                Some(c) => s.push(c),
+                None => return Err(ParseError::new("unterminated string")),
+            },
+            Some(c) => s.push(c),
+        }
+    }
+}
+
+// Reads the hex digits of a `\u` escape, and of a second one after it if the first is a high
+// surrogate, as JSON writes characters outside the Basic Multilingual Plane.
+fn unicode_escape(chars: &mut impl Iterator<Item = char>) -> Result<char, ParseError> {
+    fn hex(chars: &mut impl Iterator<Item = char>) -> Result<u32, ParseError> {
+        let digits: String = chars.take(4).collect();
+        match u32::from_str_radix(&digits, 16) {
+            Ok(n) if digits.len() == 4 => Ok(n),
+            _ => Err(ParseError::new(format!("invalid escape \\u{}", digits))),
+        }
+    }
+    let first = hex(chars)?;
+    let code = if (0xd800..0xdc00).contains(&first) {
+        let second = match (chars.next(), chars.next()) {
+            (Some('\\'), Some('u')) => hex(chars)?,
+            _ => return Err(ParseError::new("unpaired surrogate")),
+        };
+        if !(0xdc00..0xe000).contains(&second) {
+            return Err(ParseError::new("unpaired surrogate"));
+        }
+        0x10000 + ((first - 0xd800) << 10) + (second - 0xdc00)
+    } else {
+        first
+    };
+    char::from_u32(code).ok_or_else(|| ParseError::new("unpaired surrogate"))
+}
+
+// Parses the subset of DOT that describes automata: edges with `label` attributes, accepting
+// states given `shape=doublecircle` (directly or through a `node` default), and the initial
+// state as the target of an edge from a `shape=point` node.
+fn parse_dot(text: &str) -> Result<NamedDFA, ParseError> {
+    let tokens = tokenize_dot(text)?;
+    let mut builder = Builder::default();
+    let mut points: Vec<String> = Vec::new();
+    let mut initial: Option<String> = None;
+    let mut default_shape = String::new();
+
+    let mut i = 0;
+    let id = |i: usize| match tokens.get(i) {
+        Some(Token::Id(id)) => Some(id.clone()),
+        _ => None,
+    };
+    // Parses an attribute list starting at `i`, if there is one.
+    let attributes = |i: &mut usize| -> Result<HashMap<String, String>, ParseError> {
+        let mut attrs = HashMap::default();
+        if tokens.get(*i) != Some(&Token::Punct('[')) {
+            return Ok(attrs);
+        }
+        *i += 1;
+        loop {
+            match tokens.get(*i) {
+                Some(Token::Punct(']')) => {
+                    *i += 1;
+                    return Ok(attrs);
+                }
+                Some(Token::Punct(',')) | Some(Token::Punct(';')) => *i += 1,
+                Some(Token::Id(key)) if tokens.get(*i + 1) == Some(&Token::Punct('=')) => {
+                    let value =
+                        id(*i + 2).ok_or_else(|| ParseError::new("expected attribute value"))?;
+                    attrs.insert(key.clone(), value);
+                    *i += 3;
+                }
+                _ => return Err(ParseError::new("malformed attribute list")),
+            }
+        }
+    };
+
+    while i < tokens.len() {
+        match &tokens[i] {
+            Token::Id(keyword)
+                if ["strict", "digraph", "graph", "subgraph"].contains(&keyword.as_str())
+                    && tokens.get(i + 1) != Some(&Token::Punct('[')) =>
+            {
+                i += 1;
+                if id(i).is_some() {
+                    i += 1;
+                }
+            }
+            Token::Id(keyword)
+                if (keyword == "node" || keyword == "edge" || keyword == "graph") =>
+            {
+                i += 1;
+                let attrs = attributes(&mut i)?;
+                if keyword == "node" {
+                    if let Some(shape) = attrs.get("shape") {
+                        default_shape = shape.clone();
+                    }
+                }
+            }
+            Token::Id(_) if tokens.get(i + 1) == Some(&Token::Punct('=')) => i += 3,
+            Token::Id(first) => {
+                let mut chain = vec![first.clone()];
+                i += 1;
+                while tokens.get(i) == Some(&Token::Arrow) {
+                    chain.push(id(i + 1).ok_or_else(|| ParseError::new("expected node after ->"))?);
+                    i += 2;
+                }
+                let attrs = attributes(&mut i)?;
+                if chain.len() == 1 {
+                    let shape = attrs.get("shape").unwrap_or(&default_shape);
+                    if shape == "point" {
+                        points.push(first.clone());
+                    } else {
+                        let q = builder.state(first);
+                        if shape == "doublecircle" {
+                            builder.accepting.push(q);
+                        }
+                    }
+                    continue;
+                }
+                for pair in chain.windows(2) {
+                    if points.contains(&pair[0]) {
+                        initial = Some(pair[1].clone());
+                        continue;
+                    }
+                    let label = attrs.get("label").ok_or_else(|| {
+                        ParseError::new(format!("edge {} -> {} has no label", pair[0], pair[1]))
+                    })?;
+                    builder.transition(&pair[0], label, &pair[1]);
+                }
+            }
+            _ => i += 1,
+        }
+    }
+    let initial = initial.ok_or_else(|| {
+        ParseError::new("no edge from a point-shaped node marks the initial state")
+    })?;
+    let initial = builder.state(&initial);
+    Ok(builder.finish(initial))
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Json {
+    Null,
+    Bool(bool),
+    // Numbers are kept as written, since they are only ever used as names.
+    Number(String),
+    String(String),
+    Array(Vec<Json>),
+    Object(Vec<(String, Json)>),
+}
+
+struct JsonParser<'a> {
+    chars: std::iter::Peekable<std::str::Chars<'a>>,
+}
+
+impl JsonParser<'_> {
+    fn skip_whitespace(&mut self) {
+        while self.chars.peek().is_some_and(|c| c.is_whitespace()) {
+            self.chars.next();
+        }
+    }
+
+    fn expect(&mut self, expected: char) -> Result<(), ParseError> {
+        self.skip_whitespace();
+        match self.chars.next() {
+            Some(c) if c == expected => Ok(()),
+            other => Err(ParseError::new(format!(
+                "expected {:?}, found {:?}",
+                expected, other
+            ))),
+        }
+    }
+
+    // Parses comma-separated items up to `close`, the opening bracket having been consumed.
+    fn items<T>(
+        &mut self,
+        close: char,
+        mut item: impl FnMut(&mut Self) -> Result<T, ParseError>,
+    ) -> Result<Vec<T>, ParseError> {
+        let mut items = Vec::new();
+        self.skip_whitespace();
+        if self.chars.peek() == Some(&close) {
+            self.chars.next();
+            return Ok(items);
+        }
+        loop {
+            items.push(item(self)?);
+            self.skip_whitespace();
+            match self.chars.next() {
+                Some(',') => {}
+                Some(c) if c == close => return Ok(items),
+                other => {
+                    return Err(ParseError::new(format!(
+                        "expected ',' or {:?}, found {:?}",
+                        close, other
+                    )))
+                }
+            }
+        }
+    }
+
+    fn value(&mut self) -> Result<Json, ParseError> {
+        self.skip_whitespace();
+        match self.chars.next() {
+            Some('{') => Ok(Json::Object(self.items('}', |p| {
+                p.expect('"')?;
+                let key = string_literal(&mut p.chars)?;
+                p.expect(':')?;
+                Ok((key, p.value()?))
+            })?)),
+            Some('[') => Ok(Json::Array(self.items(']', |p| p.value())?)),
+            Some('"') => Ok(Json::String(string_literal(&mut self.chars)?)),
+            Some(c) if c == '-' || c.is_ascii_alphanumeric() => {
+                let mut word = c.to_string();
+                while let Some(&c) = self.chars.peek() {
+                    if !(c.is_ascii_alphanumeric() || "+-.".contains(c)) {
+                        break;
+                    }
+                    word.push(c);
+                    self.chars.next();
+                }
+                match word.as_str() {
+                    "null" => Ok(Json::Null),
+                    "true" => Ok(Json::Bool(true)),
+                    "false" => Ok(Json::Bool(false)),
+                    _ if word.parse::<f64>().is_ok() => Ok(Json::Number(word)),
+                    _ => Err(ParseError::new(format!("unexpected {:?}", word))),
+                }
+            }
+            other => Err(ParseError::new(format!("unexpected {:?}", other))),
+        }
+    }
+}
+
+fn parse_json(text: &str) -> Result<NamedDFA, ParseError> {
+    let mut parser = JsonParser {
+        chars: text.chars().peekable(),
+    };
+    let fields = match parser.value()? {
+        Json::Object(fields) => fields,
+        _ => return Err(ParseError::new("expected an object")),
+    };
+    let field = |key: &str| {
+        fields
+            .iter()
+            .find(|(k, _)| k == key)
+            .map(|(_, v)| v)
+            .ok_or_else(|| ParseError::new(format!("missing {:?}", key)))
+    };
+    let name = |value: &Json| match value {
+        Json::String(s) | Json::Number(s) => Ok(s.clone()),
+        _ => Err(ParseError::new("names must be strings or numbers")),
+    };
+    let array = |value: &Json| match value {
+        Json::Array(items) => Ok(items.clone()),
+        _ => Err(ParseError::new("expected an array")),
+    };
+
+    let mut builder = Builder::default();
+    let initial = builder.state(&name(field("initial")?)?);
+    for q in array(field("accepting")?)? {
+        let q = builder.state(&name(&q)?);
+        builder.accepting.push(q);
+    }
+    for t in array(field("transitions")?)? {
+        match array(&t)?.as_slice() {
+            [src, label, dst] => builder.transition(&name(src)?, &name(label)?, &name(dst)?),
+            _ => return Err(ParseError::new("transitions must be [src, label, dst]")),
+        }
+    }
+    Ok(builder.finish(initial))
+}
+
+// Parses a word as a JSON array of label names.
+#[cfg(feature = "wasm")]
+pub(crate) fn parse_json_word(text: &str) -> Result<Vec<String>, ParseError> {
+    let mut parser = JsonParser {
+        chars: text.chars().peekable(),
+    };
+    match parser.value()? {
+        Json::Array(items) => items
+            .into_iter()
+            .map(|item| match item {
+                Json::String(s) | Json::Number(s) => Ok(s),
+                _ => Err(ParseError::new("names must be strings or numbers")),
+            })
+            .collect(),
+        _ => Err(ParseError::new("expected an array")),
+    }
+}
+
+fn parse_dfa(text: &str) -> Result<NamedDFA, ParseError> {
+    let mut words = text.split_whitespace();
+    let mut next = || {
+        words
+            .next()
+            .ok_or_else(|| ParseError::new("unexpected end of input"))
+    };
+    let mut number = || -> Result<usize, ParseError> {
+        let word = next()?;
+        word.parse()
+            .map_err(|_| ParseError::new(format!("expected a number, found {:?}", word)))
+    };
+    let (_, transitions, initial, accepting) = (number()?, number()?, number()?, number()?);
+    let mut builder = Builder::default();
+    let initial = builder.state(&initial.to_string());
+    for _ in 0..transitions {
+        let (src, label, dst) = (number()?, number()?, number()?);
+        builder.transition(&src.to_string(), &label.to_string(), &dst.to_string());
+    }
+    for _ in 0..accepting {
+        let q = builder.state(&number()?.to_string());
+        builder.accepting.push(q);
+    }
+    Ok(builder.finish(initial))
+}
+
+#[cfg(test)]
+mod test {
+    use super::{quote, Format, NamedDFA};
+    use crate::Regex;
+
+    #[test]
+    fn parse_reference_files() {
+        let example =
+            NamedDFA::parse(include_str!("../references/example1.dfa"), Format::Dfa).unwrap();
+        assert_eq!(example.dfa.transitions.len(), 10);
+        assert!(example.accepts(&["0", "1", "1"]));
+        assert!(!example.accepts(&["0"]));
+    }
+
+    #[test]
+    fn parse_handwritten_dot() {
+        let dot = r#"
+            digraph finite_state_machine {
+                rankdir=LR;
+                node [shape = doublecircle]; done;
+                node [shape = circle];
+                start [shape=point];
+                start -> q0;
+                q0 -> q1 [label="a"];
+                q1 -> done [ label = "b" ];  // trailing comment
+                /* block
+                   comment */
+                done -> done [label="b"];
+            }
+        "#;
+        let named = NamedDFA::parse(dot, Format::Dot).unwrap();
+        assert!(named.accepts(&["a", "b", "b"]));
+        assert!(!named.accepts(&["a"]));
+    }
+
+    #[test]
+    fn round_trips() {
+        let json = r#"{"initial": "s", "accepting": [1], "transitions": [["s", "x y", 1], [1, "\"", "s"]]}"#;
+        let named = NamedDFA::parse(json, Format::Json).unwrap();
+        assert!(named.accepts(&["x y", "\"", "x y"]));
+        for format in [Format::Dot, Format::Json, Format::Dfa] {
+            let text = named.render(format);
+            let reparsed = NamedDFA::parse(&text, format).unwrap();
+            assert!(reparsed.dfa.isomorphic(&named.dfa).is_some(), "{}", text);
+        }
+        assert!(NamedDFA::parse("{\"initial\": 0}", Format::Json).is_err());
+    }
+
+    #[test]
+    fn json_escapes() {
+        let state = r#""a\r\b\f\/""#;
+        let json = format!(
+            r#"{{"initial": {}, "accepting": [], "transitions": [[{}, "\u00e9\ud83d\ude00", 1]]}}"#,
+            state, state
+        );
+        let named = NamedDFA::parse(&json, Format::Json).unwrap();
+        assert_eq!(named.states, vec!["a\r\u{8}\u{c}/", "1"]);
+        assert_eq!(named.labels, vec!["\u{e9}\u{1f600}"]);
+        for bad in [r#""\u12""#, r#""\ud83d""#, r#""\ude00""#] {
+            let json = format!(
+                r#"{{"initial": {}, "accepting": [], "transitions": []}}"#,
+                bad
+            );
+            assert!(NamedDFA::parse(&json, Format::Json).is_err(), "{}", bad);
+        }
+
+        // Names with control characters survive a round trip, written as valid JSON.
+        let names = [
+            "tab\there",
+            "cr\r",
+            "nul\u{0}",
+            "bell\u{7}",
+            "del\u{7f}",
+            "q\"\\",
+        ];
+        let transitions = names
+            .iter()
+            .map(|name| format!(r#"[0, {}, 0]"#, quote(name)));
+        let json = format!(
+            r#"{{"initial": 0, "accepting": [0], "transitions": [{}]}}"#,
+            transitions.collect::<Vec<_>>().join(", ")
+        );
+        let named = NamedDFA::parse(&json, Format::Json).unwrap();
+        let text = named.render(Format::Json);
+        assert!(
+            !text.chars().any(|c| c.is_control() && c != '\n'),
+            "{}",
+            text
+        );
+        assert!(text.contains(r#""nul\u0000""#) && text.contains(r#""del\u007f""#));
+        let reparsed = NamedDFA::parse(&text, Format::Json).unwrap();
+        let mut labels = reparsed.labels.clone();
+        labels.sort();
+        let mut expected: Vec<String> = names.iter().map(|name| name.to_string()).collect();
+        expected.sort();
+        assert_eq!(labels, expected);
+    }
+
+    #[test]
+    fn dfa_format_keeps_labels() {
+        // 1 0*, whose labels are numbered in the order they appear: "1" first.
+        let text = "2 3 0 1\n\n0 1 1\n1 0 1\n0 0 2\n\n1\n";
+        let named = NamedDFA::parse(text, Format::Dfa).unwrap();
+        let minimal = named.minimize();
+        let reparsed = NamedDFA::parse(&minimal.render(Format::Dfa), Format::Dfa).unwrap();
+        for word in [&["1"][..], &["1", "0", "0"], &["0", "1"], &["0"]] {
+            assert_eq!(reparsed.accepts(word), named.accepts(word), "{:?}", word);
+        }
+        assert!(reparsed.accepts(&["1", "0"]));
+    }
+
+    #[test]
+    fn compiled_output_is_stable() {
+        // Every map below is built with a fresh random seed, so differences would show up.
+        let render = || {
+            let regex = Regex::parse("(ab|cd)*e").unwrap();
+            NamedDFA::from_sfa(&regex.to_dfa().minimize()).render(Format::Json)
+        };
+        let first = render();
+        for _ in 0..20 {
+            assert_eq!(render(), first);
+        }
+    }
+
+    #[test]
+    fn align_labels() {
+        let mut ab = NamedDFA::parse(
+            r#"{"initial": 0, "accepting": [2], "transitions": [[0, "a", 1], [1, "b", 2]]}"#,
+            Format::Json,
+        )
+        .unwrap();
+        let mut ac = NamedDFA::parse(
+            r#"{"initial": 0, "accepting": [1], "transitions": [[0, "a", 1], [1, "c", 2]]}"#,
+            Format::Json,
+        )
+        .unwrap();
+        ab.align_labels(&mut ac);
+        assert_eq!(ab.labels, vec!["a", "b", "c"]);
+        assert!(ac.accepts(&["a"]));
+        let witness = ab.dfa.difference_witness(&ac.dfa).unwrap();
+        assert_eq!(ab.render_word(&witness), "a");
+        assert_eq!(ab.render_word(&[]), "ε");
+    }
+}
@@ -0,0 +1,214 @@
+use std::collections::VecDeque;
+use std::fmt::Debug;
+use std::hash::Hash;
+
+use crate::hash::{HashMap, HashSet};
+use crate::table::{Table, Table4};
+use crate::{DFA, NFA};
+
+// A (nondeterministic) finite-state transducer, reading symbols of type `A` and writing symbols
+// of type `B`. Each transition is `(src, input, output, dst)`, where a `None` input consumes
+// nothing and a `None` output writes nothing.
+#[derive(Debug, Clone)]
+pub struct FST<S, A, B> {
+    initial_state: S,
+    final_states: HashSet<S>,
+    transitions: Table4<S, Option<A>, Option<B>, S>,
+}
+
+impl<S, A, B> FST<S, A, B>
+where
+    S: Eq + Hash + Copy + Debug,
+    A: Eq + Hash + Copy + Debug,
+    B: Eq + Hash + Copy + Debug,
+{
+    pub fn new(
+        initial_state: S,
+        final_states: impl IntoIterator<Item = S>,
+        transitions: impl IntoIterator<Item = (S, Option<A>, Option<B>, S)>,
+    ) -> FST<S, A, B> {
+        FST {
+            initial_state,
+            final_states: final_states.into_iter().collect(),
+            transitions: transitions.into_iter().collect(),
+        }
+    }
+
+    // Swaps inputs and outputs, so that the transducer relates each output back to its inputs.
+    pub fn invert(&self) -> FST<S, B, A> {
+        FST {
+            initial_state: self.initial_state,
+            final_states: self.final_states.clone(),
+            transitions: (&self.transitions)
+                .into_iter()
+                .map(|&(src, input, output, dst)| (src, output, input, dst))
+                .collect(),
+        }
+    }
+
+    fn states(&self) -> HashSet<S> {
+        let mut states: HashSet<S> = self.final_states.clone();
+        states.insert(self.initial_state);
+        for &(src, _, _, dst) in &self.transitions {
+            states.insert(src);
+            states.insert(dst);
+        }
+        states
+    }
+
+    // Chains this transducer into `other`: the result maps `x` to `z` whenever this maps `x` to
+    // some `y` and `other` maps `y` to `z`. States are pairs of states, numbered from 0.
+    pub fn compose<S2, C>(&self, other: &FST<S2, B, C>) -> FST<u32, A, C>
+    where
+        S2: Eq + Hash + Copy + Debug,
+        C: Eq + Hash + Copy + Debug,
+    {
+        // Join this transducer's outputs to `other`'s inputs. Staying put is a move that writes
+        // and reads nothing, so that either side can move on its own without the other reading.
+        let lhs: Table<S, Option<B>, (Option<A>, S)> = (&self.transitions)
+            .into_iter()
+            .map(|&(src, input, mid, dst)| (src, mid, (input, dst)))
+            .chain(self.states().into_iter().map(|q| (q, None, (None, q))))
+            .collect();
+        let rhs: Table<S2, Option<B>, (Option<C>, S2)> = (&other.transitions)
+            .into_iter()
+            .map(|&(src, read, output, dst)| (src, read, (output, dst)))
+            .chain(other.states().into_iter().map(|q| (q, None, (None, q))))
+            .collect();
+        let start = (self.initial_state, other.initial_state);
+        let joined = lhs.join_b_from(&rhs, start, |(_, p), (_, q)| (p, q));
+
+        let mut ids: HashMap<(S, S2), u32> = HashMap::default();
+        ids.insert(start, 0);
+        let mut transitions = Vec::new();
+        for (src, _, ((input, p), (output, q))) in joined {
+            // Both staying put does nothing.
+            if (input, output, (p, q)) == (None, None, src) {
+                continue;
+            }
+            let n = ids.len() as u32;
+            let dst = *ids.entry((p, q)).or_insert(n);
+            transitions.push((ids[&src], input, output, dst));
+        }
+        let final_states = ids.iter().filter_map(|(&(p, q), &id)| {
+            (self.final_states.contains(&p) && other.final_states.contains(&q)).then_some(id)
+        });
+        FST::new(0, final_states, transitions)
+    }
+
+    // The image of `dfa`'s language: every output this transducer can produce from an input that
+    // `dfa` accepts.
+    pub fn image<S2>(&self, dfa: &DFA<S2, A>) -> DFA<u32, B>
+    where
+        S2: Eq + Hash + Copy + Debug,
+    {
+        let delta = dfa.delta();
+        let fst = self.transitions.by_a();
+
+        // Run the DFA and the transducer side by side, keeping only the transducer's output.
+        let start = (self.initial_state, dfa.initial_state);
+        let mut seen: HashSet<(S, S2)> = HashSet::default();
+        let mut queue = VecDeque::new();
+        let mut final_states = Vec::new();
+        let mut transitions = Vec::new();
+        seen.insert(start);
+        queue.push_back(start);
+        while let Some((p, q)) = queue.pop_front() {
+            if self.final_states.contains(&p) && dfa.final_states.contains(&q) {
+                final_states.push((p, q));
+            }
+            for &(input, output, p2) in fst.get(&p).into_iter().flatten() {
+                let next = match input {
+                    None => Some((p2, q)),
+                    Some(input) => delta.get(&(q, input)).map(|&q2| (p2, q2)),
+                };
+                if let Some(next) = next {
+                    transitions.push(((p, q), output, next));
+                    if seen.insert(next) {
+                        queue.push_back(next);
+                    }
+                }
+            }
+        }
+        NFA::new(start, final_states, transitions).determinize()
+    }
+
+    // The preimage of `dfa`'s language: every input from which this transducer can produce an
+    // output that `dfa` accepts.
+    pub fn preimage<S2>(&self, dfa: &DFA<S2, B>) -> DFA<u32, A>
+    where
+        S2: Eq + Hash + Copy + Debug,
+    {
+        self.invert().image(dfa)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::FST;
+    use crate::DFA;
+
+    // Optionally rewrites each 'a' to 'b', and copies every other symbol.
+    fn maybe_a_to_b() -> FST<u32, char, char> {
+        FST::new(
+            0,
+            vec![0],
+            vec![
+                (0, Some('a'), Some('a'), 0),
+                (0, Some('a'), Some('b'), 0),
+                (0, Some('b'), Some('b'), 0),
+            ],
+        )
+    }
+
+    #[test]
+    fn image_and_preimage() {
+        let aa: DFA<u32, char> = DFA::from_words(vec!["aa".chars()]);
+        let image: Vec<String> = maybe_a_to_b()
+            .image(&aa)
+            .words(None)
+            .map(|w| w.into_iter().collect())
+            .collect();
+        assert_eq!(image, vec!["aa", "ab", "ba", "bb"]);
+
+        let bb: DFA<u32, char> = DFA::from_words(vec!["bb".chars()]);
+        let preimage: Vec<String> = maybe_a_to_b()
+            .preimage(&bb)
+            .words(None)
+            .map(|w| w.into_iter().collect())
+            .collect();
+        assert_eq!(preimage, vec!["aa", "ab", "ba", "bb"]);
+    }
+
+    #[test]
+    fn compose_with_deletion() {
+        // Deletes every 'b', and copies 'a'.
+        let delete_b: FST<u32, char, char> = FST::new(
+            0,
+            vec![0],
+            vec![(0, Some('a'), Some('a'), 0), (0, Some('b'), None, 0)],
+        );
+        let composed = maybe_a_to_b().compose(&delete_b);
+        let ab: DFA<u32, char> = DFA::from_words(vec!["ab".chars()]);
+        let image: Vec<String> = composed
+            .image(&ab)
+            .words(None)
+            .map(|w| w.into_iter().collect())
+            .collect();
+        assert_eq!(image, vec!["", "a"]);
+
+        // Appends a 'c' at the end, without reading anything.
+        let append_c: FST<u32, char, char> = FST::new(
+            0,
+            vec![1],
+            vec![(0, Some('a'), Some('a'), 0), (0, None, Some('c'), 1)],
+        );
+        let image: Vec<String> = delete_b
+            .compose(&append_c)
+            .image(&ab)
+            .words(None)
+            .map(|w| w.into_iter().collect())
+            .collect();
+        assert_eq!(image, vec!["ac"]);
+    }
+}
@@ -0,0 +1,120 @@
+// The hash maps and sets used throughout the crate. By default they use the standard library's
+// hasher. With the `fxhash` feature they use `FxHasher` instead, which is much faster on the
+// small integer and tuple keys that states and labels usually are, at the cost of no protection
+// against adversarially chosen keys.
+
+use std::hash::{BuildHasherDefault, Hash, Hasher};
+
+#[cfg(not(feature = "fxhash"))]
+pub type BuildHasher = std::collections::hash_map::RandomState;
+#[cfg(feature = "fxhash")]
+pub type BuildHasher = BuildHasherDefault<FxHasher>;
+
+pub type HashMap<K, V> = std::collections::HashMap<K, V, BuildHasher>;
+pub type HashSet<T> = std::collections::HashSet<T, BuildHasher>;
+
+// Public functions return the standard library's maps and sets, so that enabling the feature
+// doesn't change any public type. `into_std` converts at that boundary, and costs nothing without
+// the feature.
+pub(crate) trait IntoStd {
+    type Std;
+    fn into_std(self) -> Self::Std;
+}
+
+impl<K: Eq + Hash, V> IntoStd for HashMap<K, V> {
+    type Std = std::collections::HashMap<K, V>;
+    #[cfg(not(feature = "fxhash"))]
+    fn into_std(self) -> Self::Std {
+        self
+    }
+    #[cfg(feature = "fxhash")]
+    fn into_std(self) -> Self::Std {
+        self.into_iter().collect()
+    }
+}
+
+impl<T: Eq + Hash> IntoStd for HashSet<T> {
+    type Std = std::collections::HashSet<T>;
+    #[cfg(not(feature = "fxhash"))]
+    fn into_std(self) -> Self::Std {
+        self
+    }
+    #[cfg(feature = "fxhash")]
+    fn into_std(self) -> Self::Std {
+        self.into_iter().collect()
+    }
+}
+
+// Builds `FxHasher`s, for maps that use it whether or not the feature is enabled.
+pub type FxBuildHasher = BuildHasherDefault<FxHasher>;
+
+// The hash function from rustc: each word of input is mixed in with a rotate, xor and multiply.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct FxHasher {
+    hash: u64,
+}
+
+const SEED: u64 = 0x51_7c_c1_b7_27_22_0a_95;
+
+impl FxHasher {
+    fn add(&mut self, word: u64) {
+        self.hash = (self.hash.rotate_left(5) ^ word).wrapping_mul(SEED);
+    }
+}
+
+impl Hasher for FxHasher {
+    fn write(&mut self, bytes: &[u8]) {
+        for chunk in bytes.chunks(8) {
+            let mut word = [0; 8];
+            word[..chunk.len()].copy_from_slice(chunk);
+            self.add(u64::from_le_bytes(word));
+        }
+    }
+
+    fn write_u8(&mut self, i: u8) {
+        self.add(u64::from(i));
+    }
+
+    fn write_u16(&mut self, i: u16) {
+        self.add(u64::from(i));
+    }
+
+    fn write_u32(&mut self, i: u32) {
+        self.add(u64::from(i));
+    }
+
+    fn write_u64(&mut self, i: u64) {
+        self.add(i);
+    }
+
+    fn write_usize(&mut self, i: usize) {
+        self.add(i as u64);
+    }
+
+    fn finish(&self) -> u64 {
+        self.hash
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::{FxBuildHasher, HashMap};
+    use std::hash::BuildHasher;
+
+    #[test]
+    fn fx_hashes_tuples() {
+        let build = FxBuildHasher::default();
+        assert_eq!(build.hash_one((1u32, 'a')), build.hash_one((1u32, 'a')));
+        assert_ne!(build.hash_one((1u32, 'a')), build.hash_one((2u32, 'a')));
+        assert_ne!(build.hash_one("ab"), build.hash_one("ba"));
+
+        let mut map: std::collections::HashMap<(u32, u32), u32, FxBuildHasher> = Default::default();
+        let mut crate_map: HashMap<(u32, u32), u32> = HashMap::default();
+        for i in 0..1000 {
+            map.insert((i, i % 7), i);
+            crate_map.insert((i, i % 7), i);
+        }
+        assert_eq!(map.len(), 1000);
+        assert!(map.iter().all(|(k, v)| crate_map[k] == *v));
+    }
+}
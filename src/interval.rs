@@ -0,0 +1,179 @@
+use crate::hash::HashMap;
+use crate::sfa::Predicate;
+
+// A set of `u32`s (typically Unicode codepoints), stored as sorted, disjoint, non-adjacent
+// inclusive intervals. Every constructor and operation keeps the intervals in that normal form,
+// so two sets are equal exactly when their intervals are.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct IntervalSet {
+    intervals: Vec<(u32, u32)>,
+}
+
+impl IntervalSet {
+    // Builds a set from intervals in any order, merging any that overlap or touch. Empty
+    // intervals (`lo > hi`) are ignored.
+    pub fn new(intervals: impl IntoIterator<Item = (u32, u32)>) -> IntervalSet {
+        let mut sorted: Vec<(u32, u32)> =
+            intervals.into_iter().filter(|(lo, hi)| lo <= hi).collect();
+        sorted.sort_unstable();
+        let mut intervals: Vec<(u32, u32)> = Vec::new();
+        for (lo, hi) in sorted {
+            match intervals.last_mut() {
+                Some(last) if lo <= last.1.saturating_add(1) => last.1 = last.1.max(hi),
+                _ => intervals.push((lo, hi)),
+            }
+        }
+        IntervalSet { intervals }
+    }
+
+    pub fn intervals(&self) -> &[(u32, u32)] {
+        &self.intervals
+    }
+
+    // Splits the union of `sets` into the coarsest disjoint pieces such that each piece is either
+    // entirely inside or entirely outside each of the sets. Unlike repeatedly intersecting and
+    // complementing, this is a single sweep over the interval boundaries.
+    pub fn split(sets: &[IntervalSet]) -> Vec<IntervalSet> {
+        // At each boundary, the sets that start or stop containing the value there.
+        let mut events: Vec<(u32, usize)> = Vec::new();
+        let mut ends: Vec<(u32, usize)> = Vec::new();
+        for (i, set) in sets.iter().enumerate() {
+            for &(lo, hi) in &set.intervals {
+                events.push((lo, i));
+                if let Some(next) = hi.checked_add(1) {
+                    ends.push((next, i));
+                }
+            }
+        }
+        let mut boundaries: Vec<u32> = events.iter().chain(&ends).map(|&(x, _)| x).collect();
+        boundaries.sort_unstable();
+        boundaries.dedup();
+        events.sort_unstable();
+        ends.sort_unstable();
+
+        let mut members = vec![false; sets.len()];
+        let mut pieces: HashMap<Vec<bool>, Vec<(u32, u32)>> = HashMap::default();
+        let (mut e, mut f) = (0, 0);
+        for (k, &x) in boundaries.iter().enumerate() {
+            while f < ends.len() && ends[f].0 == x {
+                members[ends[f].1] = false;
+                f += 1;
+            }
+            while e < events.len() && events[e].0 == x {
+                members[events[e].1] = true;
+                e += 1;
+            }
+            if members.iter().any(|&m| m) {
+                let hi = boundaries.get(k + 1).map_or(u32::MAX, |&next| next - 1);
+                pieces.entry(members.clone()).or_default().push((x, hi));
+            }
+        }
+        let mut pieces: Vec<IntervalSet> = pieces.into_values().map(IntervalSet::new).collect();
+        pieces.sort_unstable_by_key(|piece| piece.intervals[0]);
+        pieces
+    }
+}
+
+impl Predicate for IntervalSet {
+    type Symbol = u32;
+
+    fn top() -> Self {
+        IntervalSet {
+            intervals: vec![(0, u32::MAX)],
+        }
+    }
+    fn bottom() -> Self {
+        IntervalSet {
+            intervals: Vec::new(),
+        }
+    }
+    fn and(&self, other: &Self) -> Self {
+        let mut intervals = Vec::new();
+        let (mut i, mut j) = (0, 0);
+        while i < self.intervals.len() && j < other.intervals.len() {
+            let (a_lo, a_hi) = self.intervals[i];
+            let (b_lo, b_hi) = other.intervals[j];
+            let (lo, hi) = (a_lo.max(b_lo), a_hi.min(b_hi));
+            if lo <= hi {
+                intervals.push((lo, hi));
+            }
+            if a_hi < b_hi {
+                i += 1;
+            } else {
+                j += 1;
+            }
+        }
+        IntervalSet { intervals }
+    }
+    fn or(&self, other: &Self) -> Self {
+        IntervalSet::new(self.intervals.iter().chain(&other.intervals).copied())
+    }
+    fn not(&self) -> Self {
+        let mut intervals = Vec::new();
+        let mut next = Some(0);
+        for &(lo, hi) in &self.intervals {
+            if let Some(start) = next.filter(|&start| start < lo) {
+                intervals.push((start, lo - 1));
+            }
+            next = hi.checked_add(1);
+        }
+        if let Some(start) = next {
+            intervals.push((start, u32::MAX));
+        }
+        IntervalSet { intervals }
+    }
+    fn contains(&self, &x: &u32) -> bool {
+        let i = self.intervals.partition_point(|&(_, hi)| hi < x);
+        self.intervals.get(i).is_some_and(|&(lo, _)| lo <= x)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::IntervalSet;
+    use crate::sfa::Predicate;
+
+    #[test]
+    fn normalization() {
+        let set = IntervalSet::new(vec![(10, 20), (0, 3), (4, 5), (15, 25), (9, 8)]);
+        assert_eq!(set.intervals(), &[(0, 5), (10, 25)]);
+        assert_eq!(set.not().intervals(), &[(6, 9), (26, u32::MAX)]);
+        assert_eq!(set.not().not(), set);
+        assert_eq!(set.or(&set.not()), IntervalSet::top());
+        assert_eq!(
+            set.and(&IntervalSet::new(vec![(3, 12)])).intervals(),
+            &[(3, 5), (10, 12)]
+        );
+        assert!(set.contains(&25));
+        assert!(!set.contains(&7));
+    }
+
+    #[test]
+    fn split_into_disjoint_pieces() {
+        let sets = vec![
+            IntervalSet::new(vec![(0, 9)]),
+            IntervalSet::new(vec![(5, 14)]),
+            IntervalSet::new(vec![(0, 2), (12, 14)]),
+        ];
+        let pieces: Vec<Vec<(u32, u32)>> = IntervalSet::split(&sets)
+            .iter()
+            .map(|piece| piece.intervals().to_vec())
+            .collect();
+        assert_eq!(
+            pieces,
+            vec![
+                vec![(0, 2)],
+                vec![(3, 4)],
+                vec![(5, 9)],
+                vec![(10, 11)],
+                vec![(12, 14)]
+            ],
+        );
+        // Values in the same membership pattern share a piece, even if far apart.
+        let sets = vec![
+            IntervalSet::new(vec![(0, 1), (8, 9)]),
+            IntervalSet::new(vec![(4, 5)]),
+        ];
+        assert_eq!(IntervalSet::split(&sets)[0].intervals(), &[(0, 1), (8, 9)]);
+    }
+}
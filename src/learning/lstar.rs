@@ -0,0 +1,201 @@
+use std::fmt::Debug;
+use std::hash::Hash;
+
+use crate::hash::HashMap;
+use crate::DFA;
+
+// Answers questions about an unknown regular language, e.g. by running a black-box parser.
+pub trait Teacher<E> {
+    // Whether `word` is in the language.
+    fn membership(&mut self, word: &[E]) -> bool;
+    // Whether `hypothesis` accepts exactly the language. If not, returns a word on which they
+    // disagree.
+    fn equivalence(&mut self, hypothesis: &DFA<u32, E>) -> Option<Vec<E>>;
+}
+
+// Learns the language known to `teacher`, over `alphabet`, with Angluin's L* algorithm. The
+// result is the minimal DFA for the language, without dead states. Every membership query is
+// asked at most once.
+pub fn lstar<E, T>(alphabet: &[E], teacher: &mut T) -> DFA<u32, E>
+where
+    E: Eq + Hash + Copy + Debug,
+    T: Teacher<E>,
+{
+    let mut table = ObservationTable {
+        prefixes: vec![Vec::new()],
+        suffixes: vec![Vec::new()],
+        answers: HashMap::default(),
+    };
+    loop {
+        table.close(alphabet, teacher);
+        let hypothesis = table.hypothesis(alphabet, teacher);
+        match teacher.equivalence(&hypothesis) {
+            None => {
+                return match hypothesis.prune_unreachable() {
+                    Some(dfa) => dfa,
+                    None => DFA::new(0, None, None),
+                }
+            }
+            // Adding every suffix of the counterexample as an experiment (Maler and Pnueli's
+            // variant) keeps rows of distinct states distinct, so the table stays consistent.
+            Some(counterexample) => {
+                for i in 0..counterexample.len() {
+                    let suffix = counterexample[i..].to_vec();
+                    if !table.suffixes.contains(&suffix) {
+                        table.suffixes.push(suffix);
+                    }
+                }
+            }
+        }
+    }
+}
+
+struct ObservationTable<E> {
+    // Access words for the states found so far; their rows are pairwise distinct.
+    prefixes: Vec<Vec<E>>,
+    // Experiments that tell states apart.
+    suffixes: Vec<Vec<E>>,
+    answers: HashMap<Vec<E>, bool>,
+}
+
+impl<E> ObservationTable<E>
+where
+    E: Eq + Hash + Copy + Debug,
+{
+    fn query(&mut self, word: Vec<E>, teacher: &mut impl Teacher<E>) -> bool {
+        *self
+            .answers
+            .entry(word)
+            .or_insert_with_key(|word| teacher.membership(word))
+    }
+
+    // The answers to every experiment after `prefix`.
+    fn row(&mut self, prefix: &[E], teacher: &mut impl Teacher<E>) -> Vec<bool> {
+        (0..self.suffixes.len())
+            .map(|i| {
+                let word = prefix.iter().chain(&self.suffixes[i]).copied().collect();
+                self.query(word, teacher)
+            })
+            .collect()
+    }
+
+    // Adds prefixes until every one-symbol extension of a prefix has the same row as some
+    // prefix.
+    fn close(&mut self, alphabet: &[E], teacher: &mut impl Teacher<E>) {
+        let mut rows: Vec<Vec<bool>> = Vec::new();
+        let mut unique: Vec<Vec<E>> = Vec::new();
+        for prefix in std::mem::take(&mut self.prefixes) {
+            let row = self.row(&prefix, teacher);
+            if !rows.contains(&row) {
+                rows.push(row);
+                unique.push(prefix);
+            }
+        }
+        let mut i = 0;
+        while i < unique.len() {
+            for &label in alphabet {
+                let mut extension = unique[i].clone();
+                extension.push(label);
+                let row = self.row(&extension, teacher);
+                if !rows.contains(&row) {
+                    rows.push(row);
+                    unique.push(extension);
+                }
+            }
+            i += 1;
+        }
+        self.prefixes = unique;
+    }
+
+    // The DFA whose states are the rows of a closed table.
+    fn hypothesis(&mut self, alphabet: &[E], teacher: &mut impl Teacher<E>) -> DFA<u32, E> {
+        let prefixes = self.prefixes.clone();
+        let rows: Vec<Vec<bool>> = prefixes.iter().map(|p| self.row(p, teacher)).collect();
+        let mut final_states = Vec::new();
+        let mut transitions = Vec::new();
+        for (src, prefix) in prefixes.iter().enumerate() {
+            // The first experiment is the empty word.
+            if rows[src][0] {
+                final_states.push(src as u32);
+            }
+            for &label in alphabet {
+                let mut extension = prefix.clone();
+                extension.push(label);
+                let row = self.row(&extension, teacher);
+                let dst = rows.iter().position(|r| *r == row).unwrap();
+                transitions.push((src as u32, label, dst as u32));
+            }
+        }
+        DFA::new(0, final_states, transitions)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::{lstar, Teacher};
+    use crate::DFA;
+
+    // Answers from a known DFA, checking equivalence on every word up to a fixed length.
+    struct Oracle {
+        target: DFA<u32, u8>,
+        alphabet: Vec<u8>,
+        membership_queries: usize,
+    }
+
+    impl Teacher<u8> for Oracle {
+        fn membership(&mut self, word: &[u8]) -> bool {
+            self.membership_queries += 1;
+            self.target.accepts(word)
+        }
+        fn equivalence(&mut self, hypothesis: &DFA<u32, u8>) -> Option<Vec<u8>> {
+            let mut words = vec![Vec::new()];
+            for _ in 0..8 {
+                for word in &words {
+                    if self.target.accepts(word) != hypothesis.accepts(word) {
+                        return Some(word.clone());
+                    }
+                }
+                words = words
+                    .iter()
+                    .flat_map(|w| {
+                        self.alphabet
+                            .iter()
+                            .map(move |&a| [w.as_slice(), &[a]].concat())
+                    })
+                    .collect();
+            }
+            None
+        }
+    }
+
+    #[test]
+    fn learn_divisible_by_three() {
+        // Binary numbers divisible by 3, read most significant bit first.
+        let transitions =
+            (0..3u32).flat_map(|r| (0..2u8).map(move |b| (r, b, (2 * r + b as u32) % 3)));
+        let mut oracle = Oracle {
+            target: DFA::new(0, vec![0], transitions),
+            alphabet: vec![0, 1],
+            membership_queries: 0,
+        };
+        let learned = lstar(&[0, 1], &mut oracle);
+        assert_eq!(learned.states().len(), 3);
+        assert!(learned.accepts(&[1, 1, 0]));
+        assert!(!learned.accepts(&[1, 1, 1]));
+        assert!(oracle.membership_queries < 100);
+    }
+
+    #[test]
+    fn learn_with_dead_state() {
+        // Exactly "ab", which needs a counterexample to discover.
+        let mut oracle = Oracle {
+            target: DFA::new(0, vec![2], vec![(0, b'a', 1), (1, b'b', 2)]),
+            alphabet: vec![b'a', b'b'],
+            membership_queries: 0,
+        };
+        let learned = lstar(b"ab", &mut oracle);
+        assert_eq!(learned.states().len(), 3);
+        assert_eq!(learned.transitions.len(), 2);
+        assert!(learned.accepts(b"ab"));
+    }
+}
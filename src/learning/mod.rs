@@ -0,0 +1,125 @@
+// Algorithms that infer a DFA from examples of a language, rather than building one directly.
+
+use std::fmt::Debug;
+use std::hash::Hash;
+
+use crate::DFA;
+
+pub mod lstar;
+pub mod rpni;
+
+// A small DFA that accepts every word in `accept` and rejects every word in `reject`, found by
+// merging states of a tree of the samples (see `rpni`) and then minimizing. Words in neither set
+// may go either way. Panics if a word is in both sets.
+pub fn separating_dfa<E>(accept: &[Vec<E>], reject: &[Vec<E>]) -> DFA<u32, E>
+where
+    E: Ord + Hash + Copy + Debug,
+{
+    match rpni::rpni(accept, reject).prune_unreachable() {
+        None => DFA::new(0, None, None),
+        Some(dfa) => dfa.minimize(),
+    }
+}
+
+// A sample word that a DFA classifies wrongly, from `DFA::consistent_with`.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Counterexample<E> {
+    pub word: Vec<E>,
+    // Whether the word should have been accepted.
+    pub positive: bool,
+}
+
+impl<S, E> DFA<S, E>
+where
+    S: Eq + Hash + Copy + Debug,
+    E: Eq + Hash + Copy + Debug,
+{
+    // Checks that every word in `positives` is accepted and every word in `negatives` is
+    // rejected. Otherwise, returns every misclassified word, positives first, in sample order.
+    pub fn consistent_with(
+        &self,
+        positives: &[Vec<E>],
+        negatives: &[Vec<E>],
+    ) -> Result<(), Vec<Counterexample<E>>> {
+        let mut wrong = Vec::new();
+        for (words, positive) in [(positives, true), (negatives, false)] {
+            for word in words {
+                if self.accepts(word) != positive {
+                    wrong.push(Counterexample {
+                        word: word.clone(),
+                        positive,
+                    });
+                }
+            }
+        }
+        if wrong.is_empty() {
+            Ok(())
+        } else {
+            Err(wrong)
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::{separating_dfa, Counterexample};
+    use crate::DFA;
+
+    #[test]
+    fn consistent_with_reports_every_mistake() {
+        // Accepts a*.
+        let dfa: DFA<u32, char> = DFA::new(0, vec![0], vec![(0, 'a', 0)]);
+        assert_eq!(
+            dfa.consistent_with(&[vec![], vec!['a']], &[vec!['b']]),
+            Ok(())
+        );
+        assert_eq!(
+            dfa.consistent_with(
+                &[vec!['b'], vec!['a']],
+                &[vec!['a', 'a'], vec!['a', 'b'], vec![]]
+            ),
+            Err(vec![
+                Counterexample {
+                    word: vec!['b'],
+                    positive: true,
+                },
+                Counterexample {
+                    word: vec!['a', 'a'],
+                    positive: false,
+                },
+                Counterexample {
+                    word: vec![],
+                    positive: false,
+                },
+            ]),
+        );
+    }
+
+    #[test]
+    fn separate_samples() {
+        let accept: Vec<Vec<u8>> = vec![
+            b"aa".to_vec(),
+            b"abab".to_vec(),
+            b"b".to_vec(),
+            b"".to_vec(),
+        ];
+        let reject: Vec<Vec<u8>> = vec![
+            b"a".to_vec(),
+            b"ab".to_vec(),
+            b"aab".to_vec(),
+            b"ba".to_vec(),
+        ];
+        let dfa = separating_dfa(&accept, &reject);
+        assert_eq!(dfa.consistent_with(&accept, &reject), Ok(()));
+        // The tree of accepted words alone has 7 states.
+        assert!(dfa.states().len() < 7);
+
+        let only_empty = separating_dfa(&[vec![]], &[b"x".to_vec()]);
+        assert_eq!(
+            only_empty.consistent_with(&[vec![]], &[b"x".to_vec()]),
+            Ok(())
+        );
+        let nothing = separating_dfa(&[], &[b"x".to_vec()]);
+        assert!(!nothing.accepts(&[]));
+    }
+}
@@ -0,0 +1,207 @@
+use std::collections::{BTreeMap, VecDeque};
+use std::fmt::Debug;
+use std::hash::Hash;
+
+use crate::hash::HashMap;
+use crate::DFA;
+
+// Infers a DFA that accepts every word in `positives` and rejects every word in `negatives`,
+// using RPNI: starting from a tree of the positive words, states are merged in length-lexical
+// order whenever the merge keeps every negative word rejected. With enough samples (a
+// "characteristic" set), this recovers the minimal DFA of the target language.
+//
+// Panics if a word is both positive and negative.
+pub fn rpni<E>(positives: &[Vec<E>], negatives: &[Vec<E>]) -> DFA<u32, E>
+where
+    E: Ord + Hash + Copy + Debug,
+{
+    let mut current = Merger::prefix_tree(positives);
+    assert!(
+        negatives.iter().all(|word| !current.accepts(word)),
+        "a word cannot be both positive and negative",
+    );
+
+    let mut red = vec![0];
+    loop {
+        let mut blue: Vec<usize> = Vec::new();
+        for &r in &red {
+            let r = current.find(r);
+            for &t in current.delta[r].values() {
+                let t = current.find(t);
+                if !red.iter().any(|&r| current.find(r) == t) && !blue.contains(&t) {
+                    blue.push(t);
+                }
+            }
+        }
+        // Tree states are numbered in length-lexical order of the words that reach them, and a
+        // merged class is represented by its smallest member.
+        let q = match blue.into_iter().min() {
+            Some(q) => q,
+            None => break,
+        };
+        let merged = red.iter().find_map(|&r| {
+            let mut candidate = current.clone();
+            candidate.merge(r, q);
+            if negatives.iter().any(|word| candidate.accepts(word)) {
+                None
+            } else {
+                Some(candidate)
+            }
+        });
+        match merged {
+            Some(candidate) => current = candidate,
+            None => red.push(q),
+        }
+    }
+    current.to_dfa()
+}
+
+// A prefix tree whose states are being merged, as a union-find. Transitions and acceptance are
+// kept up to date on the representative of each class.
+#[derive(Clone)]
+struct Merger<E> {
+    parent: Vec<usize>,
+    delta: Vec<BTreeMap<E, usize>>,
+    accepting: Vec<bool>,
+}
+
+impl<E> Merger<E>
+where
+    E: Ord + Hash + Copy + Debug,
+{
+    // The tree of all prefixes of `words`, numbered in length-lexical order.
+    fn prefix_tree(words: &[Vec<E>]) -> Merger<E> {
+        let mut prefixes: Vec<&[E]> = words
+            .iter()
+            .flat_map(|word| (0..=word.len()).map(move |i| &word[..i]))
+            .collect();
+        // The root is there even when there are no words.
+        prefixes.push(&[]);
+        prefixes.sort_unstable_by(|a, b| a.len().cmp(&b.len()).then(a.cmp(b)));
+        prefixes.dedup();
+        let ids: HashMap<&[E], usize> = prefixes.iter().enumerate().map(|(i, &p)| (p, i)).collect();
+
+        let mut delta = vec![BTreeMap::new(); prefixes.len()];
+        for (i, prefix) in prefixes.iter().enumerate().skip(1) {
+            let (last, parent) = prefix.split_last().unwrap();
+            delta[ids[parent]].insert(*last, i);
+        }
+        let mut accepting = vec![false; prefixes.len()];
+        for word in words {
+            accepting[ids[word.as_slice()]] = true;
+        }
+        Merger {
+            parent: (0..prefixes.len()).collect(),
+            delta,
+            accepting,
+        }
+    }
+
+    fn find(&self, mut q: usize) -> usize {
+        while self.parent[q] != q {
+            q = self.parent[q];
+        }
+        q
+    }
+
+    // Merges the classes of `a` and `b`, and then any classes that must also merge to keep the
+    // automaton deterministic.
+    fn merge(&mut self, a: usize, b: usize) {
+        let (a, b) = (self.find(a), self.find(b));
+        if a == b {
+            return;
+        }
+        let (root, child) = (a.min(b), a.max(b));
+        self.parent[child] = root;
+        self.accepting[root] |= self.accepting[child];
+        for (label, t) in std::mem::take(&mut self.delta[child]) {
+            let root = self.find(root);
+            match self.delta[root].get(&label) {
+                Some(&u) => self.merge(u, t),
+                None => {
+                    self.delta[root].insert(label, t);
+                }
+            }
+        }
+    }
+
+    fn accepts(&self, word: &[E]) -> bool {
+        let mut q = self.find(0);
+        for label in word {
+            q = match self.delta[q].get(label) {
+                Some(&t) => self.find(t),
+                None => return false,
+            };
+        }
+        self.accepting[q]
+    }
+
+    fn to_dfa(&self) -> DFA<u32, E> {
+        let start = self.find(0);
+        let mut ids: HashMap<usize, u32> = HashMap::default();
+        let mut queue = VecDeque::new();
+        let mut final_states = Vec::new();
+        let mut transitions = Vec::new();
+        ids.insert(start, 0);
+        queue.push_back(start);
+        while let Some(q) = queue.pop_front() {
+            let src = ids[&q];
+            if self.accepting[q] {
+                final_states.push(src);
+            }
+            for (&label, &t) in &self.delta[q] {
+                let t = self.find(t);
+                let n = ids.len() as u32;
+                let dst = *ids.entry(t).or_insert_with(|| {
+                    queue.push_back(t);
+                    n
+                });
+                transitions.push((src, label, dst));
+            }
+        }
+        DFA::new(0, final_states, transitions)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::rpni;
+
+    fn words(ws: &[&str]) -> Vec<Vec<u8>> {
+        ws.iter().map(|w| w.as_bytes().to_vec()).collect()
+    }
+
+    #[test]
+    fn learn_repeated_ab() {
+        let positives = words(&["", "ab", "abab"]);
+        let negatives = words(&["a", "b", "aa", "ba", "bb", "aba", "abb"]);
+        let dfa = rpni(&positives, &negatives);
+        assert_eq!(dfa.states().len(), 2);
+        assert!(dfa.accepts(b"ababab"));
+        assert!(!dfa.accepts(b"abba"));
+    }
+
+    #[test]
+    fn learn_even_number_of_as() {
+        let positives = words(&["", "b", "aa", "bb", "aab", "aba", "baa", "abab"]);
+        let negatives = words(&["a", "ab", "ba", "aaa", "abb", "bab"]);
+        let dfa = rpni(&positives, &negatives);
+        for word in positives.iter() {
+            assert!(dfa.accepts(word));
+        }
+        for word in negatives.iter() {
+            assert!(!dfa.accepts(word));
+        }
+        assert!(dfa.accepts(b"abbbbba"));
+        assert!(!dfa.accepts(b"abbbbbaa"));
+    }
+
+    #[test]
+    fn no_positives() {
+        // The prefix tree is just the root, which accepts nothing.
+        let dfa = rpni(&[], &words(&["a", "b"]));
+        assert_eq!(dfa.states().len(), 1);
+        assert!(!dfa.accepts(b""));
+        assert!(!dfa.accepts(b"a"));
+    }
+}
@@ -1,31 +1,231 @@
-use std::collections::{HashSet, VecDeque};
+use std::collections::VecDeque;
 use std::fmt::Debug;
 use std::hash::Hash;
+use std::ops::Range;
 
-use partition::Partition;
-use table::Table;
+pub use aho_corasick::aho_corasick_dfa;
+pub use analysis::{Lasso, Pumping, Stats};
+pub use annotated::AnnotatedDFA;
+pub use approximate::Approximation;
+#[cfg(feature = "bdd")]
+pub use bdd::Bdd;
+pub use default::DefaultDFA;
+pub use density::{DensityComparison, Growth};
+pub use diff::DiffTag;
+pub use distance::{hamming_dfa, levenshtein_dfa};
+pub use embedded::StaticDFA;
+pub use equivalence::{DistinguishingLength, VerificationError};
+pub use format::{Format, NamedDFA, ParseError};
+pub use fst::FST;
+use hash::{HashMap, HashSet, IntoStd};
+pub use interval::IntervalSet;
+pub use learning::{separating_dfa, Counterexample};
+pub use lockstep::{Divergence, PairSimulator};
+pub use ltlf::Ltl;
+pub use lts::LTS;
+pub use matcher::{Anchoring, Matcher};
+pub use mealy::MealyMachine;
+pub use monitor::{Monitor, Verdict};
+pub use moore::MooreMachine;
+pub use nerode::Residual;
+pub use nfa::NFA;
+pub use partial::Incomplete;
+use partition::{DensePartition, Partition};
+pub use payload::{PayloadConflict, PayloadDFA};
+pub use probabilistic::ProbabilisticAutomaton;
+pub use quotient::QuotientError;
+pub use range::{CharClass, RangeDFA};
+pub use regex::Regex;
+pub use scc::{Condensation, Scc};
+pub use search::Searcher;
+pub use sfa::{ByteSet, Predicate, SFA};
+pub use spill::{Record, SpilledTable, Spiller};
+pub use suffix::SuffixAutomaton;
+use table::{IndexedTable, Table};
+pub use weighted::{DivisibleSemiring, Semiring, Tropical, WeightedDFA};
+pub use words::Words;
 
-mod partition;
+mod aho_corasick;
+mod analysis;
+mod annotated;
+mod approximate;
+#[cfg(feature = "bdd")]
+mod bdd;
+mod canonical;
+#[cfg(feature = "capi")]
+mod capi;
+mod codegen;
+mod conformance;
+mod dawg;
+mod default;
+mod density;
+mod diff;
+mod distance;
+mod edit;
+mod embedded;
+mod equivalence;
+mod format;
+mod fst;
+pub mod hash;
+mod interval;
+pub mod learning;
+mod lockstep;
+mod ltlf;
+mod lts;
+mod matcher;
+mod matrix;
+mod mealy;
+mod monitor;
+mod moore;
+mod nerode;
+mod nfa;
+mod partial;
+pub mod partition;
+mod payload;
+mod probabilistic;
+mod product;
+mod quotient;
+mod range;
+mod regex;
+mod scc;
+mod search;
+mod sfa;
+mod simulation;
+mod spill;
+mod suffix;
+mod sync;
 mod table;
+pub mod testing;
+mod trie;
+#[cfg(feature = "wasm")]
+mod wasm;
+mod weighted;
+mod words;
 
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub struct DFA<S, E> {
     initial_state: S,
+    // Every state, including the initial and accepting states, the endpoints of every transition,
+    // and any states with no transitions at all.
+    states: HashSet<S>,
     final_states: HashSet<S>,
     transitions: Table<S, E, S>,
 }
 
+// Two DFAs are equal when they have the same states, initial state, accepting states and
+// transitions, with states compared by name. DFAs that accept the same words but name or arrange
+// their states differently are not equal; compare those with `language_eq`.
+impl<S, E> PartialEq for DFA<S, E>
+where
+    S: Eq + Hash + Copy + Debug,
+    E: Eq + Hash + Copy + Debug,
+{
+    fn eq(&self, other: &Self) -> bool {
+        let transitions = |dfa: &Self| dfa.transitions().collect::<HashSet<_>>();
+        self.initial_state == other.initial_state
+            && self.states == other.states
+            && self.final_states == other.final_states
+            && transitions(self) == transitions(other)
+    }
+}
+
+impl<S, E> Eq for DFA<S, E>
+where
+    S: Eq + Hash + Copy + Debug,
+    E: Eq + Hash + Copy + Debug,
+{
+}
+
 impl<S, E> DFA<S, E>
 where
     S: Eq + Hash + Copy + Debug,
     E: Eq + Hash + Copy + Debug,
 {
-    // If the DFA represents the empty language, this will return `None`.
-    pub fn prune_unreachable(self) -> Option<DFA<S, E>> {
+    pub fn new(
+        initial_state: S,
+        final_states: impl IntoIterator<Item = S>,
+        transitions: impl IntoIterator<Item = (S, E, S)>,
+    ) -> DFA<S, E> {
+        DFA::with_states(initial_state, None, final_states, transitions)
+    }
+
+    // Like `new`, but also keeps every state in `states`, even one that no transition mentions.
+    pub fn with_states(
+        initial_state: S,
+        states: impl IntoIterator<Item = S>,
+        final_states: impl IntoIterator<Item = S>,
+        transitions: impl IntoIterator<Item = (S, E, S)>,
+    ) -> DFA<S, E> {
+        let mut transitions: Table<S, E, S> = transitions.into_iter().collect();
+        // Repeated transitions would otherwise be counted twice when minimizing.
+        transitions.dedup();
+        let final_states: HashSet<S> = final_states.into_iter().collect();
+        let mut states: HashSet<S> = states.into_iter().collect();
+        states.insert(initial_state);
+        states.extend(final_states.iter().copied());
+        for &(src, _, dst) in &transitions {
+            states.insert(src);
+            states.insert(dst);
+        }
+        DFA {
+            initial_state,
+            states,
+            final_states,
+            transitions,
+        }
+    }
+
+    pub fn initial_state(&self) -> S {
+        self.initial_state
+    }
+
+    pub fn is_accepting(&self, state: S) -> bool {
+        self.final_states.contains(&state)
+    }
+
+    pub fn transitions(&self) -> impl Iterator<Item = (S, E, S)> + '_ {
+        (&self.transitions).into_iter().copied()
+    }
+
+    // Renames every transition label with `f`, which must not map two labels leaving the same
+    // state to the same new label.
+    pub fn map_labels<E2>(&self, f: impl Fn(E) -> E2) -> DFA<S, E2>
+    where
+        E2: Eq + Hash + Copy + Debug,
+    {
+        DFA {
+            initial_state: self.initial_state,
+            states: self.states.clone(),
+            final_states: self.final_states.clone(),
+            transitions: self
+                .transitions()
+                .map(|(src, label, dst)| (src, f(label), dst))
+                .collect(),
+        }
+    }
+
+    // Every state of this DFA, including those without any transitions.
+    fn states(&self) -> HashSet<S> {
+        self.states.clone()
+    }
+
+    pub fn state_count(&self) -> usize {
+        self.states.len()
+    }
+
+    // The transition function, as a lookup table.
+    fn delta(&self) -> HashMap<(S, E), S> {
+        (&self.transitions)
+            .into_iter()
+            .map(|&(src, label, dst)| ((src, label), dst))
+            .collect()
+    }
+
+    // The states that can be reached from the initial state.
+    fn reachable(&self) -> HashSet<S> {
         let outflows = self.transitions.by_a();
-        let inflows = self.transitions.by_c();
 
-        let mut reachable = HashSet::new();
+        let mut reachable = HashSet::default();
         let mut queue = VecDeque::new();
         queue.push_back(self.initial_state);
         while let Some(src) = queue.pop_front() {
@@ -38,11 +238,15 @@ where
                 }
             }
         }
+        reachable
+    }
 
-        let mut relevant = HashSet::new();
-        for &q in &self.final_states {
-            queue.push_back(q);
-        }
+    // The states from which some accepting state can be reached.
+    fn coaccessible(&self) -> HashSet<S> {
+        let inflows = self.transitions.by_c();
+
+        let mut relevant = HashSet::default();
+        let mut queue: VecDeque<S> = self.final_states.iter().copied().collect();
         while let Some(dst) = queue.pop_front() {
             if !relevant.insert(dst) {
                 continue;
@@ -53,6 +257,25 @@ where
                 }
             }
         }
+        relevant
+    }
+
+    pub fn accepts(&self, word: &[E]) -> bool {
+        let delta = self.delta();
+        let mut q = self.initial_state;
+        for &label in word {
+            q = match delta.get(&(q, label)) {
+                Some(&dst) => dst,
+                None => return false,
+            };
+        }
+        self.final_states.contains(&q)
+    }
+
+    // If the DFA represents the empty language, this will return `None`.
+    pub fn prune_unreachable(self) -> Option<DFA<S, E>> {
+        let reachable = self.reachable();
+        let relevant = self.coaccessible();
 
         let allowed: HashSet<S> = reachable.intersection(&relevant).copied().collect();
         if !allowed.contains(&self.initial_state) {
@@ -62,6 +285,7 @@ where
         Some(DFA {
             initial_state: self.initial_state,
             final_states: self.final_states.intersection(&allowed).copied().collect(),
+            states: allowed.clone(),
             transitions: self
                 .transitions
                 .into_iter()
@@ -70,78 +294,278 @@ where
         })
     }
 
-    pub fn minimize(&self) -> DFA<S, E> {
-        let by_src = self.transitions.by_a();
-        let by_dst = self.transitions.by_c();
-        let by_label = self.transitions.by_b();
+    // Removes the transitions out of dead states, from which nothing is accepted, but keeps the
+    // states themselves and the transitions that enter them from live states. The language is the
+    // same, and every dead state becomes one of the `trap_states`, so that a run which can no
+    // longer be accepted still ends up in a state that says so, rather than falling off a missing
+    // transition.
+    pub fn prune_dead_transitions(&self) -> DFA<S, E> {
+        let live = self.coaccessible();
+        DFA {
+            initial_state: self.initial_state,
+            states: self.states.clone(),
+            final_states: self.final_states.clone(),
+            transitions: self
+                .transitions()
+                .filter(|(src, _, _)| live.contains(src))
+                .collect(),
+        }
+    }
 
-        let mut blocks = {
-            let states: HashSet<S> = by_src.keys().chain(by_dst.keys()).copied().collect();
-            Partition::new(states.into_iter().collect())
-        };
+    pub fn minimize(&self) -> DFA<S, E> {
+        self.minimize_by(|_| ())
+    }
 
-        // Start an initial partition by separating out the accepting states.
-        for &q in &self.final_states {
-            blocks.mark(q);
+    // Minimizes as `minimize` does, but represents each set of merged states by its smallest
+    // state and sorts the transitions. `minimize` represents each set by whichever state the
+    // hash maps put first, which can change from run to run; this gives the same result on every
+    // run and platform, e.g. for snapshot tests. The transitions are grouped and looked up in
+    // sorted copies of the table rather than in hash maps.
+    pub fn minimize_deterministic(&self) -> DFA<S, E>
+    where
+        S: Ord,
+        E: Ord,
+    {
+        let (live, refined) = self.refinement(&|_| ());
+        // Sorting by destination groups the transitions for `refine` without building a map.
+        let into = refined.sorted_by_c();
+        let blocks = refine_grouped(
+            self.states.iter().copied().collect(),
+            into.groups()
+                .map(|(dst, run)| (dst, run.iter().map(|&(_, src, label)| (src, label)))),
+            |q| (self.final_states.contains(q), live.contains(q)),
+        );
+        let sorted = self.transitions.clone().sorted();
+        let (minimal, _) = self.merge_blocks(
+            &blocks,
+            |set| *set.iter().min().unwrap(),
+            |q| {
+                sorted
+                    .successors(q)
+                    .iter()
+                    .map(|&(_, label, dst)| (label, dst))
+            },
+        );
+        let mut transitions: Vec<(S, E, S)> = minimal.transitions().collect();
+        transitions.sort_unstable();
+        DFA {
+            transitions: Table::from(transitions),
+            ..minimal
         }
-        blocks.split();
+    }
+
+    // Minimizes as `minimize` does, representing each set of merged states by the state that
+    // `choose` picks out of it. For example, `reach_order` can be used to pick the state that is
+    // closest to the initial state.
+    pub fn minimize_choosing(&self, choose: impl Fn(&[S]) -> S) -> DFA<S, E> {
+        self.minimize_with_classes(|_| (), choose).0
+    }
 
-        let mut cords = Partition::new(self.transitions.clone().into_iter().collect());
-        // Start the initial partition by separating out every edge label.
-        for (label, es) in by_label {
-            for (src, dst) in es {
-                cords.mark((src, label, dst));
+    // Numbers the states in the order a breadth-first search from the initial state reaches them.
+    pub fn reach_order(&self) -> std::collections::HashMap<S, usize> {
+        let outflows = self.transitions.by_a();
+        let mut order: HashMap<S, usize> = HashMap::default();
+        let mut queue = VecDeque::new();
+        order.insert(self.initial_state, 0);
+        queue.push_back(self.initial_state);
+        while let Some(src) = queue.pop_front() {
+            for &(_, dst) in outflows.get(&src).into_iter().flatten() {
+                let n = order.len();
+                order.entry(dst).or_insert_with(|| {
+                    queue.push_back(dst);
+                    n
+                });
             }
-            cords.split();
         }
-        println!("initial cord partition has {} sets", cords.len());
-
-        // Repeatedly refine partitions.
-        let mut b = 1;
-        let mut c = 0;
-        while c < cords.len() {
-            for &(src, _, _) in cords.owned(c) {
-                blocks.mark(src);
-            }
-            blocks.split();
-            c += 1;
-            while b < blocks.len() {
-                for &dst in blocks.owned(b) {
-                    if let Some(edges) = by_dst.get(&dst) {
-                        for &(src, label) in edges {
-                            cords.mark((src, label, dst));
-                        }
-                    }
-                }
-                cords.split();
-                b += 1;
-            }
+        order.into_std()
+    }
+
+    // Prunes and then minimizes deterministically. A DFA for the empty language becomes its
+    // initial state alone.
+    pub(crate) fn prune_and_minimize(&self) -> DFA<S, E>
+    where
+        S: Ord,
+        E: Ord,
+    {
+        match self.clone().prune_unreachable() {
+            None => DFA::new(self.initial_state, None, None),
+            Some(pruned) => pruned.minimize_deterministic(),
         }
+    }
+
+    // Like `minimize`, but never merges states with different `tag`s, e.g. states that trigger
+    // different actions.
+    pub fn minimize_by<K>(&self, tag: impl Fn(&S) -> K) -> DFA<S, E>
+    where
+        K: Eq + Hash,
+    {
+        self.minimize_with_classes(tag, |set| set[0]).0
+    }
+
+    // Minimizes as `minimize_by` does, with each set of merged states represented by the state
+    // `choose` picks, and also maps every state to the state that represents it in the result.
+    fn minimize_with_classes<K>(
+        &self,
+        tag: impl Fn(&S) -> K,
+        choose: impl Fn(&[S]) -> S,
+    ) -> (DFA<S, E>, HashMap<S, S>)
+    where
+        K: Eq + Hash,
+    {
+        let (live, refined) = self.refinement(&tag);
+        // Start an initial partition by separating out the accepting states, and then by tag.
+        let blocks = refine(
+            self.states.iter().copied().collect(),
+            &refined.indexed(),
+            |q| (self.final_states.contains(q), live.contains(q), tag(q)),
+        );
+        let index = self.transitions.indexed();
+        self.merge_blocks(&blocks, choose, |q| index.successors(q).iter().copied())
+    }
+
+    // The live states, and the transitions that minimizing by `tag` refines by. A transition into
+    // a dead state, from which nothing is accepted, is no different from a missing transition, so
+    // those are left out of the refinement. Dead states then stay together, apart from the rest.
+    // That only holds while the dead states all have the same tag, though: otherwise which of
+    // them a word ends in matters, so every transition is kept.
+    fn refinement<K>(&self, tag: &impl Fn(&S) -> K) -> (HashSet<S>, Table<S, E, S>)
+    where
+        K: Eq + Hash,
+    {
+        let live = self.coaccessible();
+        let dead_tags: HashSet<K> = self
+            .states
+            .iter()
+            .filter(|q| !live.contains(q))
+            .map(tag)
+            .collect();
+        let refined = self
+            .transitions()
+            .filter(|(_, _, dst)| dead_tags.len() > 1 || live.contains(dst))
+            .collect();
+        (live, refined)
+    }
+
+    // Merges each set of `blocks` into the state `choose` picks out of it, with the transitions
+    // `successors` gives for one of its members, and maps every state to its representative.
+    fn merge_blocks<I>(
+        &self,
+        blocks: &Partition<S>,
+        choose: impl Fn(&[S]) -> S,
+        successors: impl Fn(&S) -> I,
+    ) -> (DFA<S, E>, HashMap<S, S>)
+    where
+        I: IntoIterator<Item = (E, S)>,
+    {
+        let reps: Vec<S> = blocks.sets().map(&choose).collect();
+        let rep = |q: &S| reps[blocks.owner(*q)];
 
         let mut canonical_tuples = Vec::new();
-        for i in 0..blocks.len() {
-            let src = blocks.canonical(i);
-            println!(
-                "looking for canonical state for block {}: {:?}",
-                i,
-                blocks.owned(i)
-            );
-            if let Some(outgoing) = by_src.get(&src) {
-                for &(label, dst) in outgoing {
-                    canonical_tuples.push((src, label, blocks.canonical(blocks.owner(dst))));
-                }
+        for &src in &reps {
+            // The members of the set have transitions with the same labels into the same sets,
+            // except that some may have a transition into a dead state where others have none.
+            // Either way the language is the same, but taking the representative's own
+            // transitions keeps the result from depending on how the set is ordered.
+            for (label, dst) in successors(&src) {
+                canonical_tuples.push((src, label, rep(&dst)));
             }
         }
-        DFA {
-            initial_state: blocks.canonical(blocks.owner(self.initial_state)),
-            final_states: self
-                .final_states
-                .iter()
-                .map(|&q| blocks.canonical(blocks.owner(q)))
-                .collect(),
+        let mut classes = HashMap::default();
+        for (i, set) in blocks.sets().enumerate() {
+            for &q in set {
+                classes.insert(q, reps[i]);
+            }
+        }
+        let minimal = DFA {
+            initial_state: rep(&self.initial_state),
+            states: reps.iter().copied().collect(),
+            final_states: self.final_states.iter().map(rep).collect(),
             transitions: Table::from(canonical_tuples),
+        };
+        (minimal, classes)
+    }
+}
+
+// Computes the coarsest partition of `states` in which states with different `key`s are never
+// in the same set, and any two states in the same set have transitions with the same labels
+// into the same sets.
+fn refine<S, E, K>(
+    states: Vec<S>,
+    transitions: &IndexedTable<'_, S, E, S>,
+    key: impl Fn(&S) -> K,
+) -> Partition<S>
+where
+    S: Eq + Hash + Copy + Debug,
+    E: Eq + Hash + Copy + Debug,
+    K: Eq + Hash,
+{
+    let into = transitions
+        .by_c()
+        .iter()
+        .map(|(&dst, edges)| (dst, edges.iter().copied()));
+    refine_grouped(states, into, key)
+}
+
+// Like `refine`, with the transitions given as the `(src, label)`s into each state, one
+// destination at a time.
+fn refine_grouped<S, E, K, I>(
+    states: Vec<S>,
+    into: impl IntoIterator<Item = (S, I)>,
+    key: impl Fn(&S) -> K,
+) -> Partition<S>
+where
+    S: Eq + Hash + Copy + Debug,
+    E: Eq + Hash + Copy + Debug,
+    K: Eq + Hash,
+    I: IntoIterator<Item = (S, E)>,
+{
+    let mut blocks = split_by_key(states, key);
+
+    // Transitions are refined by their position, so that they are never hashed. The ones into
+    // each state are numbered consecutively, so each state only needs the range of its own.
+    let mut edges: Vec<(S, E)> = Vec::new();
+    let mut runs: HashMap<S, Range<usize>> = HashMap::default();
+    for (dst, group) in into {
+        let start = edges.len();
+        edges.extend(group);
+        runs.insert(dst, start..edges.len());
+    }
+
+    let mut cords = DensePartition::dense((0..edges.len()).collect());
+    // Start the initial partition by separating out every edge label.
+    cords.refine_by(|&e| edges[e].1);
+
+    // Repeatedly refine partitions. Every initial block but one must be used as a splitter.
+    let mut b = 1;
+    let mut c = 0;
+    while c < cords.len() {
+        for &e in cords.owned(c) {
+            blocks.mark(edges[e].0);
+        }
+        blocks.split();
+        c += 1;
+        while b < blocks.len() {
+            for &dst in blocks.owned(b) {
+                for e in runs.get(&dst).cloned().into_iter().flatten() {
+                    cords.mark(e);
+                }
+            }
+            cords.split();
+            b += 1;
         }
     }
+    blocks
+}
+
+// Partitions `states` into sets of states with the same `key`.
+fn split_by_key<S, K>(states: Vec<S>, key: impl Fn(&S) -> K) -> Partition<S>
+where
+    S: Eq + Hash + Copy + Debug,
+    K: Eq + Hash,
+{
+    let mut blocks = Partition::new(states);
+    blocks.refine_by(key);
+    blocks
 }
 
 #[cfg(test)]
@@ -164,11 +588,7 @@ mod test {
             (5, 0, 5),
             (5, 1, 5),
         ];
-        let input: DFA<u32, u8> = DFA {
-            initial_state: 0,
-            final_states: vec![2, 3, 4].into_iter().collect(),
-            transitions: Table::from(transitions),
-        };
+        let input: DFA<u32, u8> = DFA::new(0, vec![2, 3, 4], transitions);
         assert_eq!(input.transitions.len(), 12);
         assert_eq!(input.transitions.by_a().len(), 6);
         let pruned = input.prune_unreachable().unwrap();
@@ -193,11 +613,7 @@ mod test {
             (5, 0, 5),
             (5, 1, 5),
         ];
-        let input: DFA<u32, u8> = DFA {
-            initial_state: 0,
-            final_states: vec![2, 3, 4].into_iter().collect(),
-            transitions: Table::from(transitions),
-        };
+        let input: DFA<u32, u8> = DFA::new(0, vec![2, 3, 4], transitions);
         assert_eq!(input.transitions.len(), 12);
         assert_eq!(input.transitions.by_a().len(), 6);
         let pruned = input.prune_unreachable().unwrap();
@@ -220,11 +636,7 @@ mod test {
             (4, 0, 4),
             (4, 1, 4),
         ];
-        let input: DFA<u32, u8> = DFA {
-            initial_state: 0,
-            final_states: vec![2, 4].into_iter().collect(),
-            transitions: Table::from(transitions),
-        };
+        let input: DFA<u32, u8> = DFA::new(0, vec![2, 4], transitions);
         assert_eq!(input.transitions.len(), 10);
         assert_eq!(input.transitions.by_a().len(), 5);
         let pruned = input.prune_unreachable().unwrap();
@@ -236,11 +648,7 @@ mod test {
 
     #[test]
     fn prune_empty_language() {
-        let input: DFA<u32, u8> = DFA {
-            initial_state: 0,
-            final_states: HashSet::new(),
-            transitions: Table::from(vec![]),
-        };
+        let input: DFA<u32, u8> = DFA::new(0, None, None);
         let pruned = input.prune_unreachable();
         assert!(pruned.is_none());
     }
@@ -250,11 +658,7 @@ mod test {
         // This is an already-minimal DFA that accepts 0*10*
         // Every state is an accepting state.
         let transitions: Vec<(u32, u8, u32)> = vec![(0, 0, 0), (0, 1, 1), (1, 0, 1)];
-        let input: DFA<u32, u8> = DFA {
-            initial_state: 0,
-            final_states: vec![0, 1].into_iter().collect(),
-            transitions: Table::from(transitions),
-        };
+        let input: DFA<u32, u8> = DFA::new(0, vec![0, 1], transitions);
         assert_eq!(input.transitions.len(), 3);
         assert_eq!(input.transitions.by_a().len(), 2);
         let pruned = input.prune_unreachable().unwrap();
@@ -263,4 +667,132 @@ mod test {
         assert_eq!(minified.transitions.by_a().len(), 2);
         assert_eq!(minified.final_states.len(), 2);
     }
+
+    #[test]
+    fn map_labels() {
+        let input: DFA<u32, char> = DFA::new(0, vec![1], vec![(0, 'a', 1), (1, 'b', 0)]);
+        let upper = input.map_labels(|c| c.to_ascii_uppercase());
+        assert!(upper.accepts(&['A', 'B', 'A']));
+        assert!(!upper.accepts(&['a']));
+        assert_eq!(upper.map_labels(|c| c.to_ascii_lowercase()), input);
+        let reordered: DFA<u32, char> = DFA::new(0, vec![1], vec![(1, 'b', 0), (0, 'a', 1)]);
+        assert_eq!(input, reordered);
+        assert_ne!(input, DFA::new(1, vec![1], vec![(0, 'a', 1), (1, 'b', 0)]));
+    }
+
+    #[test]
+    fn minimize_deterministic() {
+        let input: DFA<u32, char> = DFA::new(
+            0,
+            vec![5, 6],
+            vec![
+                (0, 'a', 6),
+                (0, 'b', 4),
+                (4, 'a', 5),
+                (5, 'a', 5),
+                (6, 'a', 6),
+            ],
+        );
+        let minimal = input.minimize_deterministic();
+        assert_eq!(
+            minimal.transitions().collect::<Vec<_>>(),
+            vec![(0, 'a', 5), (0, 'b', 4), (4, 'a', 5), (5, 'a', 5)]
+        );
+        // Hash maps are seeded differently every time, but the result never changes.
+        for _ in 0..10 {
+            assert_eq!(input.clone().minimize_deterministic(), minimal);
+        }
+    }
+
+    #[test]
+    fn minimize_deterministic_with_dead_transitions() {
+        // 3 and 5 are equivalent, but only 3 has a transition, into the dead state 4.
+        let input: DFA<u32, char> =
+            DFA::new(0, vec![3, 5], vec![(0, 'a', 3), (0, 'b', 5), (3, 'y', 4)]);
+        let minimal = input.minimize_deterministic();
+        assert_eq!(
+            minimal.transitions().collect::<Vec<_>>(),
+            vec![(0, 'a', 3), (0, 'b', 3), (3, 'y', 4)]
+        );
+        for _ in 0..20 {
+            assert_eq!(input.clone().minimize_deterministic(), minimal);
+        }
+    }
+
+    #[test]
+    fn minimize_choosing() {
+        // 1 and 2 are equivalent, as are 3 and 4.
+        let input: DFA<u32, char> = DFA::new(
+            0,
+            vec![3, 4],
+            vec![(0, 'a', 2), (0, 'b', 1), (1, 'x', 4), (2, 'x', 3)],
+        );
+        let largest = input.minimize_choosing(|set| *set.iter().max().unwrap());
+        assert_eq!(largest.initial_state, 0);
+        assert!(largest.is_accepting(4));
+        assert_eq!(largest.transitions.len(), 3);
+
+        let order = input.reach_order();
+        assert_eq!(order[&0], 0);
+        let first = input.minimize_choosing(|set| *set.iter().min_by_key(|q| order[q]).unwrap());
+        let reached = input.delta()[&(0, 'a')];
+        assert!(first.transitions().any(|(_, _, dst)| dst == reached));
+    }
+
+    #[test]
+    fn prune_dead_transitions() {
+        // 2 and 3 are dead, and lead into each other.
+        let input: DFA<u32, char> = DFA::new(
+            0,
+            vec![1],
+            vec![
+                (0, 'a', 1),
+                (0, 'b', 2),
+                (2, 'a', 3),
+                (3, 'a', 2),
+                (3, 'b', 3),
+            ],
+        );
+        let pruned = input.prune_dead_transitions();
+        assert_eq!(pruned.state_count(), 4);
+        assert_eq!(pruned.transitions.len(), 2);
+        assert!(pruned.language_eq(&input));
+        let traps = pruned.trap_states();
+        assert!(traps.contains(&2) && traps.contains(&3));
+        assert!(!traps.contains(&0));
+    }
+
+    #[test]
+    fn states_without_transitions() {
+        // Accepts only the empty word.
+        let epsilon: DFA<u32, char> = DFA::new(0, vec![0], None);
+        assert_eq!(epsilon.state_count(), 1);
+        let minimal = epsilon.minimize();
+        assert_eq!(minimal, epsilon);
+        assert!(minimal.accepts(&[]));
+        assert_eq!(epsilon.prune_and_minimize(), epsilon);
+
+        // 2 is isolated and 3 is a sink; both are kept until pruned, and then merged away.
+        let input: DFA<u32, char> =
+            DFA::with_states(0, vec![2], vec![1], vec![(0, 'a', 1), (1, 'a', 3)]);
+        assert_eq!(input.state_count(), 4);
+        assert_ne!(input, DFA::new(0, vec![1], vec![(0, 'a', 1), (1, 'a', 3)]));
+        assert_eq!(input.minimize().state_count(), 3);
+        let pruned = input.prune_unreachable().unwrap();
+        assert_eq!(pruned.states(), vec![0, 1].into_iter().collect());
+    }
+
+    #[test]
+    fn minimize_by_tag() {
+        // Both branches accept "x", but the tags keep them apart.
+        let input: DFA<u32, char> = DFA::new(
+            0,
+            vec![3, 4],
+            vec![(0, 'a', 1), (0, 'b', 2), (1, 'x', 3), (2, 'x', 4)],
+        );
+        assert_eq!(input.minimize().transitions.len(), 3);
+        let tagged = input.minimize_by(|&q| q == 4);
+        assert_eq!(tagged.transitions.len(), 4);
+        assert!(tagged.accepts(&['b', 'x']));
+    }
 }
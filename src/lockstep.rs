@@ -0,0 +1,154 @@
+use std::fmt::Debug;
+use std::hash::Hash;
+
+use crate::hash::HashMap;
+use crate::DFA;
+
+// A way in which two DFAs run on the same input stop agreeing.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Divergence {
+    // The input so far is accepted by the left DFA only.
+    OnlyLhsAccepts,
+    // The input so far is accepted by the right DFA only.
+    OnlyRhsAccepts,
+    // The left DFA has no transition for the latest symbol, and the right one does.
+    LhsStuck,
+    // The right DFA has no transition for the latest symbol, and the left one does.
+    RhsStuck,
+}
+
+// Steps two DFAs over the same input at once, reporting every point where they disagree. Once a
+// DFA is stuck it stays stuck, and rejects everything from then on.
+#[derive(Debug, Clone)]
+pub struct PairSimulator<'a, S, S2, E> {
+    lhs: &'a DFA<S, E>,
+    rhs: &'a DFA<S2, E>,
+    lhs_delta: HashMap<(S, E), S>,
+    rhs_delta: HashMap<(S2, E), S2>,
+    lhs_state: Option<S>,
+    rhs_state: Option<S2>,
+    position: usize,
+}
+
+impl<'a, S, S2, E> PairSimulator<'a, S, S2, E>
+where
+    S: Eq + Hash + Copy + Debug,
+    S2: Eq + Hash + Copy + Debug,
+    E: Eq + Hash + Copy + Debug,
+{
+    pub fn new(lhs: &'a DFA<S, E>, rhs: &'a DFA<S2, E>) -> PairSimulator<'a, S, S2, E> {
+        PairSimulator {
+            lhs,
+            rhs,
+            lhs_delta: lhs.delta(),
+            rhs_delta: rhs.delta(),
+            lhs_state: Some(lhs.initial_state),
+            rhs_state: Some(rhs.initial_state),
+            position: 0,
+        }
+    }
+
+    // Goes back to both initial states.
+    pub fn reset(&mut self) {
+        self.lhs_state = Some(self.lhs.initial_state);
+        self.rhs_state = Some(self.rhs.initial_state);
+        self.position = 0;
+    }
+
+    // The current state of each DFA, or `None` for one that is stuck.
+    pub fn states(&self) -> (Option<S>, Option<S2>) {
+        (self.lhs_state, self.rhs_state)
+    }
+
+    // The number of symbols read since the start.
+    pub fn position(&self) -> usize {
+        self.position
+    }
+
+    // Whether the input so far is accepted by each DFA.
+    pub fn accepting(&self) -> (bool, bool) {
+        (
+            self.lhs_state.is_some_and(|q| self.lhs.is_accepting(q)),
+            self.rhs_state.is_some_and(|q| self.rhs.is_accepting(q)),
+        )
+    }
+
+    // Whether the DFAs disagree about accepting the input so far.
+    pub fn acceptance_divergence(&self) -> Option<Divergence> {
+        match self.accepting() {
+            (true, false) => Some(Divergence::OnlyLhsAccepts),
+            (false, true) => Some(Divergence::OnlyRhsAccepts),
+            _ => None,
+        }
+    }
+
+    // Reads one symbol, and returns the ways the DFAs now disagree: first whether exactly one of
+    // them got stuck on this symbol, and then whether exactly one accepts.
+    pub fn step(&mut self, label: E) -> Vec<Divergence> {
+        let lhs = self
+            .lhs_state
+            .and_then(|q| self.lhs_delta.get(&(q, label)).copied());
+        let rhs = self
+            .rhs_state
+            .and_then(|q| self.rhs_delta.get(&(q, label)).copied());
+        let mut divergences = Vec::new();
+        if self.lhs_state.is_some() && lhs.is_none() && rhs.is_some() {
+            divergences.push(Divergence::LhsStuck);
+        }
+        if self.rhs_state.is_some() && rhs.is_none() && lhs.is_some() {
+            divergences.push(Divergence::RhsStuck);
+        }
+        self.lhs_state = lhs;
+        self.rhs_state = rhs;
+        self.position += 1;
+        divergences.extend(self.acceptance_divergence());
+        divergences
+    }
+
+    // Runs both DFAs over `trace` from the start, and returns every divergence along with the
+    // number of symbols read when it happened. A divergence at 0 is about the empty input.
+    pub fn run(&mut self, trace: &[E]) -> Vec<(usize, Divergence)> {
+        self.reset();
+        let mut divergences: Vec<(usize, Divergence)> = self
+            .acceptance_divergence()
+            .map(|d| (0, d))
+            .into_iter()
+            .collect();
+        for &label in trace {
+            let position = self.position + 1;
+            divergences.extend(self.step(label).into_iter().map(|d| (position, d)));
+        }
+        divergences
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::{Divergence, PairSimulator};
+    use crate::DFA;
+
+    #[test]
+    fn lockstep_divergences() {
+        // The spec accepts "ab" and "abc"; the implementation forgot "abc" but accepts "a".
+        let spec: DFA<u32, char> =
+            DFA::new(0, vec![2, 3], vec![(0, 'a', 1), (1, 'b', 2), (2, 'c', 3)]);
+        let implementation: DFA<char, char> =
+            DFA::new('x', vec!['y', 'z'], vec![('x', 'a', 'y'), ('y', 'b', 'z')]);
+        let mut sim = PairSimulator::new(&spec, &implementation);
+        assert_eq!(
+            sim.run(&['a', 'b', 'c', 'c']),
+            vec![
+                (1, Divergence::OnlyRhsAccepts),
+                (3, Divergence::RhsStuck),
+                (3, Divergence::OnlyLhsAccepts),
+            ]
+        );
+        assert_eq!(sim.states(), (None, None));
+        assert_eq!(sim.position(), 4);
+
+        sim.reset();
+        assert_eq!(sim.step('a'), vec![Divergence::OnlyRhsAccepts]);
+        assert_eq!(sim.step('b'), vec![]);
+        assert_eq!(sim.accepting(), (true, true));
+    }
+}
@@ -0,0 +1,360 @@
+// Linear temporal logic over finite traces (LTLf), compiled into DFAs. A trace is a non-empty
+// sequence of steps, and each step is the set of atomic propositions that hold at it, written as
+// a bitmask: proposition `i` holds when bit `i` is set. The DFA for a formula accepts exactly the
+// traces that satisfy it, so it can check process logs against declarative constraints.
+//
+// The formula is put in negation normal form, and then unrolled one step at a time: reading a
+// step turns each obligation into a choice of sets of obligations for the rest of the trace. The
+// sets reachable this way are the states of an NFA, which is determinized and minimized. Every
+// valuation of the propositions is a label, so this is only practical for a handful of them.
+
+use std::collections::VecDeque;
+
+use crate::hash::HashMap;
+use crate::nfa::NFA;
+use crate::DFA;
+
+// An LTLf formula over propositions numbered from 0 to 30, so that every valuation of them fits
+// in a `u32` label.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum Ltl {
+    True,
+    False,
+    Atom(u32),
+    Not(Box<Ltl>),
+    And(Box<Ltl>, Box<Ltl>),
+    Or(Box<Ltl>, Box<Ltl>),
+    // The next step exists and satisfies the formula.
+    Next(Box<Ltl>),
+    // If there is a next step, it satisfies the formula.
+    WeakNext(Box<Ltl>),
+    // The right formula holds at some step, and the left one at every step before it.
+    Until(Box<Ltl>, Box<Ltl>),
+    // The right formula holds at every step up to and including the first one where the left one
+    // holds, or at every step if there is none.
+    Release(Box<Ltl>, Box<Ltl>),
+    Eventually(Box<Ltl>),
+    Always(Box<Ltl>),
+}
+
+impl std::ops::Not for Ltl {
+    type Output = Ltl;
+
+    fn not(self) -> Ltl {
+        Ltl::Not(Box::new(self))
+    }
+}
+
+impl Ltl {
+    pub fn atom(p: u32) -> Ltl {
+        assert!(p < 31, "only propositions 0 to 30 are supported");
+        Ltl::Atom(p)
+    }
+
+    pub fn and(self, other: Ltl) -> Ltl {
+        Ltl::And(Box::new(self), Box::new(other))
+    }
+
+    pub fn or(self, other: Ltl) -> Ltl {
+        Ltl::Or(Box::new(self), Box::new(other))
+    }
+
+    pub fn implies(self, other: Ltl) -> Ltl {
+        (!self).or(other)
+    }
+
+    pub fn next(self) -> Ltl {
+        Ltl::Next(Box::new(self))
+    }
+
+    pub fn weak_next(self) -> Ltl {
+        Ltl::WeakNext(Box::new(self))
+    }
+
+    pub fn until(self, other: Ltl) -> Ltl {
+        Ltl::Until(Box::new(self), Box::new(other))
+    }
+
+    pub fn release(self, other: Ltl) -> Ltl {
+        Ltl::Release(Box::new(self), Box::new(other))
+    }
+
+    pub fn eventually(self) -> Ltl {
+        Ltl::Eventually(Box::new(self))
+    }
+
+    pub fn always(self) -> Ltl {
+        Ltl::Always(Box::new(self))
+    }
+
+    // The number of propositions the formula can mention: one more than the largest it does.
+    pub fn propositions(&self) -> u32 {
+        match self {
+            Ltl::True | Ltl::False => 0,
+            Ltl::Atom(p) => p + 1,
+            Ltl::Not(f) | Ltl::Next(f) | Ltl::WeakNext(f) | Ltl::Eventually(f) | Ltl::Always(f) => {
+                f.propositions()
+            }
+            Ltl::And(f, g) | Ltl::Or(f, g) | Ltl::Until(f, g) | Ltl::Release(f, g) => {
+                f.propositions().max(g.propositions())
+            }
+        }
+    }
+
+    // The minimal DFA accepting the traces that satisfy this formula, over every valuation of
+    // its propositions.
+    pub fn to_dfa(&self) -> DFA<u32, u32> {
+        self.to_nfa().determinize().prune_and_minimize()
+    }
+
+    // An NFA accepting the traces that satisfy this formula. Each state is a set of obligations
+    // for the rest of the trace, some of which need it to go on for at least one more step.
+    pub fn to_nfa(&self) -> NFA<u32, u32> {
+        let letters = 1u32
+            .checked_shl(self.propositions())
+            .expect("only propositions 0 to 30 are supported");
+        let mut arena = Arena::default();
+        let root = arena.nnf(self, true);
+
+        let start: Obligations = (vec![root], true);
+        let mut ids: HashMap<Obligations, u32> = HashMap::default();
+        let mut queue = VecDeque::new();
+        let mut final_states = Vec::new();
+        let mut transitions = Vec::new();
+        ids.insert(start.clone(), 0);
+        queue.push_back(start);
+        while let Some((formulas, strong)) = queue.pop_front() {
+            let src = ids[&(formulas.clone(), strong)];
+            if !strong {
+                final_states.push(src);
+            }
+            for letter in 0..letters {
+                let mut dnf: Dnf = vec![Vec::new()];
+                for &f in &formulas {
+                    dnf = conjoin(&dnf, &arena.step(f, letter));
+                }
+                for clause in dnf {
+                    let mut next: Vec<usize> = clause.iter().map(|&(f, _)| f).collect();
+                    next.sort_unstable();
+                    next.dedup();
+                    let next = (next, clause.iter().any(|&(_, strong)| strong));
+                    let n = ids.len() as u32;
+                    let dst = *ids.entry(next.clone()).or_insert_with(|| {
+                        queue.push_back(next);
+                        n
+                    });
+                    transitions.push((src, Some(letter), dst));
+                }
+            }
+        }
+        NFA::new(0, final_states, transitions)
+    }
+}
+
+// A set of formulas that must hold from the next step on, and whether that step must exist.
+type Obligations = (Vec<usize>, bool);
+// A disjunction of conjunctions of obligations for the next step, each with whether it needs the
+// step to exist. `vec![]` is false, and `vec![vec![]]` is true.
+type Dnf = Vec<Vec<(usize, bool)>>;
+
+// A formula in negation normal form, with its subformulas interned in an `Arena`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+enum Nnf {
+    True,
+    False,
+    Literal(u32, bool),
+    And(usize, usize),
+    Or(usize, usize),
+    Next(usize),
+    WeakNext(usize),
+    Until(usize, usize),
+    Release(usize, usize),
+}
+
+#[derive(Default)]
+struct Arena {
+    nodes: Vec<Nnf>,
+    ids: HashMap<Nnf, usize>,
+}
+
+impl Arena {
+    fn intern(&mut self, node: Nnf) -> usize {
+        let nodes = &mut self.nodes;
+        *self.ids.entry(node).or_insert_with(|| {
+            nodes.push(node);
+            nodes.len() - 1
+        })
+    }
+
+    // Interns `f`, or its negation if `positive` is false, pushing negations down to the atoms.
+    fn nnf(&mut self, f: &Ltl, positive: bool) -> usize {
+        let node = match (f, positive) {
+            (Ltl::True, true) | (Ltl::False, false) => Nnf::True,
+            (Ltl::True, false) | (Ltl::False, true) => Nnf::False,
+            (Ltl::Atom(p), _) => Nnf::Literal(*p, positive),
+            (Ltl::Not(f), _) => return self.nnf(f, !positive),
+            (Ltl::And(f, g), true) | (Ltl::Or(f, g), false) => {
+                Nnf::And(self.nnf(f, positive), self.nnf(g, positive))
+            }
+            (Ltl::Or(f, g), true) | (Ltl::And(f, g), false) => {
+                Nnf::Or(self.nnf(f, positive), self.nnf(g, positive))
+            }
+            (Ltl::Next(f), true) | (Ltl::WeakNext(f), false) => Nnf::Next(self.nnf(f, positive)),
+            (Ltl::WeakNext(f), true) | (Ltl::Next(f), false) => {
+                Nnf::WeakNext(self.nnf(f, positive))
+            }
+            (Ltl::Until(f, g), true) | (Ltl::Release(f, g), false) => {
+                Nnf::Until(self.nnf(f, positive), self.nnf(g, positive))
+            }
+            (Ltl::Release(f, g), true) | (Ltl::Until(f, g), false) => {
+                Nnf::Release(self.nnf(f, positive), self.nnf(g, positive))
+            }
+            // F f = true U f, and G f = false R f.
+            (Ltl::Eventually(f), true) | (Ltl::Always(f), false) => {
+                let t = self.intern(Nnf::True);
+                Nnf::Until(t, self.nnf(f, positive))
+            }
+            (Ltl::Always(f), true) | (Ltl::Eventually(f), false) => {
+                let ff = self.intern(Nnf::False);
+                Nnf::Release(ff, self.nnf(f, positive))
+            }
+        };
+        self.intern(node)
+    }
+
+    // What must hold from the next step on for formula `f` to hold at a step with `letter`.
+    fn step(&self, f: usize, letter: u32) -> Dnf {
+        match self.nodes[f] {
+            Nnf::True => vec![Vec::new()],
+            Nnf::False => Vec::new(),
+            Nnf::Literal(p, positive) => {
+                if (letter >> p & 1 == 1) == positive {
+                    vec![Vec::new()]
+                } else {
+                    Vec::new()
+                }
+            }
+            Nnf::And(g, h) => conjoin(&self.step(g, letter), &self.step(h, letter)),
+            Nnf::Or(g, h) => {
+                let mut dnf = self.step(g, letter);
+                dnf.extend(self.step(h, letter));
+                dnf
+            }
+            Nnf::Next(g) => vec![vec![(g, true)]],
+            Nnf::WeakNext(g) => vec![vec![(g, false)]],
+            // g U h = h or (g and X (g U h)).
+            Nnf::Until(g, h) => {
+                let mut dnf = self.step(h, letter);
+                dnf.extend(conjoin(&self.step(g, letter), &[vec![(f, true)]]));
+                dnf
+            }
+            // g R h = h and (g or WX (g R h)).
+            Nnf::Release(g, h) => {
+                let mut either = self.step(g, letter);
+                either.push(vec![(f, false)]);
+                conjoin(&self.step(h, letter), &either)
+            }
+        }
+    }
+}
+
+fn conjoin(lhs: &[Vec<(usize, bool)>], rhs: &[Vec<(usize, bool)>]) -> Dnf {
+    let mut dnf = Vec::new();
+    for a in lhs {
+        for b in rhs {
+            dnf.push(a.iter().chain(b).copied().collect());
+        }
+    }
+    dnf
+}
+
+#[cfg(test)]
+mod test {
+    use super::Ltl;
+
+    // Whether `f` holds at step `i` of `trace`, straight from the definition.
+    fn holds(f: &Ltl, trace: &[u32], i: usize) -> bool {
+        let rest = || i..trace.len();
+        match f {
+            Ltl::True => true,
+            Ltl::False => false,
+            Ltl::Atom(p) => trace[i] >> p & 1 == 1,
+            Ltl::Not(f) => !holds(f, trace, i),
+            Ltl::And(f, g) => holds(f, trace, i) && holds(g, trace, i),
+            Ltl::Or(f, g) => holds(f, trace, i) || holds(g, trace, i),
+            Ltl::Next(f) => i + 1 < trace.len() && holds(f, trace, i + 1),
+            Ltl::WeakNext(f) => i + 1 == trace.len() || holds(f, trace, i + 1),
+            Ltl::Until(f, g) => {
+                rest().any(|j| holds(g, trace, j) && (i..j).all(|k| holds(f, trace, k)))
+            }
+            Ltl::Release(f, g) => {
+                rest().all(|j| holds(g, trace, j) || (i..j).any(|k| holds(f, trace, k)))
+            }
+            Ltl::Eventually(f) => rest().any(|j| holds(f, trace, j)),
+            Ltl::Always(f) => rest().all(|j| holds(f, trace, j)),
+        }
+    }
+
+    #[test]
+    fn matches_semantics() {
+        let (p, q) = (Ltl::atom(0), Ltl::atom(1));
+        let formulas = vec![
+            // Every request is eventually granted.
+            p.clone().implies(q.clone().eventually()).always(),
+            p.clone().until(q.clone()),
+            p.clone().release(q.clone()),
+            p.clone().next(),
+            !p.clone().weak_next(),
+            q.clone().next().eventually().and(!p.clone().always()),
+            Ltl::atom(2)
+                .implies(p.clone().or(q.clone()).weak_next())
+                .always(),
+        ];
+        for f in &formulas {
+            let dfa = f.to_dfa();
+            let letters = 1u32 << f.propositions();
+            let mut traces: Vec<Vec<u32>> = vec![Vec::new()];
+            for _ in 0..4 {
+                traces = traces
+                    .iter()
+                    .flat_map(|t| {
+                        (0..letters).map(move |l| {
+                            let mut t = t.clone();
+                            t.push(l);
+                            t
+                        })
+                    })
+                    .collect();
+                for trace in &traces {
+                    assert_eq!(
+                        dfa.accepts(trace),
+                        holds(f, trace, 0),
+                        "{:?} on {:?}",
+                        f,
+                        trace
+                    );
+                }
+            }
+            // Traces are never empty.
+            assert!(!dfa.accepts(&[]));
+        }
+    }
+
+    #[test]
+    fn response_pattern() {
+        // G(request -> F grant) needs only "waiting" and "not waiting".
+        let (request, grant) = (Ltl::atom(0), Ltl::atom(1));
+        let response = request.implies(grant.eventually()).always();
+        let dfa = response.to_dfa();
+        assert_eq!(dfa.state_count(), 3);
+        assert!(dfa.accepts(&[0b01, 0b00, 0b10]));
+        assert!(!dfa.accepts(&[0b01, 0b00]));
+        assert!(dfa.accepts(&[0b11]));
+    }
+
+    #[test]
+    #[should_panic(expected = "only propositions 0 to 30")]
+    fn atom_out_of_range() {
+        Ltl::atom(31);
+    }
+}
@@ -0,0 +1,382 @@
+use std::collections::HashMap;
+use std::env;
+use std::fs;
+use std::io::{self, BufRead, Read, Write};
+use std::process;
+
+use dfa_utils::testing::seeded_rng;
+use dfa_utils::{Anchoring, CharClass, Format, NamedDFA, Predicate, Regex, Stats, DFA};
+
+const USAGE: &str = "usage:
+  dfa-utils minimize [INPUT] [--from FORMAT] [--to FORMAT] [--out FILE] [--stats]
+  dfa-utils equiv A B [--from FORMAT]
+  dfa-utils subset A B [--from FORMAT]
+  dfa-utils compile PATTERN [--to FORMAT] [--out FILE]
+  dfa-utils match (PATTERN | --file FILE) [--from FORMAT] [--invert] [--anchored] [--count]
+  dfa-utils words (PATTERN | --file FILE) [--from FORMAT] [--max-len N] [--sample N [--seed N]]
+
+INPUT and --out default to stdin and stdout. Formats are dot, json and dfa, and are guessed from
+file extensions when not given. `--out FORMAT` writes that format to stdout.
+
+equiv and subset exit with status 1 and print a witness word when the check fails: for equiv, a
+word accepted by exactly one automaton, and for subset, a word that A accepts and B rejects.
+
+match prints the lines of stdin that contain a match, or with --anchored, that match entirely.
+An automaton loaded with --file reads each character as the label of the same name.
+
+words lists every accepted word up to --max-len, shortest first, or with --sample, picks N of
+them uniformly at random. A pattern's character classes count as one symbol each, and are
+listed in regex syntax.";
+
+fn main() {
+    let args: Vec<String> = env::args().skip(1).collect();
+    let result = match args.first().map(String::as_str) {
+        Some("minimize") => minimize(&args[1..]),
+        Some("equiv") => compare(&args[1..], false),
+        Some("subset") => compare(&args[1..], true),
+        Some("compile") => compile(&args[1..]),
+        Some("match") => grep(&args[1..]),
+        Some("words") => words(&args[1..]),
+        Some("-h") | Some("--help") => {
+            println!("{}", USAGE);
+            Ok(())
+        }
+        _ => Err(USAGE.to_string()),
+    };
+    if let Err(message) = result {
+        eprintln!("{}", message);
+        process::exit(2);
+    }
+}
+
+// The options shared by every subcommand.
+#[derive(Default)]
+struct Options {
+    inputs: Vec<String>,
+    from: Option<Format>,
+    to: Option<Format>,
+    out: Option<String>,
+    file: Option<String>,
+    // Flags without a value, such as `--stats`.
+    flags: Vec<String>,
+    // Options whose value is a number, such as `--seed`.
+    numbers: Vec<(String, String)>,
+}
+
+// The options that take a number, which a subcommand accepts like its flags.
+const NUMBERS: [&str; 3] = ["--max-len", "--sample", "--seed"];
+
+impl Options {
+    fn parse(args: &[String], flags: &[&str]) -> Result<Options, String> {
+        let mut options = Options::default();
+        let mut args = args.iter();
+        while let Some(arg) = args.next() {
+            let mut value = |name: &str| {
+                args.next()
+                    .cloned()
+                    .ok_or_else(|| format!("{} needs a value", name))
+            };
+            let format = |name: String| {
+                Format::from_name(&name).ok_or_else(|| format!("unknown format {:?}", name))
+            };
+            match arg.as_str() {
+                "--from" => options.from = Some(format(value("--from")?)?),
+                "--to" => options.to = Some(format(value("--to")?)?),
+                "--file" => options.file = Some(value("--file")?),
+                "--out" => {
+                    let out = value("--out")?;
+                    match Format::from_name(&out) {
+                        Some(format) => options.to = Some(format),
+                        None => options.out = Some(out),
+                    }
+                }
+                flag if flags.contains(&flag) && NUMBERS.contains(&flag) => {
+                    options.numbers.push((flag.to_string(), value(flag)?));
+                }
+                flag if flags.contains(&flag) => options.flags.push(flag.to_string()),
+                flag if flag.starts_with("--") => return Err(format!("unknown option {}", flag)),
+                input => options.inputs.push(input.to_string()),
+            }
+        }
+        Ok(options)
+    }
+
+    fn flag(&self, name: &str) -> bool {
+        self.flags.iter().any(|flag| flag == name)
+    }
+
+    fn number(&self, name: &str) -> Result<Option<u64>, String> {
+        match self.numbers.iter().rev().find(|(flag, _)| flag == name) {
+            None => Ok(None),
+            Some((_, value)) => value
+                .parse()
+                .map(Some)
+                .map_err(|_| format!("{} needs a number, not {:?}", name, value)),
+        }
+    }
+
+    // Reads the automaton at `path`, or stdin for `-`.
+    fn read(&self, path: &str) -> Result<NamedDFA, String> {
+        let mut text = String::new();
+        if path == "-" {
+            io::stdin()
+                .read_to_string(&mut text)
+                .map_err(|e| format!("stdin: {}", e))?;
+        } else {
+            text = fs::read_to_string(path).map_err(|e| format!("{}: {}", path, e))?;
+        }
+        let format = self
+            .from
+            .or_else(|| Format::from_path(path))
+            .ok_or_else(|| format!("{}: cannot tell the format, pass --from", path))?;
+        NamedDFA::parse(&text, format).map_err(|e| format!("{}: {}", path, e))
+    }
+
+    fn write(&self, dfa: &NamedDFA) -> Result<(), String> {
+        let out = self.out.as_deref().unwrap_or("-");
+        let format = self
+            .to
+            .or_else(|| Format::from_path(out))
+            .or(self.from)
+            .or_else(|| self.inputs.first().and_then(|path| Format::from_path(path)))
+            .unwrap_or(Format::Dot);
+        let text = dfa.render(format);
+        if out == "-" {
+            io::stdout()
+                .write_all(text.as_bytes())
+                .map_err(|e| format!("stdout: {}", e))
+        } else {
+            fs::write(out, text).map_err(|e| format!("{}: {}", out, e))
+        }
+    }
+}
+
+fn minimize(args: &[String]) -> Result<(), String> {
+    let options = Options::parse(args, &["--stats"])?;
+    let input = match options.inputs.as_slice() {
+        [] => "-",
+        [input] => input.as_str(),
+        _ => return Err(USAGE.to_string()),
+    };
+    let named = options.read(input)?;
+    let before = named.dfa.stats();
+    let minimized = named.minimize();
+    if options.flag("--stats") {
+        print_stats("input", &before);
+        print_stats("minimized", &minimized.dfa.stats());
+    }
+    options.write(&minimized)
+}
+
+// Checks that two automata accept the same language, or with `subset`, that the first accepts
+// only words that the second does.
+fn compare(args: &[String], subset: bool) -> Result<(), String> {
+    let options = Options::parse(args, &[])?;
+    let (mut a, mut b) = match options.inputs.as_slice() {
+        [a, b] => (options.read(a)?, options.read(b)?),
+        _ => return Err(USAGE.to_string()),
+    };
+    a.align_labels(&mut b);
+    let witness = if subset {
+        a.dfa.inclusion_witness(&b.dfa)
+    } else {
+        a.dfa.difference_witness(&b.dfa)
+    };
+    let word = match witness {
+        None => return Ok(()),
+        Some(word) => word,
+    };
+    let (a_name, b_name) = (&options.inputs[0], &options.inputs[1]);
+    let (accepted, rejected) = if a.dfa.accepts(&word) {
+        (a_name, b_name)
+    } else {
+        (b_name, a_name)
+    };
+    println!("{}", a.render_word(&word));
+    eprintln!("accepted by {} but not by {}", accepted, rejected);
+    process::exit(1);
+}
+
+// Compiles a regex to its minimal automaton, labeled by character classes.
+fn compile(args: &[String]) -> Result<(), String> {
+    let options = Options::parse(args, &[])?;
+    let pattern = match options.inputs.as_slice() {
+        [pattern] => pattern,
+        _ => return Err(USAGE.to_string()),
+    };
+    let regex = Regex::parse(pattern).map_err(|e| format!("{:?}: {}", pattern, e))?;
+    options.write(&NamedDFA::from_sfa(&regex.to_dfa().minimize()))
+}
+
+// The labels of an automaton loaded by `load`: character classes for a pattern, or the names
+// of the labels in a file, sorted.
+enum Labels {
+    Classes(Vec<CharClass>),
+    Names(Vec<String>),
+}
+
+impl Labels {
+    // The label that `c` is read as, or `u32::MAX` for a character without one.
+    fn symbol(&self, c: char) -> u32 {
+        let position = match self {
+            Labels::Classes(classes) => classes.iter().position(|class| class.contains(&c)),
+            Labels::Names(names) => names.iter().position(|name| name.chars().eq(Some(c))),
+        };
+        position.map_or(u32::MAX, |i| i as u32)
+    }
+
+    // Writes out a word. With an `rng`, each class is replaced by a random character in it, and
+    // otherwise classes of more than one character are written in regex syntax.
+    fn render(&self, word: &[u32], rng: Option<&mut dyn FnMut() -> u64>) -> String {
+        match self {
+            Labels::Classes(classes) => {
+                let mut rng = rng;
+                word.iter()
+                    .map(|&i| {
+                        let class = &classes[i as usize];
+                        let chars: Vec<std::ops::RangeInclusive<char>> = class.ranges().collect();
+                        match (chars.as_slice(), rng.as_mut()) {
+                            ([range], _) if range.start() == range.end() => {
+                                range.start().to_string()
+                            }
+                            (_, Some(rng)) => random_char(class, rng).to_string(),
+                            (_, None) => class.to_string(),
+                        }
+                    })
+                    .collect()
+            }
+            Labels::Names(names) => {
+                let names: Vec<&str> = word.iter().map(|&i| names[i as usize].as_str()).collect();
+                // Single-character labels spell out words, and longer ones are kept apart.
+                let separator = if names.iter().all(|name| name.chars().count() == 1) {
+                    ""
+                } else {
+                    " "
+                };
+                names.join(separator)
+            }
+        }
+    }
+}
+
+// Picks a character of `class` uniformly at random.
+fn random_char(class: &CharClass, rng: &mut dyn FnMut() -> u64) -> char {
+    let size = |range: &std::ops::RangeInclusive<char>| {
+        u64::from(*range.end()) - u64::from(*range.start()) + 1
+    };
+    let total: u64 = class.ranges().map(|range| size(&range)).sum();
+    let mut x = rng() % total;
+    for range in class.ranges() {
+        if x < size(&range) {
+            // Classes never contain surrogates, so every codepoint in a range is a `char`.
+            return char::from_u32(u32::from(*range.start()) + x as u32).unwrap();
+        }
+        x -= size(&range);
+    }
+    unreachable!()
+}
+
+// Loads the automaton given as a pattern or with `--file`.
+fn load(options: &Options) -> Result<(DFA<u32, u32>, Labels), String> {
+    match (options.inputs.as_slice(), &options.file) {
+        ([pattern], None) => {
+            let regex = Regex::parse(pattern).map_err(|e| format!("{:?}: {}", pattern, e))?;
+            // The minterms cover every character.
+            let (dfa, minterms) = regex.to_dfa().minimize().to_dfa();
+            Ok((dfa.map_labels(|i| i as u32), Labels::Classes(minterms)))
+        }
+        ([], Some(file)) => {
+            let named = options.read(file)?;
+            let mut names = named.labels.clone();
+            names.sort();
+            let rank: HashMap<&str, u32> = names
+                .iter()
+                .enumerate()
+                .map(|(i, name)| (name.as_str(), i as u32))
+                .collect();
+            let dfa = named
+                .dfa
+                .map_labels(|e| rank[named.labels[e as usize].as_str()]);
+            Ok((dfa, Labels::Names(names)))
+        }
+        _ => Err(USAGE.to_string()),
+    }
+}
+
+// Filters the lines of stdin, like grep.
+fn grep(args: &[String]) -> Result<(), String> {
+    let options = Options::parse(args, &["--invert", "--anchored", "--count"])?;
+    let (dfa, labels) = load(&options)?;
+    let anchoring = if options.flag("--anchored") {
+        Anchoring::Anchored
+    } else {
+        Anchoring::Unanchored
+    };
+    let matcher = dfa.matcher(anchoring);
+    let mut cache: HashMap<char, u32> = HashMap::new();
+    let mut count = 0;
+    let stdout = io::stdout();
+    let mut out = io::BufWriter::new(stdout.lock());
+    for line in io::stdin().lock().lines() {
+        let line = line.map_err(|e| format!("stdin: {}", e))?;
+        let input: Vec<u32> = line
+            .chars()
+            .map(|c| *cache.entry(c).or_insert_with(|| labels.symbol(c)))
+            .collect();
+        if matcher.is_match(&input) == options.flag("--invert") {
+            continue;
+        }
+        count += 1;
+        if !options.flag("--count") {
+            writeln!(out, "{}", line).map_err(|e| format!("stdout: {}", e))?;
+        }
+    }
+    if options.flag("--count") {
+        writeln!(out, "{}", count).map_err(|e| format!("stdout: {}", e))?;
+    }
+    Ok(())
+}
+
+// Enumerates accepted words in length-lexicographic order, or samples them.
+fn words(args: &[String]) -> Result<(), String> {
+    let options = Options::parse(args, &["--max-len", "--sample", "--seed"])?;
+    let (dfa, labels) = load(&options)?;
+    let max_len = options.number("--max-len")?;
+    let stdout = io::stdout();
+    let mut out = io::BufWriter::new(stdout.lock());
+    match options.number("--sample")? {
+        None => {
+            for word in dfa.words(max_len.map(|n| n as usize)) {
+                writeln!(out, "{}", labels.render(&word, None))
+                    .map_err(|e| format!("stdout: {}", e))?;
+            }
+        }
+        Some(n) => {
+            let max_len = max_len.ok_or("--sample needs --max-len")? as usize;
+            let mut rng = seeded_rng(options.number("--seed")?.unwrap_or(0));
+            for _ in 0..n {
+                let word = match dfa.sample_up_to(max_len, &mut rng) {
+                    Some(word) => word,
+                    None => return Err(format!("no words of length up to {}", max_len)),
+                };
+                writeln!(out, "{}", labels.render(&word, Some(&mut rng)))
+                    .map_err(|e| format!("stdout: {}", e))?;
+            }
+        }
+    }
+    Ok(())
+}
+
+fn print_stats(name: &str, stats: &Stats) {
+    eprintln!(
+        "{}: {} states, {} transitions, {} accepting, alphabet of {}, {:.0}% complete, diameter {}, {} SCCs",
+        name,
+        stats.states,
+        stats.transitions,
+        stats.accepting_states,
+        stats.alphabet_size,
+        100.0 * stats.completeness,
+        stats.diameter,
+        stats.sccs,
+    );
+}
@@ -0,0 +1,186 @@
+use std::fmt::Debug;
+use std::hash::Hash;
+use std::ops::Range;
+
+use crate::hash::{HashMap, HashSet};
+use crate::nfa::NFA;
+use crate::DFA;
+
+// Whether a match must span the whole input, or may occur anywhere inside it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Anchoring {
+    // The DFA's own semantics: the whole input must be accepted.
+    Anchored,
+    // As if the language had an implicit leading `.*`: any substring may match.
+    Unanchored,
+}
+
+// A DFA compiled for matching against input, from `DFA::matcher`.
+#[derive(Debug, Clone)]
+pub struct Matcher<E> {
+    anchoring: Anchoring,
+    exact: Compiled<E>,
+    // The DFA with a self-loop on its initial state over every symbol, which tracks every match
+    // in progress at once. Only built for unanchored matching.
+    scanner: Option<Compiled<E>>,
+}
+
+// A DFA with states numbered from 0 (the initial state), ready for fast stepping.
+#[derive(Debug, Clone)]
+struct Compiled<E> {
+    final_states: HashSet<u32>,
+    delta: HashMap<(u32, E), u32>,
+}
+
+impl<S, E> DFA<S, E>
+where
+    S: Eq + Hash + Copy + Debug,
+    E: Eq + Hash + Copy + Debug,
+{
+    pub fn matcher(&self, anchoring: Anchoring) -> Matcher<E> {
+        let exact = self.to_nfa(false).determinize();
+        let scanner = match anchoring {
+            Anchoring::Anchored => None,
+            Anchoring::Unanchored => Some(Compiled::new(self.to_nfa(true).determinize())),
+        };
+        Matcher {
+            anchoring,
+            exact: Compiled::new(exact),
+            scanner,
+        }
+    }
+
+    // The DFA as an NFA, optionally with a self-loop on the initial state over every symbol so
+    // that a match may begin anywhere.
+    pub(crate) fn to_nfa(&self, restart_anywhere: bool) -> NFA<S, E> {
+        let mut transitions: Vec<(S, Option<E>, S)> = (&self.transitions)
+            .into_iter()
+            .map(|&(src, label, dst)| (src, Some(label), dst))
+            .collect();
+        if restart_anywhere {
+            for label in self.transitions.by_b().into_keys() {
+                transitions.push((self.initial_state, Some(label), self.initial_state));
+            }
+        }
+        NFA::new(
+            self.initial_state,
+            self.final_states.iter().copied(),
+            transitions,
+        )
+    }
+}
+
+impl<E> Compiled<E>
+where
+    E: Eq + Hash + Copy + Debug,
+{
+    fn new(dfa: DFA<u32, E>) -> Compiled<E> {
+        Compiled {
+            delta: dfa.delta(),
+            final_states: dfa.final_states,
+        }
+    }
+
+    // The ends of every prefix of `input` that is accepted, in increasing order, stopping at the
+    // first missing transition.
+    fn accepted_prefixes<'a>(&'a self, input: &'a [E]) -> impl Iterator<Item = usize> + 'a {
+        let mut q = Some(0);
+        let states = std::iter::once(q).chain(input.iter().map(move |&label| {
+            q = q.and_then(|src| self.delta.get(&(src, label)).copied());
+            q
+        }));
+        states
+            .map_while(|q| q)
+            .enumerate()
+            .filter(move |(_, q)| self.final_states.contains(q))
+            .map(|(end, _)| end)
+    }
+}
+
+impl<E> Matcher<E>
+where
+    E: Eq + Hash + Copy + Debug,
+{
+    pub fn anchoring(&self) -> Anchoring {
+        self.anchoring
+    }
+
+    // Anchored: whether the whole input is accepted. Unanchored: whether any substring is.
+    pub fn is_match(&self, input: &[E]) -> bool {
+        match self.anchoring {
+            Anchoring::Anchored => self.exact.accepted_prefixes(input).last() == Some(input.len()),
+            Anchoring::Unanchored => self.earliest_end(input).is_some(),
+        }
+    }
+
+    // Finds the match that ends earliest, and among those the one that starts earliest.
+    // Anchored matches always start at 0, and need not span the whole input.
+    pub fn find(&self, input: &[E]) -> Option<Range<usize>> {
+        match self.anchoring {
+            Anchoring::Anchored => self.exact.accepted_prefixes(input).next().map(|end| 0..end),
+            Anchoring::Unanchored => {
+                let end = self.earliest_end(input)?;
+                (0..=end)
+                    .find(|&start| {
+                        self.exact
+                            .accepted_prefixes(&input[start..end])
+                            .any(|len| start + len == end)
+                    })
+                    .map(|start| start..end)
+            }
+        }
+    }
+
+    fn earliest_end(&self, input: &[E]) -> Option<usize> {
+        let scanner = self.scanner.as_ref()?;
+        let mut q = 0;
+        if scanner.final_states.contains(&q) {
+            return Some(0);
+        }
+        for (i, &label) in input.iter().enumerate() {
+            // The initial state loops on every symbol the DFA knows, so a missing transition
+            // means a symbol that no match can contain.
+            q = scanner.delta.get(&(q, label)).copied().unwrap_or(0);
+            if scanner.final_states.contains(&q) {
+                return Some(i + 1);
+            }
+        }
+        None
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::Anchoring;
+    use crate::DFA;
+
+    fn chars(s: &str) -> Vec<char> {
+        s.chars().collect()
+    }
+
+    // Accepts "ab" followed by any number of "c"s.
+    fn abc() -> DFA<u32, char> {
+        DFA::new(0, vec![2], vec![(0, 'a', 1), (1, 'b', 2), (2, 'c', 2)])
+    }
+
+    #[test]
+    fn anchored() {
+        let matcher = abc().matcher(Anchoring::Anchored);
+        assert!(matcher.is_match(&chars("abcc")));
+        assert!(!matcher.is_match(&chars("xabcc")));
+        assert!(!matcher.is_match(&chars("abx")));
+        assert_eq!(matcher.find(&chars("abx")), Some(0..2));
+        assert_eq!(matcher.find(&chars("xab")), None);
+    }
+
+    #[test]
+    fn unanchored() {
+        let matcher = abc().matcher(Anchoring::Unanchored);
+        assert!(matcher.is_match(&chars("xxaabcc")));
+        assert!(matcher.is_match(&chars("a-ab")));
+        assert!(!matcher.is_match(&chars("a-b")));
+        assert_eq!(matcher.find(&chars("xxaabcc")), Some(3..5));
+        assert_eq!(matcher.find(&chars("a-zab")), Some(3..5));
+        assert_eq!(matcher.find(&chars("ba")), None);
+    }
+}
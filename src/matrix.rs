@@ -0,0 +1,378 @@
+use std::fmt::Debug;
+use std::hash::Hash;
+
+use crate::hash::{HashMap, IntoStd};
+use crate::DFA;
+
+type Matrix = Vec<Vec<f64>>;
+
+impl<S, E> DFA<S, E>
+where
+    S: Eq + Hash + Copy + Debug,
+    E: Eq + Hash + Copy + Debug,
+{
+    // The probability that a word of length `n`, with each symbol drawn uniformly at random from
+    // the transition labels, is accepted. Missing transitions reject.
+    pub fn acceptance_probability(&self, n: u64) -> f64 {
+        let (index, step) = self.random_step_matrix();
+        self.accepted_mass(&index, &matrix_pow(&step, n, 0.0, 1.0, &f64_mul_add))
+    }
+
+    // The limit of `acceptance_probability(n)` as `n` grows, or `None` if it does not converge
+    // (e.g., because the DFA alternates between accepting and rejecting states).
+    pub fn limiting_acceptance_probability(&self) -> Option<f64> {
+        const EPSILON: f64 = 1e-12;
+        let (index, step) = self.random_step_matrix();
+        // Square until `q = step^(2^k)` is (nearly) unchanged by taking one more step.
+        let mut q = step.clone();
+        for _ in 0..64 {
+            let next = matrix_mul(&q, &step, 0.0, &f64_mul_add);
+            let delta = q
+                .iter()
+                .flatten()
+                .zip(next.iter().flatten())
+                .map(|(a, b)| (a - b).abs())
+                .fold(0.0, f64::max);
+            if delta < EPSILON {
+                return Some(self.accepted_mass(&index, &q));
+            }
+            q = matrix_mul(&q, &q, 0.0, &f64_mul_add);
+        }
+        None
+    }
+
+    // Counts the accepted words of length `n` with O(log n) multiplications of a matrix with one
+    // row per useful state. Returns `None` if some intermediate count overflows a `u128`.
+    pub(crate) fn count_words_by_matrix(&self, n: u64) -> Option<u128> {
+        let (index, adjacency) = self.trim_adjacency(Some(0), |x| x.map(|x| x + 1));
+        let checked_mul_add = |acc: Option<u128>, x: Option<u128>, y: Option<u128>| {
+            acc?.checked_add(x?.checked_mul(y?)?)
+        };
+        let paths = matrix_pow(&adjacency, n, Some(0), Some(1), &checked_mul_add);
+        self.accepted_paths(&index, &paths, Some(0), |acc, x| acc?.checked_add(x?))
+    }
+
+    // Counts the accepted words of length `n`, modulo `modulus`. This takes O(log n) matrix
+    // multiplications, so `n` can be huge. Panics if `modulus` is 0.
+    pub fn count_words_at_mod(&self, n: u64, modulus: u64) -> u64 {
+        assert!(modulus > 0, "the modulus must be positive");
+        let (index, adjacency) = self.trim_adjacency(0, |x| (x + 1) % modulus);
+        let mod_mul_add = |acc: u64, x: u64, y: u64| {
+            ((u128::from(acc) + u128::from(x) * u128::from(y)) % u128::from(modulus)) as u64
+        };
+        let paths = matrix_pow(&adjacency, n, 0, 1 % modulus, &mod_mul_add);
+        // `acc + x` can overflow a `u64` when `modulus` is over half of `u64::MAX`.
+        self.accepted_paths(&index, &paths, 0, |acc, x| {
+            ((u128::from(acc) + u128::from(x)) % u128::from(modulus)) as u64
+        })
+    }
+
+    // Numbers the states that are both reachable and co-accessible, and builds their adjacency
+    // matrix, where `inc` adds one transition to an entry.
+    fn trim_adjacency<T: Copy>(
+        &self,
+        zero: T,
+        inc: impl Fn(T) -> T,
+    ) -> (HashMap<S, usize>, Vec<Vec<T>>) {
+        let coaccessible = self.coaccessible();
+        let index: HashMap<S, usize> = self
+            .reachable()
+            .into_iter()
+            .filter(|q| coaccessible.contains(q))
+            .enumerate()
+            .map(|(i, q)| (q, i))
+            .collect();
+        let mut adjacency = vec![vec![zero; index.len()]; index.len()];
+        for (src, _, dst) in &self.transitions {
+            if let (Some(&i), Some(&j)) = (index.get(src), index.get(dst)) {
+                adjacency[i][j] = inc(adjacency[i][j]);
+            }
+        }
+        (index, adjacency)
+    }
+
+    // Sums the entries of a path-count matrix from the initial state to the accepting states.
+    fn accepted_paths<T: Copy>(
+        &self,
+        index: &HashMap<S, usize>,
+        paths: &[Vec<T>],
+        zero: T,
+        add: impl Fn(T, T) -> T,
+    ) -> T {
+        let row = match index.get(&self.initial_state) {
+            Some(&i) => &paths[i],
+            None => return zero,
+        };
+        self.final_states
+            .iter()
+            .filter_map(|q| index.get(q))
+            .fold(zero, |acc, &j| add(acc, row[j]))
+    }
+
+    // The topological entropy of the language, in bits per symbol: the base-2 logarithm of the
+    // growth rate of the number of accepted words of length n. It is computed as the dominant
+    // eigenvalue of the adjacency matrix of the trimmed DFA, so only states that are both
+    // reachable and can lead to acceptance count. Finite languages have entropy 0.
+    pub fn entropy(&self) -> f64 {
+        let (_, adjacency) = self.trim_adjacency(0.0, |x| x + 1.0);
+        let radius = spectral_radius(&adjacency);
+        if radius < 1.0 {
+            // The only possible eigenvalue below 1 is 0, for finite languages.
+            return 0.0;
+        }
+        radius.log2()
+    }
+
+    // Numbers the states, and builds the matrix whose (i, j) entry is the probability of moving
+    // from state i to state j on a uniformly random label.
+    fn random_step_matrix(&self) -> (HashMap<S, usize>, Matrix) {
+        let index: HashMap<S, usize> = self
+            .states()
+            .into_iter()
+            .enumerate()
+            .map(|(i, q)| (q, i))
+            .collect();
+        let alphabet_size = self.transitions.by_b().len();
+        let mut step = vec![vec![0.0; index.len()]; index.len()];
+        for &(src, _, dst) in &self.transitions {
+            step[index[&src]][index[&dst]] += 1.0 / alphabet_size as f64;
+        }
+        (index, step)
+    }
+
+    // Given a matrix of transition probabilities, the probability of ending up in an accepting
+    // state when starting from the initial state.
+    fn accepted_mass(&self, index: &HashMap<S, usize>, m: &[Vec<f64>]) -> f64 {
+        let row = &m[index[&self.initial_state]];
+        self.final_states.iter().map(|q| row[index[q]]).sum()
+    }
+}
+
+impl<S, E> DFA<S, E>
+where
+    S: Eq + Hash + Copy + Debug + Ord,
+    E: Eq + Hash + Copy + Debug,
+{
+    // Returns the states in increasing order, and the matrix whose (i, j) entry counts the
+    // transitions from the i-th state to the j-th state.
+    pub fn adjacency_matrix(&self) -> (Vec<S>, Vec<Vec<u64>>) {
+        let (states, index) = self.sorted_states();
+        let mut adjacency = vec![vec![0; states.len()]; states.len()];
+        for (src, _, dst) in &self.transitions {
+            adjacency[index[src]][index[dst]] += 1;
+        }
+        (states, adjacency)
+    }
+
+    // Like `adjacency_matrix`, but with a separate 0/1 transition matrix for each label. The
+    // states are numbered the same way.
+    pub fn symbol_matrices(&self) -> (Vec<S>, std::collections::HashMap<E, Vec<Vec<u64>>>) {
+        let (states, index) = self.sorted_states();
+        let mut matrices: HashMap<E, Vec<Vec<u64>>> = HashMap::default();
+        for (src, label, dst) in &self.transitions {
+            matrices
+                .entry(*label)
+                .or_insert_with(|| vec![vec![0; states.len()]; states.len()])[index[src]]
+                [index[dst]] = 1;
+        }
+        (states, matrices.into_std())
+    }
+
+    fn sorted_states(&self) -> (Vec<S>, HashMap<S, usize>) {
+        let mut states: Vec<S> = self.states().into_iter().collect();
+        states.sort_unstable();
+        let index = states.iter().enumerate().map(|(i, &q)| (q, i)).collect();
+        (states, index)
+    }
+}
+
+// Multiplies square matrices over the arithmetic where `mul_add(acc, x, y)` is `acc + x * y`.
+fn matrix_mul<T>(
+    a: &[Vec<T>],
+    b: &[Vec<T>],
+    zero: T,
+    mul_add: &impl Fn(T, T, T) -> T,
+) -> Vec<Vec<T>>
+where
+    T: Copy + PartialEq,
+{
+    let n = a.len();
+    let mut c = vec![vec![zero; n]; n];
+    for i in 0..n {
+        for k in 0..n {
+            if a[i][k] == zero {
+                continue;
+            }
+            for j in 0..n {
+                c[i][j] = mul_add(c[i][j], a[i][k], b[k][j]);
+            }
+        }
+    }
+    c
+}
+
+// Raises a square matrix to the `n`th power by repeated squaring.
+fn matrix_pow<T>(
+    a: &[Vec<T>],
+    mut n: u64,
+    zero: T,
+    one: T,
+    mul_add: &impl Fn(T, T, T) -> T,
+) -> Vec<Vec<T>>
+where
+    T: Copy + PartialEq,
+{
+    let size = a.len();
+    let mut result: Vec<Vec<T>> = (0..size)
+        .map(|i| (0..size).map(|j| if i == j { one } else { zero }).collect())
+        .collect();
+    let mut base = a.to_vec();
+    while n > 0 {
+        if n & 1 == 1 {
+            result = matrix_mul(&result, &base, zero, mul_add);
+        }
+        n >>= 1;
+        if n > 0 {
+            base = matrix_mul(&base, &base, zero, mul_add);
+        }
+    }
+    result
+}
+
+fn f64_mul_add(acc: f64, x: f64, y: f64) -> f64 {
+    acc + x * y
+}
+
+// Estimates the spectral radius of a non-negative matrix by power iteration. Iterating on
+// `a + I` instead of `a` shifts every eigenvalue by 1, which guarantees that the dominant one is
+// unique even if `a` is periodic.
+pub(crate) fn spectral_radius(a: &[Vec<f64>]) -> f64 {
+    const EPSILON: f64 = 1e-12;
+    let n = a.len();
+    if n == 0 {
+        return 0.0;
+    }
+    let mut x = vec![1.0 / n as f64; n];
+    let mut radius = 0.0;
+    for _ in 0..100_000 {
+        let mut y = x.clone();
+        for i in 0..n {
+            for j in 0..n {
+                y[j] += x[i] * a[i][j];
+            }
+        }
+        let norm: f64 = y.iter().sum();
+        let done = (norm - radius).abs() < EPSILON * norm;
+        radius = norm;
+        x = y.into_iter().map(|v| v / norm).collect();
+        if done {
+            break;
+        }
+    }
+    radius - 1.0
+}
+
+#[cfg(test)]
+mod test {
+    use crate::DFA;
+
+    #[test]
+    fn acceptance_probability_ends_in_one() {
+        // Accepts words over {0, 1} that end in a 1.
+        let input: DFA<u32, u8> =
+            DFA::new(0, vec![1], vec![(0, 0, 0), (0, 1, 1), (1, 0, 0), (1, 1, 1)]);
+        assert_eq!(input.acceptance_probability(0), 0.0);
+        assert!((input.acceptance_probability(5) - 0.5).abs() < 1e-12);
+        assert!((input.limiting_acceptance_probability().unwrap() - 0.5).abs() < 1e-9);
+    }
+
+    #[test]
+    fn acceptance_probability_partial() {
+        // Accepts 0*1, and every other word falls off the DFA.
+        let input: DFA<u32, u8> = DFA::new(0, vec![1], vec![(0, 0, 0), (0, 1, 1)]);
+        assert!((input.acceptance_probability(3) - 0.125).abs() < 1e-12);
+        assert!(input.limiting_acceptance_probability().unwrap().abs() < 1e-9);
+    }
+
+    #[test]
+    fn entropy_of_golden_mean_shift() {
+        // Binary words with no two consecutive 1s: the growth rate is the golden ratio.
+        let input: DFA<u32, u8> = DFA::new(0, vec![0, 1], vec![(0, 0, 0), (0, 1, 1), (1, 0, 0)]);
+        let golden: f64 = (1.0 + 5f64.sqrt()) / 2.0;
+        assert!((input.entropy() - golden.log2()).abs() < 1e-9);
+
+        // Every binary word: one bit per symbol, even with an extra dead state.
+        let input: DFA<u32, u8> = DFA::new(0, vec![0], vec![(0, 0, 0), (0, 1, 0), (0, 2, 1)]);
+        assert!((input.entropy() - 1.0).abs() < 1e-9);
+
+        // Periodic, but still one bit per symbol.
+        let input: DFA<u32, u8> =
+            DFA::new(0, vec![0], vec![(0, 0, 1), (0, 1, 1), (1, 0, 0), (1, 1, 0)]);
+        assert!((input.entropy() - 1.0).abs() < 1e-9);
+
+        let finite: DFA<u32, u8> = DFA::new(0, vec![1], vec![(0, 0, 1), (0, 1, 1)]);
+        assert_eq!(finite.entropy(), 0.0);
+    }
+
+    #[test]
+    fn adjacency_and_symbol_matrices() {
+        let input: DFA<u32, u8> = DFA::new(
+            5,
+            vec![7],
+            vec![(5, 0, 6), (5, 1, 6), (6, 0, 7), (7, 1, 5), (7, 0, 7)],
+        );
+        let (states, adjacency) = input.adjacency_matrix();
+        assert_eq!(states, vec![5, 6, 7]);
+        assert_eq!(adjacency, vec![vec![0, 2, 0], vec![0, 0, 1], vec![1, 0, 1]]);
+
+        let (states, matrices) = input.symbol_matrices();
+        assert_eq!(states, vec![5, 6, 7]);
+        assert_eq!(
+            matrices[&0],
+            vec![vec![0, 1, 0], vec![0, 0, 1], vec![0, 0, 1]]
+        );
+        assert_eq!(
+            matrices[&1],
+            vec![vec![0, 1, 0], vec![0, 0, 0], vec![1, 0, 0]]
+        );
+    }
+
+    #[test]
+    fn count_words_for_huge_lengths() {
+        // Accepts a*b*, which has n + 1 words of length n.
+        let input: DFA<u32, char> =
+            DFA::new(0, vec![0, 1], vec![(0, 'a', 0), (0, 'b', 1), (1, 'b', 1)]);
+        assert_eq!(input.count_words_at(1_000_000_000), 1_000_000_001);
+        assert_eq!(input.count_words_by_matrix(7), Some(8));
+
+        // Binary words with no two consecutive 1s are counted by the Fibonacci numbers.
+        let input: DFA<u32, u8> = DFA::new(
+            0,
+            vec![0, 1],
+            vec![(0, 0, 0), (0, 1, 1), (1, 0, 0), (1, 1, 2), (2, 0, 2)],
+        );
+        assert_eq!(input.count_words_at(10), 144);
+        assert_eq!(input.count_words_by_matrix(10), Some(144));
+        assert_eq!(input.count_words_by_matrix(1000), None);
+        // F(10^9 + 2) mod 998244353.
+        assert_eq!(
+            input.count_words_at_mod(1_000_000_000, 998_244_353),
+            849_739_281
+        );
+    }
+
+    #[test]
+    #[should_panic(expected = "the modulus must be positive")]
+    fn count_words_at_mod_zero() {
+        let input: DFA<u32, u8> = DFA::new(0, vec![0], vec![(0, 0, 0)]);
+        input.count_words_at_mod(3, 0);
+    }
+
+    #[test]
+    fn acceptance_probability_periodic() {
+        // Accepts words of even length.
+        let input: DFA<u32, u8> = DFA::new(0, vec![0], vec![(0, 0, 1), (1, 0, 0)]);
+        assert_eq!(input.acceptance_probability(1_000_000_000), 1.0);
+        assert_eq!(input.limiting_acceptance_probability(), None);
+    }
+}
@@ -0,0 +1,217 @@
+use std::collections::VecDeque;
+use std::fmt::Debug;
+use std::hash::Hash;
+
+use crate::hash::{HashMap, HashSet};
+use crate::refine;
+use crate::table::Table;
+use crate::MooreMachine;
+
+// A deterministic state machine that emits an output on each transition.
+#[derive(Debug, Clone)]
+pub struct MealyMachine<S, E, O> {
+    initial_state: S,
+    transitions: Table<S, E, S>,
+    // Transitions that are missing from this map emit nothing.
+    outputs: HashMap<(S, E), O>,
+}
+
+impl<S, E, O> MealyMachine<S, E, O>
+where
+    S: Eq + Hash + Copy + Debug,
+    E: Eq + Hash + Copy + Debug,
+    O: Eq + Hash + Clone + Debug,
+{
+    // Each transition is `(src, label, output, dst)`.
+    pub fn new(
+        initial_state: S,
+        transitions: impl IntoIterator<Item = (S, E, O, S)>,
+    ) -> MealyMachine<S, E, O> {
+        let mut outputs = HashMap::default();
+        let mut transitions: Table<S, E, S> = transitions
+            .into_iter()
+            .map(|(src, label, output, dst)| {
+                outputs.insert((src, label), output);
+                (src, label, dst)
+            })
+            .collect();
+        // Repeated transitions would otherwise be counted twice when minimizing.
+        transitions.dedup();
+        MealyMachine {
+            initial_state,
+            transitions,
+            outputs,
+        }
+    }
+
+    // Feeds `input` to the machine, and returns the outputs emitted along the way, or `None` if
+    // some transition is missing.
+    pub fn run(&self, input: &[E]) -> Option<Vec<O>> {
+        let delta: HashMap<(S, E), S> = (&self.transitions)
+            .into_iter()
+            .map(|&(src, label, dst)| ((src, label), dst))
+            .collect();
+        let mut q = self.initial_state;
+        let mut emitted = Vec::new();
+        for &label in input {
+            emitted.extend(self.outputs.get(&(q, label)).cloned());
+            q = *delta.get(&(q, label))?;
+        }
+        Some(emitted)
+    }
+
+    // Merges every pair of states that emit the same outputs on every input.
+    pub fn minimize(&self) -> MealyMachine<S, E, O> {
+        // Fold each transition's output into its label, so that `refine` tells them apart.
+        let mut ids: HashMap<Option<&O>, usize> = HashMap::default();
+        let labeled: Table<S, (E, usize), S> = (&self.transitions)
+            .into_iter()
+            .map(|&(src, label, dst)| {
+                let n = ids.len();
+                let id = *ids.entry(self.outputs.get(&(src, label))).or_insert(n);
+                (src, (label, id), dst)
+            })
+            .collect();
+
+        let mut states: HashSet<S> = HashSet::default();
+        states.insert(self.initial_state);
+        for &(src, _, dst) in &self.transitions {
+            states.insert(src);
+            states.insert(dst);
+        }
+        let blocks = refine(states.into_iter().collect(), &labeled.indexed(), |_| ());
+
+        let by_src = self.transitions.by_a();
+        let mut transitions = Vec::new();
+        let mut outputs = HashMap::default();
+        for i in 0..blocks.len() {
+            let src = blocks.canonical(i);
+            for &(label, dst) in by_src.get(&src).into_iter().flatten() {
+                transitions.push((src, label, blocks.canonical(blocks.owner(dst))));
+                if let Some(output) = self.outputs.get(&(src, label)) {
+                    outputs.insert((src, label), output.clone());
+                }
+            }
+        }
+        MealyMachine {
+            initial_state: blocks.canonical(blocks.owner(self.initial_state)),
+            transitions: Table::from(transitions),
+            outputs,
+        }
+    }
+
+    // Converts a Moore machine by emitting each state's output on the transitions into it. The
+    // Moore machine's initial output is lost, since a Mealy machine only emits on transitions.
+    pub fn from_moore(moore: &MooreMachine<S, E, O>) -> MealyMachine<S, E, O> {
+        let mut outputs = HashMap::default();
+        let transitions = moore
+            .transitions()
+            .map(|(src, label, dst)| {
+                if let Some(output) = moore.output(dst) {
+                    outputs.insert((src, label), output.clone());
+                }
+                (src, label, dst)
+            })
+            .collect();
+        MealyMachine {
+            initial_state: moore.initial_state(),
+            transitions,
+            outputs,
+        }
+    }
+
+    // Converts to a Moore machine whose states are pairs of a state and the output of the
+    // transition that entered it, numbered from 0 (the initial state, which has no output).
+    // Feeding both machines the same input produces the same outputs.
+    pub fn to_moore(&self) -> MooreMachine<u32, E, O> {
+        let by_src = self.transitions.by_a();
+
+        let mut ids: HashMap<(S, Option<&O>), u32> = HashMap::default();
+        let mut queue = VecDeque::new();
+        let mut outputs = Vec::new();
+        let mut transitions = Vec::new();
+        ids.insert((self.initial_state, None), 0);
+        queue.push_back((self.initial_state, None));
+        while let Some((q, output)) = queue.pop_front() {
+            let src = ids[&(q, output)];
+            if let Some(output) = output {
+                outputs.push((src, output.clone()));
+            }
+            for &(label, dst) in by_src.get(&q).into_iter().flatten() {
+                let next = (dst, self.outputs.get(&(q, label)));
+                let n = ids.len() as u32;
+                let id = *ids.entry(next).or_insert_with(|| {
+                    queue.push_back(next);
+                    n
+                });
+                transitions.push((src, label, id));
+            }
+        }
+        MooreMachine::new(0, outputs, transitions)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::MealyMachine;
+
+    // Emits, for each bit, whether it differs from the previous one. The starting states 0 and 1
+    // have the same behavior, as do 2 and 3.
+    fn edge_detector() -> MealyMachine<u32, u8, bool> {
+        MealyMachine::new(
+            0,
+            vec![
+                (0, 0, false, 1),
+                (0, 1, true, 2),
+                (1, 0, false, 1),
+                (1, 1, true, 3),
+                (2, 0, true, 1),
+                (2, 1, false, 3),
+                (3, 0, true, 0),
+                (3, 1, false, 2),
+            ],
+        )
+    }
+
+    #[test]
+    fn minimize_mealy() {
+        let machine = edge_detector();
+        let minimized = machine.minimize();
+        assert_eq!(minimized.transitions.len(), 4);
+        let input = [0, 1, 1, 0, 1, 0, 0, 0, 1];
+        assert_eq!(minimized.run(&input), machine.run(&input));
+        assert_eq!(
+            machine.run(&input),
+            Some(vec![
+                false, true, false, true, true, true, false, false, true
+            ])
+        );
+    }
+
+    #[test]
+    fn moore_roundtrip() {
+        let machine = edge_detector();
+        let moore = machine.to_moore();
+        let input = [0, 1, 1, 0, 1, 0, 0, 0, 1];
+        assert_eq!(moore.output(moore.initial_state()), None);
+        let emitted: Vec<bool> = (1..=input.len())
+            .map(|len| *moore.output(moore.run(&input[..len]).unwrap()).unwrap())
+            .collect();
+        assert_eq!(Some(emitted), machine.run(&input));
+
+        let back = MealyMachine::from_moore(&moore);
+        assert_eq!(back.run(&input), machine.run(&input));
+        assert_eq!(back.minimize().transitions.len(), 4);
+    }
+
+    #[test]
+    fn repeated_transitions() {
+        let machine: MealyMachine<u32, char, bool> = MealyMachine::new(
+            0,
+            vec![(0, 'a', true, 1), (0, 'a', true, 1), (1, 'a', false, 0)],
+        );
+        let minimized = machine.minimize();
+        assert_eq!(minimized.transitions.len(), 2);
+        assert_eq!(minimized.run(&['a', 'a']), Some(vec![true, false]));
+    }
+}
@@ -0,0 +1,185 @@
+// Runtime monitors: a DFA compiled into a small object that watches a stream of events and says,
+// after each one, whether the events so far can still end up accepted. The DFA describes the
+// allowed complete traces, e.g. a protocol's valid sessions, and the monitor judges their
+// prefixes, so that a violation is reported at the first event that makes acceptance impossible.
+
+use std::fmt::Debug;
+use std::hash::Hash;
+
+use crate::hash::{HashMap, HashSet};
+use crate::DFA;
+
+// What a `Monitor` concludes about the events it has seen so far.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Verdict {
+    // Some continuations are accepted and some are not.
+    StillOk,
+    // No continuation is accepted. This is final.
+    Violated,
+    // Every continuation over the DFA's labels is accepted. Only an event that the DFA has no
+    // label for can still lead to a violation.
+    Satisfied,
+}
+
+#[derive(Debug, Clone)]
+pub struct Monitor<E> {
+    delta: HashMap<(u32, E), u32>,
+    accepting: Vec<bool>,
+    verdicts: Vec<Verdict>,
+    // `None` once an event had no transition.
+    state: Option<u32>,
+}
+
+impl<E> Monitor<E>
+where
+    E: Eq + Hash + Copy + Debug,
+{
+    // Reads one event and judges the events so far. An event with no transition, including one
+    // the DFA has no label for, is a violation.
+    pub fn observe(&mut self, event: E) -> Verdict {
+        self.state = self
+            .state
+            .and_then(|q| self.delta.get(&(q, event)).copied());
+        self.verdict()
+    }
+
+    pub fn verdict(&self) -> Verdict {
+        self.state
+            .map_or(Verdict::Violated, |q| self.verdicts[q as usize])
+    }
+
+    // Whether the DFA accepts the events so far as a complete trace.
+    pub fn accepting(&self) -> bool {
+        self.state.is_some_and(|q| self.accepting[q as usize])
+    }
+
+    // Forgets every event, going back to the initial state.
+    pub fn reset(&mut self) {
+        self.state = Some(0);
+    }
+
+    // The number of states the monitor tracks.
+    pub fn len(&self) -> usize {
+        self.verdicts.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.verdicts.is_empty()
+    }
+}
+
+impl<S, E> DFA<S, E>
+where
+    S: Eq + Hash + Copy + Debug,
+    E: Eq + Hash + Copy + Debug,
+{
+    // Minimizes the DFA and compiles it into a `Monitor` for its prefixes.
+    pub fn into_monitor(self) -> Monitor<E> {
+        let alphabet: HashSet<E> = self.transitions().map(|(_, label, _)| label).collect();
+        let minimal = self.minimize();
+        // Number the reachable states from 0, the initial state.
+        let ids = minimal.reach_order();
+        let dfa: DFA<u32, E> = DFA::with_states(
+            0,
+            ids.values().map(|&i| i as u32),
+            minimal
+                .final_states
+                .iter()
+                .filter_map(|q| ids.get(q).map(|&i| i as u32)),
+            minimal
+                .transitions()
+                .filter(|(src, _, _)| ids.contains_key(src))
+                .map(|(src, label, dst)| (ids[&src] as u32, label, ids[&dst] as u32)),
+        );
+        let live = dfa.coaccessible();
+
+        // A state is satisfied when nothing it leads to rejects or is missing a transition. Work
+        // backwards from the states that do.
+        let outflows = dfa.transitions.by_a();
+        let inflows = dfa.transitions.by_c();
+        let mut unsure: Vec<u32> = dfa
+            .states()
+            .into_iter()
+            .filter(|q| {
+                !dfa.is_accepting(*q) || outflows.get(q).map_or(0, Vec::len) < alphabet.len()
+            })
+            .collect();
+        let mut unsatisfied: HashSet<u32> = HashSet::default();
+        while let Some(q) = unsure.pop() {
+            if unsatisfied.insert(q) {
+                unsure.extend(inflows.get(&q).into_iter().flatten().map(|&(src, _)| src));
+            }
+        }
+
+        let n = dfa.state_count();
+        let verdicts = (0..n as u32)
+            .map(|q| {
+                if !live.contains(&q) {
+                    Verdict::Violated
+                } else if unsatisfied.contains(&q) {
+                    Verdict::StillOk
+                } else {
+                    Verdict::Satisfied
+                }
+            })
+            .collect();
+        Monitor {
+            delta: dfa.delta(),
+            accepting: (0..n as u32).map(|q| dfa.is_accepting(q)).collect(),
+            verdicts,
+            state: Some(0),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::Verdict;
+    use crate::DFA;
+
+    #[test]
+    fn protocol_monitor() {
+        // Open, then any number of reads, then close; after closing, anything goes.
+        let protocol: DFA<u32, &str> = DFA::new(
+            0,
+            vec![2],
+            vec![
+                (0, "open", 1),
+                (1, "read", 1),
+                (1, "close", 2),
+                (2, "open", 2),
+                (2, "read", 2),
+                (2, "close", 2),
+            ],
+        );
+        let mut monitor = protocol.into_monitor();
+        assert_eq!(monitor.len(), 3);
+        assert_eq!(monitor.verdict(), Verdict::StillOk);
+        assert_eq!(monitor.observe("open"), Verdict::StillOk);
+        assert_eq!(monitor.observe("read"), Verdict::StillOk);
+        assert!(!monitor.accepting());
+        assert_eq!(monitor.observe("close"), Verdict::Satisfied);
+        assert!(monitor.accepting());
+        assert_eq!(monitor.observe("read"), Verdict::Satisfied);
+        assert_eq!(monitor.observe("write"), Verdict::Violated);
+
+        monitor.reset();
+        assert_eq!(monitor.observe("read"), Verdict::Violated);
+        assert_eq!(monitor.observe("open"), Verdict::Violated);
+    }
+
+    #[test]
+    fn dead_states_are_violations() {
+        // After "b", only a dead loop remains.
+        let dfa: DFA<u32, char> = DFA::new(
+            0,
+            vec![1],
+            vec![(0, 'a', 1), (0, 'b', 2), (2, 'a', 2), (2, 'b', 2)],
+        );
+        let mut monitor = dfa.into_monitor();
+        assert_eq!(monitor.observe('b'), Verdict::Violated);
+        monitor.reset();
+        assert_eq!(monitor.observe('a'), Verdict::StillOk);
+        assert!(monitor.accepting());
+    }
+}
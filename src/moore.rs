@@ -0,0 +1,156 @@
+use std::fmt::Debug;
+use std::hash::Hash;
+
+use crate::hash::{HashMap, HashSet};
+use crate::refine;
+use crate::table::Table;
+
+// A deterministic state machine that emits an output in each state. A DFA is the special case
+// where the output is whether the state is accepting.
+#[derive(Debug, Clone)]
+pub struct MooreMachine<S, E, O> {
+    initial_state: S,
+    outputs: HashMap<S, O>,
+    transitions: Table<S, E, S>,
+}
+
+impl<S, E, O> MooreMachine<S, E, O>
+where
+    S: Eq + Hash + Copy + Debug,
+    E: Eq + Hash + Copy + Debug,
+    O: Eq + Hash + Clone + Debug,
+{
+    // States that are missing from `outputs` emit nothing.
+    pub fn new(
+        initial_state: S,
+        outputs: impl IntoIterator<Item = (S, O)>,
+        transitions: impl IntoIterator<Item = (S, E, S)>,
+    ) -> MooreMachine<S, E, O> {
+        let mut transitions: Table<S, E, S> = transitions.into_iter().collect();
+        // Repeated transitions would otherwise be counted twice when minimizing.
+        transitions.dedup();
+        MooreMachine {
+            initial_state,
+            outputs: outputs.into_iter().collect(),
+            transitions,
+        }
+    }
+
+    pub fn initial_state(&self) -> S {
+        self.initial_state
+    }
+
+    pub fn output(&self, state: S) -> Option<&O> {
+        self.outputs.get(&state)
+    }
+
+    pub fn transitions(&self) -> impl Iterator<Item = (S, E, S)> + '_ {
+        (&self.transitions).into_iter().copied()
+    }
+
+    // Feeds `input` to the machine, and returns the state it ends up in, or `None` if some
+    // transition is missing.
+    pub fn run(&self, input: &[E]) -> Option<S> {
+        let delta: HashMap<(S, E), S> = (&self.transitions)
+            .into_iter()
+            .map(|&(src, label, dst)| ((src, label), dst))
+            .collect();
+        let mut q = self.initial_state;
+        for &label in input {
+            q = *delta.get(&(q, label))?;
+        }
+        Some(q)
+    }
+
+    // Merges every pair of states that have the same output and that, on every input, keep
+    // producing the same outputs. Unlike `DFA::minimize`, nothing is pruned: a missing
+    // transition is observably different from any transition.
+    pub fn minimize(&self) -> MooreMachine<S, E, O> {
+        let mut states: HashSet<S> = self.outputs.keys().copied().collect();
+        states.insert(self.initial_state);
+        for &(src, _, dst) in &self.transitions {
+            states.insert(src);
+            states.insert(dst);
+        }
+        let index = self.transitions.indexed();
+        let blocks = refine(states.into_iter().collect(), &index, |q| {
+            self.outputs.get(q)
+        });
+
+        let mut transitions = Vec::new();
+        let mut outputs = Vec::new();
+        for i in 0..blocks.len() {
+            let src = blocks.canonical(i);
+            if let Some(output) = self.outputs.get(&src) {
+                outputs.push((src, output.clone()));
+            }
+            for &(label, dst) in index.successors(&src) {
+                transitions.push((src, label, blocks.canonical(blocks.owner(dst))));
+            }
+        }
+        MooreMachine {
+            initial_state: blocks.canonical(blocks.owner(self.initial_state)),
+            outputs: outputs.into_iter().collect(),
+            transitions: Table::from(transitions),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::MooreMachine;
+
+    #[test]
+    fn minimize_by_output() {
+        // Tracks the running sum of the input bits mod 3, with every residue split in two.
+        let mut transitions = Vec::new();
+        for q in 0..6u32 {
+            for b in 0..2u32 {
+                transitions.push((q, b, ((q % 3 + b) % 3) + 3 * ((q + b) % 2)));
+            }
+        }
+        let outputs = (0..6u32).map(|q| (q, q % 3));
+        let machine: MooreMachine<u32, u32, u32> = MooreMachine::new(0, outputs, transitions);
+        let minimized = machine.minimize();
+        assert_eq!(minimized.transitions.len(), 6);
+        assert_eq!(minimized.outputs.len(), 3);
+
+        let input = [1, 1, 0, 1, 1, 1, 0];
+        for len in 0..=input.len() {
+            let expected = machine.output(machine.run(&input[..len]).unwrap());
+            let actual = minimized.output(minimized.run(&input[..len]).unwrap());
+            assert_eq!(expected, actual);
+        }
+    }
+
+    #[test]
+    fn minimize_keeps_distinct_outputs() {
+        let machine: MooreMachine<u32, char, &str> = MooreMachine::new(
+            0,
+            vec![(0, "idle"), (1, "busy"), (2, "idle")],
+            vec![(0, 'a', 1), (1, 'a', 2), (2, 'a', 1)],
+        );
+        let minimized = machine.minimize();
+        // States 0 and 2 have the same behavior.
+        assert_eq!(minimized.transitions.len(), 2);
+        assert_eq!(
+            minimized.output(minimized.run(&['a', 'a']).unwrap()),
+            Some(&"idle")
+        );
+    }
+
+    #[test]
+    fn repeated_transitions() {
+        let machine: MooreMachine<u32, char, &str> = MooreMachine::new(
+            0,
+            vec![(0, "even"), (1, "odd")],
+            vec![(0, 'a', 1), (0, 'a', 1), (1, 'a', 0)],
+        );
+        let minimized = machine.minimize();
+        assert_eq!(minimized.transitions.len(), 2);
+        assert_eq!(
+            minimized.output(minimized.run(&['a']).unwrap()),
+            Some(&"odd")
+        );
+    }
+}
@@ -0,0 +1,405 @@
+use std::collections::{hash_map::Entry, VecDeque};
+use std::fmt::Debug;
+use std::hash::Hash;
+
+use crate::hash::{HashMap, HashSet};
+use crate::DFA;
+
+// A pair of states, where `None` stands for a rejecting sink.
+type Pair<S> = (Option<S>, Option<S>);
+
+// The Myhill-Nerode classes of a DFA's language.
+struct NerodeClasses<S, E> {
+    // The class of every reachable state.
+    class: HashMap<S, usize>,
+    // A shortest word in each class.
+    access: Vec<Vec<E>>,
+    // The class of the words that run into a missing transition, if there are any.
+    stuck: Option<usize>,
+    // The class of the words after which nothing is accepted, if any reach a state.
+    empty: Option<usize>,
+}
+
+// The words accepted from a state onwards: `dfa` is rooted at one of `states`, which are all the
+// reachable states that accept exactly these words.
+#[derive(Debug, Clone)]
+pub struct Residual<S, E> {
+    pub states: Vec<S>,
+    pub dfa: DFA<S, E>,
+}
+
+impl<S, E> DFA<S, E>
+where
+    S: Eq + Hash + Copy + Debug,
+    E: Eq + Hash + Copy + Debug,
+{
+    // Computes the classic table-filling data for Myhill-Nerode equivalence: for every pair of
+    // distinguishable states, a shortest word accepted starting from exactly one of them. Pairs
+    // appear in both orders. Pairs that are missing are equivalent, and would be merged by
+    // `minimize`. A missing transition is treated as moving to a rejecting sink.
+    pub fn distinguishability_table(&self) -> std::collections::HashMap<(S, S), Vec<E>> {
+        let delta = self.delta();
+        let states: Vec<S> = self.states().into_iter().collect();
+        let alphabet: HashSet<E> = self.transitions.by_b().into_keys().collect();
+        let accepts = |q: Option<S>| q.is_some_and(|q| self.final_states.contains(&q));
+
+        let mut table: HashMap<Pair<S>, Vec<E>> = HashMap::default();
+        let all: Vec<Option<S>> = states.iter().copied().map(Some).chain(Some(None)).collect();
+        for &p in &all {
+            for &q in &all {
+                if accepts(p) != accepts(q) {
+                    table.insert((p, q), Vec::new());
+                }
+            }
+        }
+        // Round `k` finds every pair whose shortest distinguishing word has length `k`.
+        loop {
+            let mut found = Vec::new();
+            for &p in &all {
+                for &q in &all {
+                    if table.contains_key(&(p, q)) {
+                        continue;
+                    }
+                    for &label in &alphabet {
+                        let step = |r: Option<S>| r.and_then(|r| delta.get(&(r, label)).copied());
+                        if let Some(suffix) = table.get(&(step(p), step(q))) {
+                            let mut word = vec![label];
+                            word.extend(suffix);
+                            found.push(((p, q), word));
+                            break;
+                        }
+                    }
+                }
+            }
+            if found.is_empty() {
+                break;
+            }
+            table.extend(found);
+        }
+
+        table
+            .into_iter()
+            .filter_map(|((p, q), word)| Some(((p?, q?), word)))
+            .collect()
+    }
+
+    // Returns a shortest word that is accepted starting from exactly one of `s1` and `s2`, or
+    // `None` if the two states are equivalent. A missing transition is treated as moving to a
+    // rejecting sink.
+    pub fn distinguishing_word(&self, s1: S, s2: S) -> Option<Vec<E>> {
+        let delta = self.delta();
+        let alphabet: HashSet<E> = self.transitions.by_b().into_keys().collect();
+        let accepts = |q: Option<S>| q.is_some_and(|q| self.final_states.contains(&q));
+
+        let start = (Some(s1), Some(s2));
+        let mut parents: HashMap<Pair<S>, Option<(Pair<S>, E)>> = HashMap::default();
+        let mut queue = VecDeque::new();
+        parents.insert(start, None);
+        queue.push_back(start);
+        while let Some((p, q)) = queue.pop_front() {
+            if accepts(p) != accepts(q) {
+                let mut word = Vec::new();
+                let mut cur = (p, q);
+                while let Some((prev, label)) = parents[&cur] {
+                    word.push(label);
+                    cur = prev;
+                }
+                word.reverse();
+                return Some(word);
+            }
+            for &label in &alphabet {
+                let step = |r: Option<S>| r.and_then(|r| delta.get(&(r, label)).copied());
+                let next = (step(p), step(q));
+                if next.0.is_none() && next.1.is_none() {
+                    continue;
+                }
+                if let Entry::Vacant(e) = parents.entry(next) {
+                    e.insert(Some(((p, q), label)));
+                    queue.push_back(next);
+                }
+            }
+        }
+        None
+    }
+
+    // The residual languages of the reachable states, one for each distinct language, in the
+    // order a breadth-first search first reaches them. The first is the language itself.
+    pub fn residuals(&self) -> Vec<Residual<S, E>> {
+        let order = self.reach_order();
+        let reachable = DFA {
+            initial_state: self.initial_state,
+            states: order.keys().copied().collect(),
+            final_states: self
+                .final_states
+                .iter()
+                .copied()
+                .filter(|q| order.contains_key(q))
+                .collect(),
+            transitions: self
+                .transitions()
+                .filter(|(src, _, _)| order.contains_key(src))
+                .collect(),
+        };
+
+        let mut groups: HashMap<S, Vec<S>> = HashMap::default();
+        let (_, classes) = reachable.minimize_with_classes(|_| (), |set| set[0]);
+        for (q, rep) in classes {
+            groups.entry(rep).or_default().push(q);
+        }
+        let mut groups: Vec<Vec<S>> = groups.into_values().collect();
+        for states in &mut groups {
+            states.sort_by_key(|q| order[q]);
+        }
+        groups.sort_by_key(|states| order[&states[0]]);
+
+        groups
+            .into_iter()
+            .map(|states| {
+                let rooted = DFA {
+                    initial_state: states[0],
+                    ..reachable.clone()
+                };
+                let from_root = rooted.reachable();
+                let dfa = DFA {
+                    initial_state: states[0],
+                    states: from_root.clone(),
+                    final_states: from_root
+                        .intersection(&rooted.final_states)
+                        .copied()
+                        .collect(),
+                    transitions: rooted
+                        .transitions()
+                        .filter(|(src, _, _)| from_root.contains(src))
+                        .collect(),
+                };
+                Residual { states, dfa }
+            })
+            .collect()
+    }
+
+    // The number of Myhill-Nerode classes of the language, over the labels used by this DFA:
+    // one per distinct residual of a reachable state, plus one for the words that run into a
+    // missing transition, unless some reachable state already accepts nothing.
+    pub fn nerode_index(&self) -> usize {
+        self.nerode_classes().access.len()
+    }
+
+    // For each of `prefixes`, a shortest word in the same Myhill-Nerode class: one after which
+    // exactly the same suffixes are accepted.
+    pub fn nerode_representatives(&self, prefixes: &[Vec<E>]) -> Vec<Vec<E>> {
+        let classes = self.nerode_classes();
+        let delta = self.delta();
+        prefixes
+            .iter()
+            .map(|prefix| {
+                let (mut end, mut read) = (Some(self.initial_state), 0);
+                while let (Some(q), Some(label)) = (end, prefix.get(read)) {
+                    end = delta.get(&(q, *label)).copied();
+                    read += 1;
+                }
+                let class = match end {
+                    Some(q) => Some(classes.class[&q]),
+                    None => classes.stuck.or(classes.empty),
+                };
+                match class {
+                    Some(class) => classes.access[class].clone(),
+                    // Only a label this DFA doesn't use can run into a missing transition, and no
+                    // word over its own labels accepts nothing afterwards, so that label alone is
+                    // a shortest word in the class.
+                    None => vec![prefix[read - 1]],
+                }
+            })
+            .collect()
+    }
+
+    fn nerode_classes(&self) -> NerodeClasses<S, E> {
+        let residuals = self.residuals();
+        let mut class = HashMap::default();
+        for (i, residual) in residuals.iter().enumerate() {
+            for &q in &residual.states {
+                class.insert(q, i);
+            }
+        }
+
+        // Reach every state by a shortest word, as `reach_order` does.
+        let outflows = self.transitions.by_a();
+        let alphabet: HashSet<E> = self.transitions.by_b().into_keys().collect();
+        let mut words: HashMap<S, Vec<E>> = HashMap::default();
+        let mut queue = VecDeque::new();
+        let mut stuck_word: Option<Vec<E>> = None;
+        words.insert(self.initial_state, Vec::new());
+        queue.push_back(self.initial_state);
+        while let Some(src) = queue.pop_front() {
+            let edges = outflows.get(&src).map_or(&[][..], Vec::as_slice);
+            if stuck_word.is_none() && edges.len() < alphabet.len() {
+                let missing = alphabet
+                    .iter()
+                    .find(|&&e| edges.iter().all(|&(l, _)| l != e));
+                let mut word = words[&src].clone();
+                word.push(*missing.unwrap());
+                stuck_word = Some(word);
+            }
+            for &(label, dst) in edges {
+                if !words.contains_key(&dst) {
+                    let mut word = words[&src].clone();
+                    word.push(label);
+                    words.insert(dst, word);
+                    queue.push_back(dst);
+                }
+            }
+        }
+
+        // Each residual's states come in the order they are reached, so the first is closest.
+        let mut access: Vec<Vec<E>> = residuals
+            .iter()
+            .map(|residual| words[&residual.states[0]].clone())
+            .collect();
+        let empty = residuals
+            .iter()
+            .position(|residual| residual.dfa.final_states.is_empty());
+        let stuck = stuck_word.map(|word| match empty {
+            Some(i) => {
+                if word.len() < access[i].len() {
+                    access[i] = word;
+                }
+                i
+            }
+            None => {
+                access.push(word);
+                access.len() - 1
+            }
+        });
+        NerodeClasses {
+            class,
+            access,
+            stuck,
+            empty,
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::DFA;
+
+    #[test]
+    fn distinguishability_table_wikipedia() {
+        let input: DFA<u32, u8> = DFA::new(
+            0,
+            vec![2, 3, 4],
+            vec![
+                (0, 0, 1),
+                (0, 1, 2),
+                (1, 0, 0),
+                (1, 1, 3),
+                (2, 0, 4),
+                (2, 1, 5),
+                (3, 0, 4),
+                (3, 1, 5),
+                (4, 0, 4),
+                (4, 1, 5),
+                (5, 0, 5),
+                (5, 1, 5),
+            ],
+        );
+        let table = input.distinguishability_table();
+        // The minimal DFA merges {0, 1}, {2, 3, 4}, and {5}.
+        for &(p, q) in &[(0, 1), (2, 3), (2, 4), (3, 4)] {
+            assert!(!table.contains_key(&(p, q)));
+        }
+        assert_eq!(table[&(0, 2)], vec![]);
+        assert_eq!(table[&(5, 0)], vec![1]);
+        assert_eq!(table[&(0, 5)], vec![1]);
+        // Every pair of states in different classes is distinguishable, in both orders.
+        assert_eq!(table.len(), 2 * (2 * 3 + 2 + 3));
+    }
+
+    #[test]
+    fn distinguishing_word_is_shortest() {
+        // Accepts words over {0, 1} whose third-to-last symbol is a 1. States track the last
+        // three symbols read.
+        let mut transitions = Vec::new();
+        for q in 0..8u32 {
+            for b in 0..2u8 {
+                transitions.push((q, b, ((q << 1) | u32::from(b)) & 7));
+            }
+        }
+        let input: DFA<u32, u8> = DFA::new(0, vec![4, 5, 6, 7], transitions);
+        assert_eq!(input.distinguishing_word(0, 4), Some(vec![]));
+        assert_eq!(input.distinguishing_word(0, 1).map(|w| w.len()), Some(2));
+        assert_eq!(input.distinguishing_word(0, 2).map(|w| w.len()), Some(1));
+        assert_eq!(input.distinguishing_word(3, 3), None);
+
+        let table = input.distinguishability_table();
+        assert_eq!(table[&(0, 1)].len(), 2);
+    }
+
+    #[test]
+    fn residuals() {
+        // Words over {a, b} containing "ab". State 3 is unreachable, and 2 and 4 are equivalent.
+        let input: DFA<u32, char> = DFA::new(
+            0,
+            vec![2, 4],
+            vec![
+                (0, 'a', 1),
+                (0, 'b', 0),
+                (1, 'a', 1),
+                (1, 'b', 2),
+                (2, 'a', 4),
+                (2, 'b', 2),
+                (4, 'a', 4),
+                (4, 'b', 4),
+                (3, 'a', 0),
+            ],
+        );
+        let residuals = input.residuals();
+        assert_eq!(residuals.len(), 3);
+        assert_eq!(residuals[0].states, vec![0]);
+        assert_eq!(residuals[1].states, vec![1]);
+        assert_eq!(residuals[2].states, vec![2, 4]);
+        assert!(residuals[0].dfa.language_eq(&input));
+        assert!(residuals[1].dfa.accepts(&['b']));
+        assert!(residuals[2].dfa.accepts(&[]));
+        assert!(!residuals[1].dfa.is_accepting(3));
+    }
+
+    #[test]
+    fn nerode_index() {
+        // Accepts "ab" only. Besides the prefixes of "ab", everything else is one more class.
+        let input: DFA<u32, char> = DFA::new(0, vec![2], vec![(0, 'a', 1), (1, 'b', 2)]);
+        assert_eq!(input.nerode_index(), 4);
+        let prefixes: Vec<Vec<char>> = ["ab", "a", "ba", "abb", ""]
+            .iter()
+            .map(|w| w.chars().collect())
+            .collect();
+        let reps: Vec<String> = input
+            .nerode_representatives(&prefixes)
+            .into_iter()
+            .map(|w| w.into_iter().collect())
+            .collect();
+        assert_eq!(reps, vec!["ab", "a", "b", "b", ""]);
+
+        // A complete DFA with a reachable dead state has no extra class.
+        let complete: DFA<u32, char> = DFA::new(
+            0,
+            vec![1],
+            vec![
+                (0, 'a', 1),
+                (0, 'b', 2),
+                (1, 'a', 2),
+                (1, 'b', 2),
+                (2, 'a', 2),
+                (2, 'b', 2),
+            ],
+        );
+        assert_eq!(complete.nerode_index(), 3);
+
+        // Even-length words of a's, complete over {a}, so only a 'b' gets stuck.
+        let even: DFA<u32, char> = DFA::new(0, vec![0], vec![(0, 'a', 1), (1, 'a', 0)]);
+        let prefixes = vec![vec!['b'], vec!['a', 'b', 'a'], vec!['a']];
+        assert_eq!(
+            even.nerode_representatives(&prefixes),
+            vec![vec!['b'], vec!['b'], vec!['a']]
+        );
+    }
+}
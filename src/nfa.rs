@@ -0,0 +1,241 @@
+use std::collections::VecDeque;
+use std::fmt::Debug;
+use std::hash::Hash;
+
+use crate::hash::{HashMap, HashSet};
+use crate::table::Table;
+use crate::DFA;
+
+// A nondeterministic finite automaton. Transitions labeled `None` are epsilon transitions, which
+// can be taken without consuming any input. A run may begin in any of the initial states.
+#[derive(Debug, Clone)]
+pub struct NFA<S, E> {
+    pub(crate) initial_states: Vec<S>,
+    pub(crate) final_states: HashSet<S>,
+    pub(crate) transitions: Table<S, Option<E>, S>,
+}
+
+impl<S, E> NFA<S, E>
+where
+    S: Eq + Hash + Copy + Debug,
+    E: Eq + Hash + Copy + Debug,
+{
+    pub fn new(
+        initial_state: S,
+        final_states: impl IntoIterator<Item = S>,
+        transitions: impl IntoIterator<Item = (S, Option<E>, S)>,
+    ) -> NFA<S, E> {
+        NFA::with_initial_states(vec![initial_state], final_states, transitions)
+    }
+
+    pub fn with_initial_states(
+        initial_states: impl IntoIterator<Item = S>,
+        final_states: impl IntoIterator<Item = S>,
+        transitions: impl IntoIterator<Item = (S, Option<E>, S)>,
+    ) -> NFA<S, E> {
+        NFA {
+            initial_states: initial_states.into_iter().collect(),
+            final_states: final_states.into_iter().collect(),
+            transitions: transitions.into_iter().collect(),
+        }
+    }
+
+    // Every state mentioned by this NFA.
+    pub(crate) fn states(&self) -> HashSet<S> {
+        let mut states: HashSet<S> = self.final_states.clone();
+        states.extend(self.initial_states.iter().copied());
+        for &(src, _, dst) in &self.transitions {
+            states.insert(src);
+            states.insert(dst);
+        }
+        states
+    }
+
+    pub fn accepts(&self, word: &[E]) -> bool {
+        let outflows = self.transitions.by_a();
+        let mut current = closure(&outflows, self.initial_states.clone());
+        for &label in word {
+            let next = current
+                .iter()
+                .flat_map(|q| outflows.get(q).into_iter().flatten())
+                .filter(|&&(e, _)| e == Some(label))
+                .map(|&(_, dst)| dst)
+                .collect();
+            current = closure(&outflows, next);
+        }
+        current.iter().any(|q| self.final_states.contains(q))
+    }
+
+    // Builds an equivalent DFA with the subset construction. Only reachable subsets become
+    // states, numbered from 0 (the subset of initial states). The empty subset is left out, so the
+    // result is partial.
+    pub fn determinize(&self) -> DFA<u32, E> {
+        let outflows = self.transitions.by_a();
+
+        // Represent subsets as sorted lists of indices, so that they can be hashed.
+        let mut index: HashMap<S, usize> = HashMap::default();
+        let mut key = |states: HashSet<S>| -> Vec<usize> {
+            let mut k: Vec<usize> = states
+                .into_iter()
+                .map(|q| {
+                    let n = index.len();
+                    *index.entry(q).or_insert(n)
+                })
+                .collect();
+            k.sort_unstable();
+            k
+        };
+
+        let initial = closure(&outflows, self.initial_states.clone());
+        let mut ids: HashMap<Vec<usize>, u32> = HashMap::default();
+        let mut queue = VecDeque::new();
+        let mut final_states = Vec::new();
+        let mut transitions = Vec::new();
+        ids.insert(key(initial.clone()), 0);
+        queue.push_back(initial);
+        while let Some(subset) = queue.pop_front() {
+            let src = ids[&key(subset.clone())];
+            if subset.iter().any(|q| self.final_states.contains(q)) {
+                final_states.push(src);
+            }
+            let mut successors: HashMap<E, Vec<S>> = HashMap::default();
+            for q in &subset {
+                for &(label, dst) in outflows.get(q).into_iter().flatten() {
+                    if let Some(label) = label {
+                        successors.entry(label).or_default().push(dst);
+                    }
+                }
+            }
+            for (label, next) in successors {
+                let next = closure(&outflows, next);
+                let n = ids.len() as u32;
+                let dst = *ids.entry(key(next.clone())).or_insert_with(|| {
+                    queue.push_back(next);
+                    n
+                });
+                transitions.push((src, label, dst));
+            }
+        }
+        DFA::new(0, final_states, transitions)
+    }
+}
+
+impl<S, E> DFA<S, E>
+where
+    S: Eq + Hash + Copy + Debug,
+    E: Eq + Hash + Copy + Debug,
+{
+    // A DFA for the words accepted when starting from any of `initial_states`, in place of the
+    // usual initial state.
+    pub fn with_initial_states(&self, initial_states: impl IntoIterator<Item = S>) -> DFA<u32, E> {
+        let transitions = (&self.transitions)
+            .into_iter()
+            .map(|&(src, label, dst)| (src, Some(label), dst));
+        NFA::with_initial_states(
+            initial_states,
+            self.final_states.iter().copied(),
+            transitions,
+        )
+        .determinize()
+    }
+
+    // An NFA for the reversed language: every transition is flipped, and runs start from any
+    // accepting state.
+    pub fn reverse(&self) -> NFA<S, E> {
+        let transitions = (&self.transitions)
+            .into_iter()
+            .map(|&(src, label, dst)| (dst, Some(label), src));
+        NFA::with_initial_states(
+            self.final_states.iter().copied(),
+            Some(self.initial_state),
+            transitions,
+        )
+    }
+}
+
+// All states reachable from `start` by epsilon transitions alone.
+fn closure<S, E>(outflows: &HashMap<S, Vec<(Option<E>, S)>>, start: Vec<S>) -> HashSet<S>
+where
+    S: Eq + Hash + Copy,
+{
+    let mut seen = HashSet::default();
+    let mut stack = start;
+    while let Some(q) = stack.pop() {
+        if !seen.insert(q) {
+            continue;
+        }
+        for (label, dst) in outflows.get(&q).into_iter().flatten() {
+            if label.is_none() {
+                stack.push(*dst);
+            }
+        }
+    }
+    seen
+}
+
+#[cfg(test)]
+mod test {
+    use super::NFA;
+    use crate::DFA;
+
+    #[test]
+    fn determinize_third_from_last() {
+        // Accepts words over {0, 1} whose third-to-last symbol is a 1.
+        let nfa: NFA<u32, u8> = NFA::new(
+            0,
+            vec![3],
+            vec![
+                (0, Some(0), 0),
+                (0, Some(1), 0),
+                (0, Some(1), 1),
+                (1, Some(0), 2),
+                (1, Some(1), 2),
+                (2, Some(0), 3),
+                (2, Some(1), 3),
+            ],
+        );
+        assert!(nfa.accepts(&[0, 1, 0, 0]));
+        assert!(!nfa.accepts(&[1, 0, 0, 0]));
+
+        let dfa = nfa.determinize();
+        assert_eq!(dfa.states().len(), 8);
+        for word in dfa.words(Some(5)) {
+            assert!(nfa.accepts(&word));
+        }
+        assert_eq!(dfa.words(Some(5)).count(), 4 + 8 + 16);
+    }
+
+    #[test]
+    fn determinize_epsilon() {
+        // Accepts a*b* via an epsilon transition between the two loops.
+        let nfa: NFA<u32, char> = NFA::new(
+            0,
+            vec![1],
+            vec![(0, Some('a'), 0), (0, None, 1), (1, Some('b'), 1)],
+        );
+        assert!(nfa.accepts(&[]));
+        assert!(nfa.accepts(&['a', 'a', 'b']));
+        assert!(!nfa.accepts(&['b', 'a']));
+        let dfa = nfa.determinize();
+        assert_eq!(dfa.count_words_at(4), 5);
+    }
+
+    #[test]
+    fn multiple_initial_states() {
+        // Accepts "abc".
+        let dfa: DFA<u32, char> = DFA::new(0, vec![3], vec![(0, 'a', 1), (1, 'b', 2), (2, 'c', 3)]);
+        let suffixes = dfa.with_initial_states(vec![0, 1, 2, 3]);
+        assert_eq!(suffixes.words(None).count(), 4);
+        assert!(suffixes.words(None).any(|word| word == vec!['b', 'c']));
+
+        let reversed = dfa.reverse();
+        assert!(reversed.accepts(&['c', 'b', 'a']));
+        assert!(!reversed.accepts(&['a', 'b', 'c']));
+        let nfa: NFA<u32, char> = NFA::with_initial_states(
+            vec![0, 1],
+            vec![2],
+            vec![(0, Some('x'), 2), (1, Some('y'), 2)],
+        );
+        assert_eq!(nfa.determinize().count_words_at(1), 2);
+    }
+}
@@ -0,0 +1,181 @@
+// DFAs here may be partial: a state need not have a transition for every symbol, and a missing
+// transition rejects the rest of the input, as if it led to a rejecting sink. Operations that
+// only depend on the language (`accepts`, `product`, `minimize`, and so on) treat partial and
+// complete DFAs alike; in particular `minimize` merges a state with a missing transition and one
+// whose transition leads to a dead state. A few operations depend on the alphabet, which a
+// partial DFA doesn't determine, and those take it explicitly. `complement` is the main one, and
+// it requires a DFA that is complete over its alphabet.
+
+use std::fmt::{self, Debug, Display};
+use std::hash::Hash;
+
+use crate::hash::HashSet;
+use crate::DFA;
+
+// Returned when an operation needs a complete DFA: `state` has no transition for `label`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Incomplete<S, E> {
+    pub state: S,
+    pub label: E,
+}
+
+impl<S: Debug, E: Debug> Display for Incomplete<S, E> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "state {:?} has no transition for {:?}",
+            self.state, self.label
+        )
+    }
+}
+
+impl<S: Debug, E: Debug> std::error::Error for Incomplete<S, E> {}
+
+impl<S, E> DFA<S, E>
+where
+    S: Eq + Hash + Copy + Debug,
+    E: Eq + Hash + Copy + Debug,
+{
+    // Finds a state without a transition for some symbol of `alphabet`, if there is one.
+    pub fn missing_transition(&self, alphabet: &[E]) -> Option<Incomplete<S, E>> {
+        let present: HashSet<(S, E)> = self
+            .transitions()
+            .map(|(src, label, _)| (src, label))
+            .collect();
+        self.states().into_iter().find_map(|state| {
+            let label = alphabet
+                .iter()
+                .find(|&&label| !present.contains(&(state, label)))?;
+            Some(Incomplete {
+                state,
+                label: *label,
+            })
+        })
+    }
+
+    // Whether every state has a transition for every symbol of `alphabet`.
+    pub fn is_complete(&self, alphabet: &[E]) -> bool {
+        self.missing_transition(alphabet).is_none()
+    }
+
+    // Adds a rejecting `sink` state, and sends every missing transition over `alphabet` to it.
+    // Panics if `sink` is already a state.
+    pub fn complete(&self, alphabet: &[E], sink: S) -> DFA<S, E> {
+        let mut states = self.states();
+        assert!(states.insert(sink), "{:?} is already a state", sink);
+        let present: HashSet<(S, E)> = self
+            .transitions()
+            .map(|(src, label, _)| (src, label))
+            .collect();
+        let mut transitions: Vec<(S, E, S)> = self.transitions().collect();
+        for &state in &states {
+            for &label in alphabet {
+                if !present.contains(&(state, label)) {
+                    transitions.push((state, label, sink));
+                }
+            }
+        }
+        DFA::with_states(
+            self.initial_state,
+            states,
+            self.final_states.iter().copied(),
+            transitions,
+        )
+    }
+
+    // Accepts exactly the words over `alphabet` that this rejects. This needs the DFA to be
+    // complete over `alphabet`, since otherwise it would also have to accept the words that fall
+    // off a missing transition; use `complete` first for a partial DFA.
+    pub fn complement(&self, alphabet: &[E]) -> Result<DFA<S, E>, Incomplete<S, E>> {
+        if let Some(missing) = self.missing_transition(alphabet) {
+            return Err(missing);
+        }
+        Ok(DFA::with_states(
+            self.initial_state,
+            self.states.iter().copied(),
+            self.states.difference(&self.final_states).copied(),
+            self.transitions(),
+        ))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::Incomplete;
+    use crate::DFA;
+
+    #[test]
+    fn complement_needs_complete() {
+        // Accepts the words that start with 'a'; 0 has no transition for 'b'.
+        let partial: DFA<u32, char> =
+            DFA::new(0, vec![1], vec![(0, 'a', 1), (1, 'a', 1), (1, 'b', 1)]);
+        let alphabet = ['a', 'b'];
+        assert!(!partial.is_complete(&alphabet));
+        assert_eq!(
+            partial.complement(&alphabet),
+            Err(Incomplete {
+                state: 0,
+                label: 'b'
+            })
+        );
+
+        let complete = partial.complete(&alphabet, 9);
+        assert!(complete.is_complete(&alphabet));
+        assert!(complete.language_eq(&partial));
+        let complement = complete.complement(&alphabet).unwrap();
+        assert!(complement.accepts(&[]));
+        assert!(complement.accepts(&['b', 'a']));
+        assert!(!complement.accepts(&['a', 'b']));
+    }
+
+    #[test]
+    fn minimize_treats_missing_as_dead() {
+        // 1 has no transitions, and 2 only loops into a dead state; both accept "" only.
+        let input: DFA<u32, char> = DFA::new(
+            0,
+            vec![1, 2],
+            vec![(0, 'a', 1), (0, 'b', 2), (2, 'a', 3), (3, 'a', 3)],
+        );
+        let minimal = input.minimize_deterministic();
+        assert!(minimal.language_eq(&input));
+        assert_eq!(
+            minimal
+                .transitions()
+                .filter(|&(src, _, _)| src == 0)
+                .count(),
+            2
+        );
+        let targets: Vec<u32> = minimal
+            .transitions()
+            .filter(|&(src, _, _)| src == 0)
+            .map(|(_, _, dst)| dst)
+            .collect();
+        assert_eq!(targets[0], targets[1]);
+    }
+
+    #[test]
+    fn minimize_by_keeps_tagged_dead_states() {
+        // 1 and 2 are both dead, but only 2 is tagged, so "xb" and "yb" must end apart.
+        let input: DFA<u32, char> = DFA::new(
+            6,
+            vec![4],
+            vec![
+                (6, 'x', 0),
+                (6, 'y', 5),
+                (0, 'a', 4),
+                (0, 'b', 1),
+                (5, 'a', 4),
+                (5, 'b', 2),
+            ],
+        );
+        let tagged = input.minimize_by(|&q| q == 2);
+        let delta = tagged.delta();
+        let end = |word: &[char]| {
+            word.iter()
+                .fold(tagged.initial_state(), |q, &label| delta[&(q, label)])
+        };
+        assert_eq!(end(&['y', 'b']), 2);
+        assert_ne!(end(&['x', 'b']), 2);
+        assert_eq!(end(&['x', 'a']), end(&['y', 'a']));
+    }
+}
@@ -1,29 +1,150 @@
+// A partition of a fixed set of elements into disjoint sets, refined by splitting sets apart.
+// This is the structure behind DFA minimization, but it is useful anywhere a coarsest stable
+// partition is needed, such as congruence closure.
+//
+// Sets are numbered from 0 in order of creation, and a set keeps its number when it is split: the
+// smaller half becomes a new set. There are two ways to refine:
+//
+// - `refine_by` splits every set by a key computed for each element. This is the easier of the
+//   two to use correctly.
+// - `mark` some elements and then `split`: each set with marked elements is split into its
+//   marked and unmarked parts. Every `mark` must be followed by a `split` before the partition
+//   is otherwise used.
+//
+// Refinements can be undone: `checkpoint` starts recording changes, and `rollback` undoes
+// everything since the latest checkpoint, in time proportional to the amount of change.
+
 use std::fmt::Debug;
 use std::hash::Hash;
-use std::{collections::HashMap, ops::Range};
+use std::ops::Range;
+
+use crate::hash::HashMap;
+
+// Where a partition keeps a number for each element: its position, or the set that owns it.
+pub trait Slots<T> {
+    // Slots for `elements`, where the `i`th element gets `value(i)`.
+    fn filled(elements: &[T], value: impl Fn(usize) -> usize) -> Self;
+    fn get(&self, item: &T) -> usize;
+    fn set(&mut self, item: T, value: usize);
+    fn contains(&self, item: &T) -> bool;
+    fn remove(&mut self, item: &T);
+}
+
+impl<T> Slots<T> for HashMap<T, usize>
+where
+    T: Eq + Hash + Copy,
+{
+    fn filled(elements: &[T], value: impl Fn(usize) -> usize) -> Self {
+        elements
+            .iter()
+            .enumerate()
+            .map(|(i, &e)| (e, value(i)))
+            .collect()
+    }
+    fn get(&self, item: &T) -> usize {
+        self[item]
+    }
+    fn set(&mut self, item: T, value: usize) {
+        self.insert(item, value);
+    }
+    fn contains(&self, item: &T) -> bool {
+        self.contains_key(item)
+    }
+    fn remove(&mut self, item: &T) {
+        HashMap::remove(self, item);
+    }
+}
+
+// The slot of an index that is not an element.
+const ABSENT: usize = usize::MAX;
+
+// For elements that are small indices, slots can be a plain vector.
+impl Slots<usize> for Vec<usize> {
+    fn filled(elements: &[usize], value: impl Fn(usize) -> usize) -> Self {
+        let mut slots = vec![ABSENT; elements.iter().max().map_or(0, |&e| e + 1)];
+        for (i, &e) in elements.iter().enumerate() {
+            slots[e] = value(i);
+        }
+        slots
+    }
+    fn get(&self, item: &usize) -> usize {
+        self[*item]
+    }
+    fn set(&mut self, item: usize, value: usize) {
+        if item >= self.len() {
+            self.resize(item + 1, ABSENT);
+        }
+        self[item] = value;
+    }
+    fn contains(&self, item: &usize) -> bool {
+        self.as_slice()
+            .get(*item)
+            .is_some_and(|&slot| slot != ABSENT)
+    }
+    fn remove(&mut self, item: &usize) {
+        self[*item] = ABSENT;
+    }
+}
 
-type SetId = usize;
-pub struct Partition<T> {
+// The number of a set in a partition.
+pub type SetId = usize;
+
+#[derive(Debug, Clone)]
+pub struct Partition<T, M = HashMap<T, usize>> {
     elements: Vec<T>,
-    locations: HashMap<T, usize>,
-    owners: HashMap<T, SetId>,
+    locations: M,
+    owners: M,
     spans: Vec<Range<usize>>,
     marked: Vec<usize>,
     // List of sets
     touched: Vec<SetId>,
+    // While there are checkpoints, every change is recorded here so that it can be undone.
+    journal: Vec<Change<T>>,
+    // The length of the journal at each checkpoint.
+    checkpoints: Vec<usize>,
+}
+
+// A change to a partition, recorded with what it replaced.
+#[derive(Debug, Clone)]
+enum Change<T> {
+    Element(usize, T),
+    PushElement,
+    PopElement(T),
+    Location(T, Option<usize>),
+    Owner(T, Option<usize>),
+    Span(SetId, Range<usize>),
+    PushSpan,
 }
 
+// A partition of the indices `0..n`, which never hashes.
+pub type DensePartition = Partition<usize, Vec<usize>>;
+
 impl<T> Partition<T>
 where
     T: Eq + Hash + Copy + Debug,
 {
     pub fn new(elements: Vec<T>) -> Partition<T> {
-        let locations = elements
-            .iter()
-            .enumerate()
-            .map(|(idx, &e)| (e, idx))
-            .collect();
-        let owners = elements.iter().map(|&e| (e, 0)).collect();
+        Partition::with_slots(elements)
+    }
+}
+
+impl DensePartition {
+    // A single set of the given indices, which should be roughly `0..n`, since the slots take
+    // room for every index up to the largest.
+    pub fn dense(elements: Vec<usize>) -> DensePartition {
+        Partition::with_slots(elements)
+    }
+}
+
+impl<T, M> Partition<T, M>
+where
+    T: Eq + Copy + Debug,
+    M: Slots<T>,
+{
+    fn with_slots(elements: Vec<T>) -> Partition<T, M> {
+        let locations = M::filled(&elements, |i| i);
+        let owners = M::filled(&elements, |_| 0);
+        #[allow(clippy::single_range_in_vec_init)]
         let spans = vec![0..elements.len()];
         let marked = vec![0];
         let touched = vec![];
@@ -34,39 +155,281 @@ where
             spans,
             marked,
             touched,
+            journal: vec![],
+            checkpoints: vec![],
         }
     }
+    // The number of sets.
     pub fn len(&self) -> usize {
         self.spans.len()
     }
+    // Whether there are no elements. (A partition of no elements still has one, empty, set.)
+    pub fn is_empty(&self) -> bool {
+        self.elements.is_empty()
+    }
+    // The elements of a set, in no particular order.
     pub fn owned(&self, set_id: SetId) -> &[T] {
         let span = self.spans[set_id].clone();
         &self.elements[span]
     }
+    // The number of elements in a set.
+    pub fn size(&self, set_id: SetId) -> usize {
+        self.spans[set_id].len()
+    }
+    // Every set, in order of their numbers.
+    pub fn sets(&self) -> impl Iterator<Item = &[T]> + '_ {
+        (0..self.len()).map(move |i| self.owned(i))
+    }
+    pub fn contains(&self, item: T) -> bool {
+        self.locations.contains(&item)
+    }
+    // The set that contains `item`. Panics if `item` is not an element.
     pub fn owner(&self, item: T) -> SetId {
-        self.owners[&item]
+        self.owners.get(&item)
     }
+    // Some element of a set, which stays the same until the set is next split or changed. Panics
+    // if the set is empty.
     pub fn canonical(&self, set_id: SetId) -> T {
         self.elements[self.spans[set_id].start]
     }
+    // The set of every element, as of now.
+    pub fn snapshot(&self) -> std::collections::HashMap<T, SetId>
+    where
+        T: Hash,
+    {
+        self.sets()
+            .enumerate()
+            .flat_map(|(i, set)| set.iter().map(move |&e| (e, i)))
+            .collect()
+    }
+    // Splits every set so that elements end up together only if `key` gives them the same value.
+    // Each set is split into all of its groups at once; the largest group keeps the set's number.
+    // Panics if there are marks waiting for a `split`.
+    pub fn refine_by<K>(&mut self, key: impl Fn(&T) -> K)
+    where
+        K: Eq + Hash,
+    {
+        assert!(
+            self.touched.is_empty(),
+            "refine_by called between mark and split"
+        );
+        for set_id in 0..self.len() {
+            let span = self.spans[set_id].clone();
+            let mut groups: HashMap<K, Vec<T>> = HashMap::default();
+            for e in &self.elements[span.clone()] {
+                groups.entry(key(e)).or_default().push(*e);
+            }
+            if groups.len() < 2 {
+                continue;
+            }
+            let mut groups: Vec<Vec<T>> = groups.into_values().collect();
+            let largest = (0..groups.len()).max_by_key(|&i| groups[i].len()).unwrap();
+            groups.swap(0, largest);
+
+            let mut start = span.start;
+            for (i, group) in groups.into_iter().enumerate() {
+                let end = start + group.len();
+                let owner = if i == 0 {
+                    self.set_span(set_id, start..end);
+                    set_id
+                } else {
+                    self.push_span(start..end);
+                    self.spans.len() - 1
+                };
+                for (location, e) in (start..end).zip(group) {
+                    self.set_element(location, e);
+                    self.set_location(e, location);
+                    self.set_owner(e, owner);
+                }
+                start = end;
+            }
+        }
+    }
+    // Adds a new element to an existing set. This moves one element of each set that lies after
+    // it, so it takes time proportional to the number of sets in the worst case. Panics if `item`
+    // is already an element, or if there are marks waiting for a `split`.
+    pub fn insert(&mut self, item: T, set_id: SetId) {
+        assert!(
+            self.touched.is_empty(),
+            "insert called between mark and split"
+        );
+        assert!(!self.contains(item), "{:?} is already an element", item);
+        let mut hole = self.elements.len();
+        self.push_element(item);
+        if self.spans[set_id].is_empty() {
+            // An empty set can live anywhere, so it moves to the end.
+            self.set_span(set_id, hole..hole);
+        }
+        // Shift every set that lies after this one along by one, by moving its first element to
+        // its end, until the hole is just past the end of this set.
+        while self.spans[set_id].end != hole {
+            let t = self.owners.get(&self.elements[hole - 1]);
+            let Range { start, end } = self.spans[t];
+            let moved = self.elements[start];
+            self.set_element(end, moved);
+            self.set_location(moved, end);
+            self.set_span(t, start + 1..end + 1);
+            hole = start;
+        }
+        self.set_element(hole, item);
+        self.set_location(item, hole);
+        self.set_owner(item, set_id);
+        let span = self.spans[set_id].clone();
+        self.set_span(set_id, span.start..span.end + 1);
+    }
+    // Removes an element, leaving its set in place even if it becomes empty. Like `insert`, this
+    // takes time proportional to the number of sets in the worst case. Panics if `item` is not an
+    // element, or if there are marks waiting for a `split`.
+    pub fn remove(&mut self, item: T) {
+        assert!(
+            self.touched.is_empty(),
+            "remove called between mark and split"
+        );
+        let owner = self.owners.get(&item);
+        let i = self.locations.get(&item);
+        // Swap it to the end of its set, and take that position out of the set.
+        let mut hole = self.spans[owner].end - 1;
+        let last = self.elements[hole];
+        self.set_element(i, last);
+        self.set_element(hole, item);
+        self.set_location(last, i);
+        let span = self.spans[owner].clone();
+        if span.len() == 1 {
+            self.set_span(owner, 0..0);
+        } else {
+            self.set_span(owner, span.start..span.end - 1);
+        }
+        // Shift every set that lies after it back by one, by moving its last element to its
+        // start, until the hole is at the very end.
+        while hole + 1 < self.elements.len() {
+            let t = self.owners.get(&self.elements[hole + 1]);
+            let Range { start, end } = self.spans[t];
+            let moved = self.elements[end - 1];
+            self.set_element(hole, moved);
+            self.set_location(moved, hole);
+            self.set_span(t, start - 1..end - 1);
+            hole = end - 1;
+        }
+        self.pop_element();
+        self.remove_slots(item);
+    }
+    // Starts recording changes, so that `rollback` can undo them. Checkpoints nest. Panics if
+    // there are marks waiting for a `split`.
+    pub fn checkpoint(&mut self) {
+        assert!(
+            self.touched.is_empty(),
+            "checkpoint called between mark and split"
+        );
+        self.checkpoints.push(self.journal.len());
+    }
+    // Undoes every change since the latest checkpoint, and removes that checkpoint. Panics if
+    // there is no checkpoint, or if there are marks waiting for a `split`.
+    pub fn rollback(&mut self) {
+        assert!(
+            self.touched.is_empty(),
+            "rollback called between mark and split"
+        );
+        let start = self
+            .checkpoints
+            .pop()
+            .expect("rollback without a checkpoint");
+        while self.journal.len() > start {
+            match self.journal.pop().unwrap() {
+                Change::Element(i, e) => self.elements[i] = e,
+                Change::PushElement => {
+                    self.elements.pop();
+                }
+                Change::PopElement(e) => self.elements.push(e),
+                Change::Location(e, Some(i)) => self.locations.set(e, i),
+                Change::Location(e, None) => self.locations.remove(&e),
+                Change::Owner(e, Some(s)) => self.owners.set(e, s),
+                Change::Owner(e, None) => self.owners.remove(&e),
+                Change::Span(s, span) => self.spans[s] = span,
+                Change::PushSpan => {
+                    self.spans.pop();
+                    self.marked.pop();
+                }
+            }
+        }
+    }
+    // Keeps every change since the latest checkpoint, and removes that checkpoint. The changes
+    // can still be undone by rolling back to an earlier checkpoint.
+    pub fn commit(&mut self) {
+        self.checkpoints.pop().expect("commit without a checkpoint");
+        if self.checkpoints.is_empty() {
+            self.journal.clear();
+        }
+    }
+    fn record(&mut self, change: impl FnOnce(&Self) -> Change<T>) {
+        if !self.checkpoints.is_empty() {
+            let change = change(self);
+            self.journal.push(change);
+        }
+    }
+    fn set_element(&mut self, i: usize, e: T) {
+        self.record(|p| Change::Element(i, p.elements[i]));
+        self.elements[i] = e;
+    }
+    fn push_element(&mut self, e: T) {
+        self.record(|_| Change::PushElement);
+        self.elements.push(e);
+    }
+    fn pop_element(&mut self) {
+        self.record(|p| Change::PopElement(*p.elements.last().unwrap()));
+        self.elements.pop();
+    }
+    fn set_location(&mut self, e: T, i: usize) {
+        self.record(|p| Change::Location(e, p.location(e)));
+        self.locations.set(e, i);
+    }
+    fn set_owner(&mut self, e: T, s: SetId) {
+        self.record(|p| Change::Owner(e, p.owner_of(e)));
+        self.owners.set(e, s);
+    }
+    fn remove_slots(&mut self, e: T) {
+        self.record(|p| Change::Location(e, p.location(e)));
+        self.record(|p| Change::Owner(e, Some(p.owners.get(&e))));
+        self.locations.remove(&e);
+        self.owners.remove(&e);
+    }
+    fn location(&self, e: T) -> Option<usize> {
+        self.locations.contains(&e).then(|| self.locations.get(&e))
+    }
+    fn owner_of(&self, e: T) -> Option<SetId> {
+        self.owners.contains(&e).then(|| self.owners.get(&e))
+    }
+    fn set_span(&mut self, s: SetId, span: Range<usize>) {
+        self.record(|p| Change::Span(s, p.spans[s].clone()));
+        self.spans[s] = span;
+    }
+    fn push_span(&mut self, span: Range<usize>) {
+        self.record(|_| Change::PushSpan);
+        self.spans.push(span);
+        self.marked.push(0);
+    }
+    // Marks `item` to be split off from its set by the next `split`. Panics if `item` is already
+    // marked.
     pub fn mark(&mut self, item: T) {
-        let owner = self.owners[&item];
-        let i = self.locations[&item];
+        let owner = self.owners.get(&item);
+        let i = self.locations.get(&item);
         let j = self.spans[owner].start + self.marked[owner];
         assert!(i >= j, "{:?} was already marked", item);
 
         if i > j {
             // Swap this to the contiguous "marked" region of this set.
             let target = self.elements[j];
-            self.elements.swap(i, j);
-            self.locations.insert(item, j);
-            self.locations.insert(target, i);
+            self.set_element(i, target);
+            self.set_element(j, item);
+            self.set_location(item, j);
+            self.set_location(target, i);
         }
         if self.marked[owner] == 0 {
             self.touched.push(owner);
         }
         self.marked[owner] += 1;
     }
+    // Splits every set that has marked elements into its marked and unmarked parts, and clears
+    // the marks.
     pub fn split(&mut self) {
         while let Some(s) = self.touched.pop() {
             let Range { start, end } = self.spans[s];
@@ -77,18 +440,17 @@ where
                 continue;
             }
             let s1 = self.spans.len();
-            self.marked.push(0);
             if mid - start >= end - mid {
                 // the unmarked part is smaller, so that's the new set
-                self.spans.push(mid..end);
-                self.spans[s] = start..mid;
+                self.push_span(mid..end);
+                self.set_span(s, start..mid);
             } else {
                 // the marked part is smaller, so that's the new set
-                self.spans.push(start..mid);
-                self.spans[s] = mid..end;
+                self.push_span(start..mid);
+                self.set_span(s, mid..end);
             }
             for i in self.spans[s1].clone() {
-                self.owners.insert(self.elements[i], s1);
+                self.set_owner(self.elements[i], s1);
             }
         }
     }
@@ -96,7 +458,7 @@ where
 
 #[cfg(test)]
 mod test {
-    use super::Partition;
+    use super::{DensePartition, Partition};
 
     #[test]
     fn smoke_test() {
@@ -115,4 +477,127 @@ mod test {
         p.split();
         assert_eq!(p.len(), 4);
     }
+
+    #[test]
+    fn dense() {
+        let mut p = DensePartition::dense(vec![3, 0, 2, 1]);
+        p.mark(0);
+        p.mark(1);
+        p.split();
+        assert_eq!(p.len(), 2);
+        assert_eq!(p.owner(0), p.owner(1));
+        assert_ne!(p.owner(0), p.owner(3));
+        let mut owned = p.owned(p.owner(2)).to_vec();
+        owned.sort_unstable();
+        assert_eq!(owned, vec![2, 3]);
+    }
+
+    #[test]
+    fn refine_by_key() {
+        let mut p = Partition::new((0..12).collect());
+        p.refine_by(|n| n % 2);
+        assert_eq!(p.len(), 2);
+        p.refine_by(|n| n % 3);
+        assert_eq!(p.len(), 6);
+        assert!(p.sets().all(|set| set.len() == 2));
+        assert_eq!(p.size(p.owner(5)), 2);
+        let snapshot = p.snapshot();
+        assert_eq!(snapshot[&1], snapshot[&7]);
+        assert_ne!(snapshot[&1], snapshot[&4]);
+
+        // Marking still works on the rearranged sets.
+        p.mark(7);
+        p.split();
+        assert_eq!(p.len(), 7);
+        assert_eq!(p.owned(p.owner(7)), &[7]);
+        assert_eq!(p.owned(p.owner(1)), &[1]);
+    }
+
+    #[test]
+    #[should_panic(expected = "between mark and split")]
+    fn refine_by_with_pending_marks() {
+        let mut p = Partition::new(vec!['a', 'b', 'c']);
+        p.mark('a');
+        p.refine_by(|&c| c == 'b');
+    }
+
+    // Checks that every element is where the partition thinks it is.
+    fn check<T: Eq + std::hash::Hash + Copy + std::fmt::Debug>(p: &Partition<T>) {
+        let mut count = 0;
+        for (set_id, set) in p.sets().enumerate() {
+            count += set.len();
+            for &e in set {
+                assert_eq!(p.owner(e), set_id);
+                assert_eq!(p.elements[p.locations[&e]], e);
+            }
+        }
+        assert_eq!(count, p.elements.len());
+    }
+
+    #[test]
+    fn insert_and_remove() {
+        let mut p = Partition::new((0..6).collect());
+        p.refine_by(|n| n % 3);
+        let zeros = p.owner(0);
+        let ones = p.owner(1);
+
+        p.insert(6, zeros);
+        p.insert(7, ones);
+        check(&p);
+        assert_eq!(p.size(zeros), 3);
+        assert_eq!(p.owner(6), zeros);
+
+        p.remove(0);
+        p.remove(3);
+        p.remove(6);
+        check(&p);
+        assert!(!p.contains(3));
+        assert_eq!(p.size(zeros), 0);
+        assert_eq!(p.len(), 3);
+
+        p.insert(9, zeros);
+        p.insert(3, zeros);
+        check(&p);
+        p.refine_by(|&n| n > 5);
+        check(&p);
+        assert_eq!(p.len(), 5);
+        assert_ne!(p.owner(1), p.owner(7));
+
+        let mut d = DensePartition::dense(vec![0, 1]);
+        d.insert(5, 0);
+        d.remove(0);
+        assert!(d.contains(5) && !d.contains(0) && !d.contains(3));
+        assert_eq!(d.size(0), 2);
+    }
+
+    #[test]
+    fn checkpoint_and_rollback() {
+        let mut p = Partition::new((0..8).collect());
+        p.refine_by(|n| n % 2);
+        let before = p.snapshot();
+
+        p.checkpoint();
+        p.refine_by(|n| n % 4);
+        p.insert(8, p.owner(0));
+        p.remove(3);
+        p.checkpoint();
+        p.mark(1);
+        p.split();
+        assert_eq!(p.len(), 5);
+        p.rollback();
+        assert_eq!(p.len(), 4);
+        assert!(p.contains(8) && !p.contains(3));
+        check(&p);
+        p.rollback();
+        check(&p);
+        assert_eq!(p.snapshot(), before);
+        assert_eq!(p.len(), 2);
+
+        // Committed changes stay once the last checkpoint is gone.
+        p.checkpoint();
+        p.refine_by(|&n| n < 4);
+        p.commit();
+        assert_eq!(p.len(), 4);
+        assert!(p.journal.is_empty());
+    }
 }
@@ -0,0 +1,257 @@
+use std::collections::VecDeque;
+use std::fmt::{self, Debug, Display};
+use std::hash::Hash;
+
+use crate::hash::{HashMap, HashSet};
+use crate::refine;
+use crate::table::Table;
+
+// A discovered state of a product: its number, and the state and label that we came from.
+type Discovered<S, E> = (u32, Option<(S, E)>);
+
+// A DFA in which every transition carries a payload, such as the action a lexer runs on that
+// edge or a rule priority.
+#[derive(Debug, Clone)]
+pub struct PayloadDFA<S, E, P> {
+    initial_state: S,
+    final_states: HashSet<S>,
+    transitions: Table<S, E, S>,
+    payloads: HashMap<(S, E), P>,
+}
+
+// Returned by `PayloadDFA::intersection` when two transitions taken together carry payloads that
+// cannot be combined. `word` is a shortest input that ends on such a pair of transitions.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PayloadConflict<E> {
+    pub word: Vec<E>,
+}
+
+impl<E: Debug> Display for PayloadConflict<E> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "conflicting payloads after input {:?}", self.word)
+    }
+}
+
+impl<E: Debug> std::error::Error for PayloadConflict<E> {}
+
+impl<S, E, P> PayloadDFA<S, E, P>
+where
+    S: Eq + Hash + Copy + Debug,
+    E: Eq + Hash + Copy + Debug,
+    P: Eq + Hash + Clone + Debug,
+{
+    // Each transition is `(src, label, payload, dst)`.
+    pub fn new(
+        initial_state: S,
+        final_states: impl IntoIterator<Item = S>,
+        transitions: impl IntoIterator<Item = (S, E, P, S)>,
+    ) -> PayloadDFA<S, E, P> {
+        let mut payloads = HashMap::default();
+        let mut transitions: Table<S, E, S> = transitions
+            .into_iter()
+            .map(|(src, label, payload, dst)| {
+                payloads.insert((src, label), payload);
+                (src, label, dst)
+            })
+            .collect();
+        // Repeated transitions would otherwise be counted twice when minimizing.
+        transitions.dedup();
+        PayloadDFA {
+            initial_state,
+            final_states: final_states.into_iter().collect(),
+            transitions,
+            payloads,
+        }
+    }
+
+    // Feeds `input` to the automaton, and returns whether it was accepted along with the
+    // payloads of the transitions taken, or `None` if some transition is missing.
+    pub fn run(&self, input: &[E]) -> Option<(bool, Vec<&P>)> {
+        let delta: HashMap<(S, E), S> = (&self.transitions)
+            .into_iter()
+            .map(|&(src, label, dst)| ((src, label), dst))
+            .collect();
+        let mut q = self.initial_state;
+        let mut payloads = Vec::new();
+        for &label in input {
+            let dst = *delta.get(&(q, label))?;
+            payloads.push(self.payloads.get(&(q, label))?);
+            q = dst;
+        }
+        Some((self.final_states.contains(&q), payloads))
+    }
+
+    // Merges every pair of states that accept the same words and produce the same payloads on
+    // every input. States are never merged if that would change a payload.
+    pub fn minimize(&self) -> PayloadDFA<S, E, P> {
+        // Fold each transition's payload into its label, so that `refine` tells them apart.
+        let mut ids: HashMap<&P, usize> = HashMap::default();
+        let labeled: Table<S, (E, usize), S> = (&self.transitions)
+            .into_iter()
+            .map(|&(src, label, dst)| {
+                let n = ids.len();
+                let id = *ids.entry(&self.payloads[&(src, label)]).or_insert(n);
+                (src, (label, id), dst)
+            })
+            .collect();
+
+        let mut states: HashSet<S> = self.final_states.clone();
+        states.insert(self.initial_state);
+        for &(src, _, dst) in &self.transitions {
+            states.insert(src);
+            states.insert(dst);
+        }
+        let blocks = refine(states.into_iter().collect(), &labeled.indexed(), |q| {
+            self.final_states.contains(q)
+        });
+
+        let by_src = self.transitions.by_a();
+        let mut transitions = Vec::new();
+        for i in 0..blocks.len() {
+            let src = blocks.canonical(i);
+            for &(label, dst) in by_src.get(&src).into_iter().flatten() {
+                let payload = self.payloads[&(src, label)].clone();
+                transitions.push((src, label, payload, blocks.canonical(blocks.owner(dst))));
+            }
+        }
+        PayloadDFA::new(
+            blocks.canonical(blocks.owner(self.initial_state)),
+            self.final_states
+                .iter()
+                .map(|&q| blocks.canonical(blocks.owner(q))),
+            transitions,
+        )
+    }
+
+    // The intersection of two automata, with states numbered from 0 (the initial state). Each
+    // transition's payload is `combine` applied to the payloads of the two transitions it pairs
+    // up. If `combine` returns `None` for any reachable pair, the whole intersection fails.
+    pub fn intersection<S2, P2, P3>(
+        &self,
+        other: &PayloadDFA<S2, E, P2>,
+        combine: impl Fn(&P, &P2) -> Option<P3>,
+    ) -> Result<PayloadDFA<u32, E, P3>, PayloadConflict<E>>
+    where
+        S2: Eq + Hash + Copy + Debug,
+        P2: Eq + Hash + Clone + Debug,
+        P3: Eq + Hash + Clone + Debug,
+    {
+        let lhs = self.transitions.by_a();
+        let rhs: HashMap<(S2, E), S2> = (&other.transitions)
+            .into_iter()
+            .map(|&(src, label, dst)| ((src, label), dst))
+            .collect();
+
+        let start = (self.initial_state, other.initial_state);
+        let mut parents: HashMap<(S, S2), Discovered<(S, S2), E>> = HashMap::default();
+        let mut queue = VecDeque::new();
+        let mut final_states = Vec::new();
+        let mut transitions = Vec::new();
+        parents.insert(start, (0, None));
+        queue.push_back(start);
+        while let Some((p, q)) = queue.pop_front() {
+            let src = parents[&(p, q)].0;
+            if self.final_states.contains(&p) && other.final_states.contains(&q) {
+                final_states.push(src);
+            }
+            for &(label, p2) in lhs.get(&p).into_iter().flatten() {
+                let q2 = match rhs.get(&(q, label)) {
+                    Some(&q2) => q2,
+                    None => continue,
+                };
+                let combined = combine(&self.payloads[&(p, label)], &other.payloads[&(q, label)]);
+                let payload = match combined {
+                    Some(payload) => payload,
+                    None => {
+                        let mut word = vec![label];
+                        let mut cur = (p, q);
+                        while let (_, Some((prev, label))) = parents[&cur] {
+                            word.push(label);
+                            cur = prev;
+                        }
+                        word.reverse();
+                        return Err(PayloadConflict { word });
+                    }
+                };
+                let n = parents.len() as u32;
+                let dst = parents
+                    .entry((p2, q2))
+                    .or_insert_with(|| {
+                        queue.push_back((p2, q2));
+                        (n, Some(((p, q), label)))
+                    })
+                    .0;
+                transitions.push((src, label, payload, dst));
+            }
+        }
+        Ok(PayloadDFA::new(0, final_states, transitions))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::{PayloadConflict, PayloadDFA};
+
+    #[test]
+    fn minimize_respects_payloads() {
+        // Two branches accepting "ax" and "bx", whose final edges run different actions.
+        let dfa: PayloadDFA<u32, char, &str> = PayloadDFA::new(
+            0,
+            vec![3, 4],
+            vec![
+                (0, 'a', "skip", 1),
+                (0, 'b', "skip", 2),
+                (1, 'x', "emit", 3),
+                (2, 'x', "emit", 4),
+            ],
+        );
+        assert_eq!(dfa.minimize().transitions.len(), 3);
+
+        let different: PayloadDFA<u32, char, &str> = PayloadDFA::new(
+            0,
+            vec![3, 4],
+            vec![
+                (0, 'a', "skip", 1),
+                (0, 'b', "skip", 2),
+                (1, 'x', "emit", 3),
+                (2, 'x', "drop", 4),
+            ],
+        );
+        let minimal = different.minimize();
+        assert_eq!(minimal.transitions.len(), 4);
+        assert_eq!(
+            minimal.run(&['b', 'x']),
+            Some((true, vec![&"skip", &"drop"]))
+        );
+        assert_eq!(minimal.run(&['a', 'a']), None);
+    }
+
+    #[test]
+    fn intersection_combines_or_rejects() {
+        // Lexer-style priorities: the lower number wins.
+        let ident: PayloadDFA<u32, char, u32> =
+            PayloadDFA::new(0, vec![1], vec![(0, 'i', 2, 1), (1, 'f', 2, 1)]);
+        let keyword: PayloadDFA<u32, char, u32> =
+            PayloadDFA::new(0, vec![2], vec![(0, 'i', 1, 1), (1, 'f', 1, 2)]);
+        let both = ident
+            .intersection(&keyword, |a, b| Some(*a.min(b)))
+            .unwrap();
+        assert_eq!(both.run(&['i', 'f']), Some((true, vec![&1, &1])));
+
+        // Refusing to combine different priorities reports where they first meet.
+        let strict = ident.intersection(&keyword, |a, b| if a == b { Some(*a) } else { None });
+        assert_eq!(strict.unwrap_err(), PayloadConflict { word: vec!['i'] });
+    }
+
+    #[test]
+    fn repeated_transitions() {
+        let dfa: PayloadDFA<u32, char, &str> = PayloadDFA::new(
+            0,
+            vec![1],
+            vec![(0, 'a', "x", 1), (0, 'a', "x", 1), (1, 'a', "x", 1)],
+        );
+        let minimal = dfa.minimize();
+        assert_eq!(minimal.transitions.len(), 2);
+        assert_eq!(minimal.run(&['a', 'a']), Some((true, vec![&"x", &"x"])));
+    }
+}
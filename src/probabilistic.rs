@@ -0,0 +1,174 @@
+use std::fmt::Debug;
+use std::hash::Hash;
+
+use crate::hash::{HashMap, HashSet, IntoStd};
+
+// A generative probabilistic automaton. In each state, it either stops (with that state's stop
+// probability), or takes one of its outgoing transitions (with that transition's probability),
+// emitting the transition's label. A state's probabilities should sum to 1. Several transitions
+// may share a source and label, so the same word can be generated along several paths.
+#[derive(Debug, Clone)]
+pub struct ProbabilisticAutomaton<S, E> {
+    initial_state: S,
+    stop: HashMap<S, f64>,
+    // Each transition is `(src, label, probability, dst)`.
+    transitions: Vec<(S, E, f64, S)>,
+}
+
+impl<S, E> ProbabilisticAutomaton<S, E>
+where
+    S: Eq + Hash + Copy + Debug,
+    E: Eq + Hash + Copy + Debug,
+{
+    pub fn new(
+        initial_state: S,
+        stop: impl IntoIterator<Item = (S, f64)>,
+        transitions: impl IntoIterator<Item = (S, E, f64, S)>,
+    ) -> ProbabilisticAutomaton<S, E> {
+        ProbabilisticAutomaton {
+            initial_state,
+            stop: stop.into_iter().collect(),
+            transitions: transitions.into_iter().collect(),
+        }
+    }
+
+    // The probability of generating exactly `word` and then stopping.
+    pub fn probability(&self, word: &[E]) -> f64 {
+        self.log_probability(word).exp()
+    }
+
+    // The natural logarithm of `probability(word)`, computed without underflowing on long words.
+    // Impossible words have a log-probability of negative infinity.
+    pub fn log_probability(&self, word: &[E]) -> f64 {
+        let mut outflows: HashMap<(S, E), Vec<(f64, S)>> = HashMap::default();
+        for &(src, label, p, dst) in &self.transitions {
+            outflows.entry((src, label)).or_default().push((p, dst));
+        }
+
+        // The forward algorithm, rescaling the distribution after every step.
+        let mut log_scale = 0.0;
+        let mut forward: HashMap<S, f64> = HashMap::default();
+        forward.insert(self.initial_state, 1.0);
+        for &label in word {
+            let mut next: HashMap<S, f64> = HashMap::default();
+            for (&q, &mass) in &forward {
+                for &(p, dst) in outflows.get(&(q, label)).into_iter().flatten() {
+                    *next.entry(dst).or_insert(0.0) += mass * p;
+                }
+            }
+            let total: f64 = next.values().sum();
+            if total == 0.0 {
+                return f64::NEG_INFINITY;
+            }
+            log_scale += total.ln();
+            forward = next
+                .into_iter()
+                .map(|(q, mass)| (q, mass / total))
+                .collect();
+        }
+        let stopped: f64 = forward
+            .iter()
+            .map(|(q, mass)| mass * self.stop.get(q).copied().unwrap_or(0.0))
+            .sum();
+        log_scale + stopped.ln()
+    }
+
+    // The long-run fraction of time spent in each state, treating each stop as a restart from
+    // the initial state. Computed by power iteration, to within about 1e-12.
+    pub fn stationary_distribution(&self) -> std::collections::HashMap<S, f64> {
+        const EPSILON: f64 = 1e-12;
+        let mut states: HashSet<S> = self.stop.keys().copied().collect();
+        states.insert(self.initial_state);
+        for &(src, _, _, dst) in &self.transitions {
+            states.insert(src);
+            states.insert(dst);
+        }
+
+        let mut moves: HashMap<S, Vec<(f64, S)>> = HashMap::default();
+        for &(src, _, p, dst) in &self.transitions {
+            moves.entry(src).or_default().push((p, dst));
+        }
+        for (&q, &p) in &self.stop {
+            moves.entry(q).or_default().push((p, self.initial_state));
+        }
+
+        let mut dist: HashMap<S, f64> = states
+            .iter()
+            .map(|&q| (q, 1.0 / states.len() as f64))
+            .collect();
+        for _ in 0..1_000_000 {
+            // Staying put half of the time doesn't change the stationary distribution, but it
+            // does make the chain aperiodic, so that the iteration converges.
+            let mut next: HashMap<S, f64> =
+                dist.iter().map(|(&q, &mass)| (q, mass / 2.0)).collect();
+            for (q, &mass) in &dist {
+                for &(p, dst) in moves.get(q).into_iter().flatten() {
+                    *next.get_mut(&dst).unwrap() += mass * p / 2.0;
+                }
+            }
+            let total: f64 = next.values().sum();
+            let delta = next
+                .iter()
+                .map(|(q, &mass)| (mass / total - dist[q]).abs())
+                .fold(0.0, f64::max);
+            dist = next
+                .into_iter()
+                .map(|(q, mass)| (q, mass / total))
+                .collect();
+            if delta < EPSILON {
+                break;
+            }
+        }
+        dist.into_std()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::ProbabilisticAutomaton;
+
+    #[test]
+    fn word_probabilities() {
+        // A fair coin, flipped until it stops with probability 1/2 after each flip. Both flips
+        // from state 0 can lead to either state.
+        let pa: ProbabilisticAutomaton<u32, char> = ProbabilisticAutomaton::new(
+            0,
+            vec![(0, 0.5), (1, 0.5)],
+            vec![
+                (0, 'h', 0.125, 0),
+                (0, 'h', 0.125, 1),
+                (0, 't', 0.25, 1),
+                (1, 'h', 0.25, 0),
+                (1, 't', 0.25, 1),
+            ],
+        );
+        assert!((pa.probability(&[]) - 0.5).abs() < 1e-12);
+        assert!((pa.probability(&['h']) - 0.125).abs() < 1e-12);
+        assert!((pa.probability(&['t', 't']) - 0.03125).abs() < 1e-12);
+        assert_eq!(pa.probability(&['x']), 0.0);
+
+        let long: Vec<char> = "ht".repeat(1000).chars().collect();
+        let expected = 2000.0 * 0.25f64.ln() + 0.5f64.ln();
+        assert!((pa.log_probability(&long) - expected).abs() < 1e-9);
+    }
+
+    #[test]
+    fn stationary_distribution_of_periodic_chain() {
+        // Deterministically alternates between two states, and never stops.
+        let pa: ProbabilisticAutomaton<u32, char> =
+            ProbabilisticAutomaton::new(0, vec![], vec![(0, 'a', 1.0, 1), (1, 'b', 1.0, 0)]);
+        let dist = pa.stationary_distribution();
+        assert!((dist[&0] - 0.5).abs() < 1e-9);
+        assert!((dist[&1] - 0.5).abs() < 1e-9);
+
+        // Stops (and so restarts) from state 1 a quarter of the time.
+        let pa: ProbabilisticAutomaton<u32, char> = ProbabilisticAutomaton::new(
+            0,
+            vec![(1, 0.25)],
+            vec![(0, 'a', 1.0, 1), (1, 'b', 0.75, 1)],
+        );
+        let dist = pa.stationary_distribution();
+        assert!((dist[&0] - 0.2).abs() < 1e-9);
+        assert!((dist[&1] - 0.8).abs() < 1e-9);
+    }
+}
@@ -0,0 +1,173 @@
+use std::fmt::Debug;
+use std::hash::Hash;
+
+use crate::hash::{HashMap, HashSet};
+use crate::table::Table;
+use crate::DFA;
+
+// A pair of states of two DFAs, where `None` stands for a rejecting sink.
+pub(crate) type Pair<S, S2> = (Option<S>, Option<S2>);
+
+// The transitions of `dfa` on every label of `alphabet`, where each missing one leads to `None`,
+// a rejecting sink that stays put. Every pair of states of two such tables moves on every label.
+fn complete<S, E>(dfa: &DFA<S, E>, alphabet: &[E]) -> Table<Option<S>, E, Option<S>>
+where
+    S: Eq + Hash + Copy + Debug,
+    E: Eq + Hash + Copy + Debug,
+{
+    let delta = dfa.delta();
+    let mut transitions = Vec::new();
+    for p in dfa.states().into_iter().map(Some).chain(Some(None)) {
+        for &label in alphabet {
+            let dst = p.and_then(|p| delta.get(&(p, label)).copied());
+            transitions.push((p, label, dst));
+        }
+    }
+    transitions.into()
+}
+
+impl<S, E> DFA<S, E>
+where
+    S: Eq + Hash + Copy + Debug,
+    E: Eq + Hash + Copy + Debug,
+{
+    // Runs both DFAs side by side, accepting a word when `accept` says so given whether each DFA
+    // accepts it. A missing transition counts as moving to a rejecting sink, so that words one
+    // DFA has never heard of are simply rejected by it. Only pairs of states reachable on the
+    // same input are built, and they are numbered from 0 (the initial pair). Words with a symbol
+    // that neither DFA uses are always rejected.
+    pub fn product<S2>(
+        &self,
+        other: &DFA<S2, E>,
+        accept: impl Fn(bool, bool) -> bool,
+    ) -> DFA<u32, E>
+    where
+        S2: Eq + Hash + Copy + Debug,
+    {
+        self.product_pairs(other, accept).0
+    }
+
+    // Builds `product`, and also returns the pair of states behind each of its states.
+    pub(crate) fn product_pairs<S2>(
+        &self,
+        other: &DFA<S2, E>,
+        accept: impl Fn(bool, bool) -> bool,
+    ) -> (DFA<u32, E>, Vec<Pair<S, S2>>)
+    where
+        S2: Eq + Hash + Copy + Debug,
+    {
+        let alphabet: Vec<E> = self
+            .transitions
+            .by_b()
+            .into_keys()
+            .chain(other.transitions.by_b().into_keys())
+            .collect::<HashSet<E>>()
+            .into_iter()
+            .collect();
+        let start = (Some(self.initial_state), Some(other.initial_state));
+        let (lhs, rhs) = (complete(self, &alphabet), complete(other, &alphabet));
+        let joined = lhs.join_b_from(&rhs, start, |p, q| (p, q));
+
+        let mut ids: HashMap<Pair<S, S2>, u32> = HashMap::default();
+        let mut pairs = vec![start];
+        ids.insert(start, 0);
+        let mut transitions = Vec::new();
+        for (src, label, dst) in joined {
+            // Once both sides are stuck, only a rejecting sink is left, which isn't worth
+            // building unless `accept` accepts there.
+            if dst == (None, None) && !accept(false, false) {
+                continue;
+            }
+            let n = ids.len() as u32;
+            let dst = *ids.entry(dst).or_insert_with(|| {
+                pairs.push(dst);
+                n
+            });
+            transitions.push((ids[&src], label, dst));
+        }
+        let final_states: Vec<u32> = pairs
+            .iter()
+            .enumerate()
+            .filter_map(|(i, &(p, q))| {
+                let accepts_p = p.is_some_and(|p| self.final_states.contains(&p));
+                let accepts_q = q.is_some_and(|q| other.final_states.contains(&q));
+                accept(accepts_p, accepts_q).then_some(i as u32)
+            })
+            .collect();
+        (DFA::new(0, final_states, transitions), pairs)
+    }
+
+    pub fn union<S2>(&self, other: &DFA<S2, E>) -> DFA<u32, E>
+    where
+        S2: Eq + Hash + Copy + Debug,
+    {
+        self.product(other, |a, b| a || b)
+    }
+
+    pub fn intersection<S2>(&self, other: &DFA<S2, E>) -> DFA<u32, E>
+    where
+        S2: Eq + Hash + Copy + Debug,
+    {
+        self.product(other, |a, b| a && b)
+    }
+
+    // Accepts the words that `self` accepts and `other` does not.
+    pub fn difference<S2>(&self, other: &DFA<S2, E>) -> DFA<u32, E>
+    where
+        S2: Eq + Hash + Copy + Debug,
+    {
+        self.product(other, |a, b| a && !b)
+    }
+
+    // Accepts the words that exactly one of `self` and `other` accepts.
+    pub fn symmetric_difference<S2>(&self, other: &DFA<S2, E>) -> DFA<u32, E>
+    where
+        S2: Eq + Hash + Copy + Debug,
+    {
+        self.product(other, |a, b| a != b)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::DFA;
+
+    #[test]
+    fn boolean_combinations() {
+        // Words of a's and b's that start with an a, and words that end with a b.
+        let starts_a: DFA<u32, char> =
+            DFA::new(0, vec![1], vec![(0, 'a', 1), (1, 'a', 1), (1, 'b', 1)]);
+        let ends_b: DFA<u32, char> = DFA::new(
+            0,
+            vec![1],
+            vec![(0, 'a', 0), (0, 'b', 1), (1, 'a', 0), (1, 'b', 1)],
+        );
+        let words = ["", "a", "b", "ab", "ba", "abb"];
+        let check = |dfa: &DFA<u32, char>, accepted: &[&str]| {
+            for w in &words {
+                let w_chars: Vec<char> = w.chars().collect();
+                assert_eq!(dfa.accepts(&w_chars), accepted.contains(w), "{:?}", w);
+            }
+        };
+        check(&starts_a.union(&ends_b), &["a", "b", "ab", "abb"]);
+        check(&starts_a.intersection(&ends_b), &["ab", "abb"]);
+        check(&starts_a.difference(&ends_b), &["a"]);
+        check(&starts_a.symmetric_difference(&ends_b), &["a", "b"]);
+        // Implication: anything that starts with an a must end with a b.
+        check(
+            &starts_a.product(&ends_b, |a, b| !a || b),
+            &["", "b", "ab", "ba", "abb"],
+        );
+    }
+
+    #[test]
+    fn without_transitions() {
+        let epsilon: DFA<u32, char> = DFA::new(0, vec![0], None);
+        let empty: DFA<u32, char> = DFA::new(0, None, None);
+        let union = epsilon.union(&empty);
+        assert!(union.accepts(&[]));
+        assert_eq!(union.state_count(), 1);
+        assert!(!epsilon.intersection(&empty).accepts(&[]));
+        assert!(epsilon.difference(&empty).language_eq(&epsilon));
+    }
+}
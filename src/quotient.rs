@@ -0,0 +1,95 @@
+use std::fmt::{self, Debug, Display};
+use std::hash::Hash;
+
+use crate::hash::HashMap;
+use crate::DFA;
+
+// Why `DFA::quotient` could not merge states as asked.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum QuotientError<S, E> {
+    // The state appears in more than one block.
+    Overlap(S),
+    // Merging the block containing `state` would give it two different transitions on `label`.
+    Nondeterministic { state: S, label: E },
+}
+
+impl<S: Debug, E: Debug> Display for QuotientError<S, E> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            QuotientError::Overlap(q) => write!(f, "state {:?} is in more than one block", q),
+            QuotientError::Nondeterministic { state, label } => write!(
+                f,
+                "merging the block of state {:?} gives two transitions on {:?}",
+                state, label
+            ),
+        }
+    }
+}
+
+impl<S: Debug, E: Debug> std::error::Error for QuotientError<S, E> {}
+
+impl<S, E> DFA<S, E>
+where
+    S: Eq + Hash + Copy + Debug,
+    E: Eq + Hash + Copy + Debug,
+{
+    // Merges each block of `partition` into a single state, named by the block's first member.
+    // States in no block are left alone. A merged state accepts if any of its members did, so
+    // the result accepts at least everything this DFA accepts. Fails if the blocks overlap, or
+    // if merging would make some transition nondeterministic.
+    pub fn quotient(&self, partition: &[Vec<S>]) -> Result<DFA<S, E>, QuotientError<S, E>> {
+        let mut representative: HashMap<S, S> = HashMap::default();
+        for block in partition {
+            for &q in block {
+                if representative.insert(q, block[0]).is_some() {
+                    return Err(QuotientError::Overlap(q));
+                }
+            }
+        }
+        let merged = |q: S| representative.get(&q).copied().unwrap_or(q);
+
+        let mut delta: HashMap<(S, E), S> = HashMap::default();
+        for &(src, label, dst) in &self.transitions {
+            let (src, dst) = (merged(src), merged(dst));
+            if *delta.entry((src, label)).or_insert(dst) != dst {
+                return Err(QuotientError::Nondeterministic { state: src, label });
+            }
+        }
+        Ok(DFA::new(
+            merged(self.initial_state),
+            self.final_states.iter().map(|&q| merged(q)),
+            delta
+                .into_iter()
+                .map(|((src, label), dst)| (src, label, dst)),
+        ))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::QuotientError;
+    use crate::DFA;
+
+    #[test]
+    fn quotient_merges_blocks() {
+        // Counts a's modulo 4, accepting multiples of 2.
+        let dfa: DFA<u32, char> = DFA::new(0, vec![0, 2], (0..4).map(|q| (q, 'a', (q + 1) % 4)));
+        let parity = dfa.quotient(&[vec![0, 2], vec![1, 3]]).unwrap();
+        assert_eq!(parity.states().len(), 2);
+        assert!(parity.accepts(&['a', 'a']));
+        assert!(!parity.accepts(&['a', 'a', 'a']));
+
+        // Merging only some states can break determinism.
+        assert_eq!(
+            dfa.quotient(&[vec![0, 1]]).unwrap_err(),
+            QuotientError::Nondeterministic {
+                state: 0,
+                label: 'a'
+            },
+        );
+        assert_eq!(
+            dfa.quotient(&[vec![0, 2], vec![2, 3]]).unwrap_err(),
+            QuotientError::Overlap(2),
+        );
+    }
+}
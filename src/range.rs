@@ -0,0 +1,197 @@
+use std::fmt::{self, Display};
+use std::ops::RangeInclusive;
+
+use crate::interval::IntervalSet;
+use crate::sfa::{Predicate, SFA};
+
+// A DFA over `char`s whose transitions are labeled by character classes, so that a class like
+// "any letter" is one edge rather than one edge per codepoint.
+pub type RangeDFA<S> = SFA<S, CharClass>;
+
+// The valid `char`s: every codepoint except the surrogates.
+fn chars() -> IntervalSet {
+    IntervalSet::new(vec![(0, 0xD7FF), (0xE000, u32::from(char::MAX))])
+}
+
+// A set of `char`s, as the codepoint intervals it covers. Ranges on either side of the
+// surrogates stay separate, since no `char` lies between them.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct CharClass(IntervalSet);
+
+impl CharClass {
+    pub fn new(ranges: impl IntoIterator<Item = RangeInclusive<char>>) -> CharClass {
+        let intervals = ranges
+            .into_iter()
+            .map(|r| (u32::from(*r.start()), u32::from(*r.end())));
+        CharClass(IntervalSet::new(intervals).and(&chars()))
+    }
+
+    pub fn ranges(&self) -> impl Iterator<Item = RangeInclusive<char>> + '_ {
+        // Every interval starts and ends on a valid `char`.
+        self.0
+            .intervals()
+            .iter()
+            .map(|&(lo, hi)| char::from_u32(lo).unwrap()..=char::from_u32(hi).unwrap())
+    }
+}
+
+impl From<RangeInclusive<char>> for CharClass {
+    fn from(range: RangeInclusive<char>) -> CharClass {
+        CharClass::new(Some(range))
+    }
+}
+
+// Writes the class in regex syntax: a single character, `.` for every character, or a bracketed
+// class, negated when that is shorter.
+impl Display for CharClass {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        // Written the way `Regex::parse` reads them back, where `.` leaves out newlines.
+        if *self == CharClass::top() {
+            return write!(f, "[\\s\\S]");
+        }
+        if *self == CharClass::bottom() {
+            return write!(f, "[^\\s\\S]");
+        }
+        if *self == CharClass::from('\n'..='\n').not() {
+            return write!(f, ".");
+        }
+        let ranges: Vec<RangeInclusive<char>> = self.ranges().collect();
+        if let [range] = ranges.as_slice() {
+            if range.start() == range.end() {
+                return write_char(f, *range.start(), "\\.|*+?()[]{}^$");
+            }
+        }
+        let negated = self.not();
+        let (negate, ranges) = if negated.ranges().count() < ranges.len() {
+            (true, negated.ranges().collect())
+        } else {
+            (false, ranges)
+        };
+        write!(f, "[{}", if negate { "^" } else { "" })?;
+        for range in ranges {
+            write_char(f, *range.start(), "\\]^-[")?;
+            if range.start() != range.end() {
+                write!(f, "-")?;
+                write_char(f, *range.end(), "\\]^-[")?;
+            }
+        }
+        write!(f, "]")
+    }
+}
+
+// Writes `c`, escaped if it is one of `special` or not printable.
+fn write_char(f: &mut fmt::Formatter<'_>, c: char, special: &str) -> fmt::Result {
+    match c {
+        '\n' => write!(f, "\\n"),
+        '\t' => write!(f, "\\t"),
+        '\r' => write!(f, "\\r"),
+        c if special.contains(c) => write!(f, "\\{}", c),
+        c if c.is_control() || (c.is_whitespace() && c != ' ') => {
+            write!(f, "\\u{{{:x}}}", u32::from(c))
+        }
+        c => write!(f, "{}", c),
+    }
+}
+
+impl Predicate for CharClass {
+    type Symbol = char;
+
+    fn top() -> Self {
+        CharClass(chars())
+    }
+    fn bottom() -> Self {
+        CharClass(IntervalSet::bottom())
+    }
+    fn and(&self, other: &Self) -> Self {
+        CharClass(self.0.and(&other.0))
+    }
+    fn or(&self, other: &Self) -> Self {
+        CharClass(self.0.or(&other.0))
+    }
+    fn not(&self) -> Self {
+        CharClass(self.0.not().and(&chars()))
+    }
+    fn contains(&self, &c: &char) -> bool {
+        self.0.contains(&u32::from(c))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::{CharClass, RangeDFA};
+    use crate::sfa::Predicate;
+
+    fn chars(s: &str) -> Vec<char> {
+        s.chars().collect()
+    }
+
+    #[test]
+    fn class_operations() {
+        let lower = CharClass::from('a'..='z');
+        let middle = CharClass::new(vec!['m'..='p', 'f'..='g', 'o'..='r']);
+        assert_eq!(
+            middle.ranges().collect::<Vec<_>>(),
+            vec!['f'..='g', 'm'..='r']
+        );
+        assert_eq!(
+            lower.and(&middle.not()).ranges().collect::<Vec<_>>(),
+            vec!['a'..='e', 'h'..='l', 's'..='z'],
+        );
+        assert!(lower.not().contains(&'λ'));
+        assert!(!lower.not().contains(&'q'));
+        assert_eq!(lower.or(&lower.not()), CharClass::top());
+        assert!(!CharClass::top().not().is_satisfiable());
+        assert_eq!(middle.to_string(), "[f-gm-r]");
+        assert_eq!(CharClass::from('a'..='z').not().to_string(), "[^a-z]");
+        assert_eq!(CharClass::from('*'..='*').to_string(), "\\*");
+        assert_eq!(CharClass::top().to_string(), "[\\s\\S]");
+        assert_eq!(CharClass::from('\n'..='\n').not().to_string(), ".");
+    }
+
+    #[test]
+    fn product_and_minimize_split_ranges() {
+        let letters: RangeDFA<u32> = RangeDFA::new(
+            0,
+            vec![1],
+            vec![
+                (0, CharClass::from('a'..='z'), 1),
+                (1, CharClass::from('a'..='z'), 1),
+            ],
+        );
+        let greek: RangeDFA<u32> =
+            RangeDFA::new(0, vec![0], vec![(0, CharClass::from('α'..='ω'), 0)]);
+        let middle: RangeDFA<u32> = RangeDFA::new(
+            0,
+            vec![1],
+            vec![
+                (0, CharClass::from('f'..='p'), 1),
+                (1, CharClass::from('k'..='u'), 1),
+            ],
+        );
+
+        let both = letters.intersection(&middle);
+        assert!(both.accepts(&chars("fkup")));
+        assert!(!both.accepts(&chars("fe")));
+        let either = letters.union(&greek).minimize();
+        assert!(either.accepts(&chars("αβγ")));
+        assert!(either.accepts(&chars("abc")));
+        assert!(!either.accepts(&chars("aβ")));
+
+        // Splitting one class across two edges into the same state merges back on minimization.
+        let split: RangeDFA<u32> = RangeDFA::new(
+            0,
+            vec![1],
+            vec![
+                (0, CharClass::from('a'..='m'), 1),
+                (0, CharClass::from('n'..='z'), 1),
+            ],
+        );
+        let minimal = split.minimize();
+        assert!(minimal.accepts(&chars("q")));
+        assert!(!minimal.accepts(&chars("qq")));
+        assert!(minimal
+            .intersection(&letters)
+            .minimize()
+            .accepts(&chars("z")));
+    }
+}
@@ -0,0 +1,512 @@
+use std::collections::VecDeque;
+use std::iter::Peekable;
+use std::str::Chars;
+
+use crate::format::ParseError;
+use crate::hash::{HashMap, HashSet};
+use crate::range::{CharClass, RangeDFA};
+use crate::sfa::{minterms, Predicate};
+
+// A parsed regular expression, shaped like `regex_syntax::hir::HirKind` so that parser output
+// maps onto it one variant at a time. Anchors, look-around and captures have no counterpart:
+// a DFA only decides whether the whole input matches.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Regex {
+    // Matches only the empty string.
+    Empty,
+    // Matches any single character in the class.
+    Class(CharClass),
+    Concat(Vec<Regex>),
+    Alternation(Vec<Regex>),
+    // Matches between `min` and `max` (inclusive, or unbounded if `None`) repetitions of `sub`.
+    Repetition {
+        min: u32,
+        max: Option<u32>,
+        sub: Box<Regex>,
+    },
+}
+
+impl Regex {
+    pub fn literal(text: &str) -> Regex {
+        Regex::Concat(
+            text.chars()
+                .map(|c| Regex::Class(CharClass::from(c..=c)))
+                .collect(),
+        )
+    }
+
+    // Parses the common regex syntax: literals, `.`, bracketed classes, the escapes `\d \w \s`
+    // (ASCII only) and their negations, code points written `\u{7f}`, groups, alternation, and
+    // the repetitions `* + ? {m} {m,} {m,n}`. Anchors are rejected, since a DFA always matches the
+    // whole input, and so are repetition counts above `MAX_REPETITION`, 1000.
+    pub fn parse(pattern: &str) -> Result<Regex, ParseError> {
+        let mut parser = Parser {
+            chars: pattern.chars().peekable(),
+        };
+        let regex = parser.alternation()?;
+        match parser.chars.next() {
+            None => Ok(regex),
+            Some(c) => Err(ParseError::new(format!("unexpected {:?}", c))),
+        }
+    }
+
+    // Compiles to a DFA by Thompson's construction followed by a subset construction over
+    // character classes. The classes leaving each subset are split into disjoint pieces, so
+    // that no class is ever expanded into individual characters.
+    pub fn to_dfa(&self) -> RangeDFA<u32> {
+        let mut nfa = Thompson::default();
+        let start = nfa.state();
+        let end = nfa.build(self, start);
+        let mut outflows: HashMap<u32, Vec<(Option<CharClass>, u32)>> = HashMap::default();
+        for (src, label, dst) in nfa.transitions {
+            outflows.entry(src).or_default().push((label, dst));
+        }
+
+        let initial = closure(&outflows, vec![start]);
+        let mut ids: HashMap<Vec<u32>, u32> = HashMap::default();
+        let mut queue = VecDeque::new();
+        let mut final_states = Vec::new();
+        let mut transitions = Vec::new();
+        ids.insert(initial.clone(), 0);
+        queue.push_back(initial);
+        while let Some(subset) = queue.pop_front() {
+            let src = ids[&subset];
+            if subset.contains(&end) {
+                final_states.push(src);
+            }
+            let edges: Vec<&(Option<CharClass>, u32)> = subset
+                .iter()
+                .flat_map(|q| outflows.get(q).into_iter().flatten())
+                .collect();
+            let classes: Vec<&CharClass> = edges
+                .iter()
+                .filter_map(|(label, _)| label.as_ref())
+                .collect();
+            for piece in minterms(classes.iter().copied()) {
+                let next: Vec<u32> = edges
+                    .iter()
+                    .filter(|(label, _)| {
+                        label
+                            .as_ref()
+                            .is_some_and(|class| class.and(&piece).is_satisfiable())
+                    })
+                    .map(|&&(_, dst)| dst)
+                    .collect();
+                if next.is_empty() {
+                    continue;
+                }
+                let next = closure(&outflows, next);
+                let n = ids.len() as u32;
+                let dst = *ids.entry(next.clone()).or_insert_with(|| {
+                    queue.push_back(next);
+                    n
+                });
+                transitions.push((src, piece, dst));
+            }
+        }
+        RangeDFA::new(0, final_states, transitions)
+    }
+}
+
+// The largest count `{m}` or `{m,n}` may give. `to_dfa` copies the repeated expression once per
+// count, so an unchecked `a{4000000000}` would exhaust memory instead of failing to parse.
+const MAX_REPETITION: u32 = 1000;
+
+struct Parser<'a> {
+    chars: Peekable<Chars<'a>>,
+}
+
+impl Parser<'_> {
+    fn alternation(&mut self) -> Result<Regex, ParseError> {
+        let mut alternatives = vec![self.concat()?];
+        while self.chars.next_if_eq(&'|').is_some() {
+            alternatives.push(self.concat()?);
+        }
+        Ok(if alternatives.len() == 1 {
+            alternatives.pop().unwrap()
+        } else {
+            Regex::Alternation(alternatives)
+        })
+    }
+
+    fn concat(&mut self) -> Result<Regex, ParseError> {
+        let mut items = Vec::new();
+        while let Some(&c) = self.chars.peek() {
+            if c == '|' || c == ')' {
+                break;
+            }
+            let mut item = self.atom()?;
+            while let Some((min, max)) = self.repetition()? {
+                item = Regex::Repetition {
+                    min,
+                    max,
+                    sub: Box::new(item),
+                };
+            }
+            items.push(item);
+        }
+        Ok(match items.len() {
+            0 => Regex::Empty,
+            1 => items.pop().unwrap(),
+            _ => Regex::Concat(items),
+        })
+    }
+
+    fn atom(&mut self) -> Result<Regex, ParseError> {
+        match self.chars.next().unwrap() {
+            '(' => {
+                if self.chars.next_if_eq(&'?').is_some() && self.chars.next_if_eq(&':').is_none() {
+                    return Err(ParseError::new("only (?:...) groups are supported"));
+                }
+                let regex = self.alternation()?;
+                match self.chars.next() {
+                    Some(')') => Ok(regex),
+                    _ => Err(ParseError::new("unclosed group")),
+                }
+            }
+            '[' => Ok(Regex::Class(self.class()?)),
+            '.' => Ok(Regex::Class(CharClass::from('\n'..='\n').not())),
+            '\\' => Ok(Regex::Class(self.escape()?)),
+            c @ ('^' | '$') => Err(ParseError::new(format!("anchor {:?} is not supported", c))),
+            c @ ('*' | '+' | '?' | '{') => {
+                Err(ParseError::new(format!("{:?} has nothing to repeat", c)))
+            }
+            c => Ok(Regex::Class(CharClass::from(c..=c))),
+        }
+    }
+
+    // Parses a repetition operator, if one comes next, as the bounds it allows.
+    fn repetition(&mut self) -> Result<Option<(u32, Option<u32>)>, ParseError> {
+        let bounds = match self.chars.peek() {
+            Some('*') => (0, None),
+            Some('+') => (1, None),
+            Some('?') => (0, Some(1)),
+            Some('{') => {
+                self.chars.next();
+                let min = self.number()?;
+                let max = if self.chars.next_if_eq(&',').is_none() {
+                    Some(min)
+                } else if self.chars.peek() == Some(&'}') {
+                    None
+                } else {
+                    Some(self.number()?)
+                };
+                if self.chars.next() != Some('}') {
+                    return Err(ParseError::new("unclosed repetition"));
+                }
+                if max.is_some_and(|max| max < min) {
+                    return Err(ParseError::new("repetition bounds are out of order"));
+                }
+                if max.unwrap_or(min) > MAX_REPETITION {
+                    return Err(ParseError::new(format!(
+                        "repetition count is over the limit of {}",
+                        MAX_REPETITION
+                    )));
+                }
+                return Ok(Some((min, max)));
+            }
+            _ => return Ok(None),
+        };
+        self.chars.next();
+        Ok(Some(bounds))
+    }
+
+    fn number(&mut self) -> Result<u32, ParseError> {
+        let mut digits = String::new();
+        while let Some(d) = self.chars.next_if(char::is_ascii_digit) {
+            digits.push(d);
+        }
+        digits
+            .parse()
+            .map_err(|_| ParseError::new("expected a repetition count"))
+    }
+
+    // Parses a bracketed class, whose `[` has been consumed.
+    fn class(&mut self) -> Result<CharClass, ParseError> {
+        let negate = self.chars.next_if_eq(&'^').is_some();
+        let mut class = CharClass::bottom();
+        let mut first = true;
+        loop {
+            let c = match self.chars.next() {
+                None => return Err(ParseError::new("unclosed class")),
+                Some(']') if !first => break,
+                Some(c) => c,
+            };
+            first = false;
+            let lo = match c {
+                '\\' => {
+                    let escaped = self.escape()?;
+                    match single_char(&escaped) {
+                        Some(c) => c,
+                        None => {
+                            class = class.or(&escaped);
+                            continue;
+                        }
+                    }
+                }
+                c => c,
+            };
+            let mut ahead = self.chars.clone();
+            if ahead.next() == Some('-') && !matches!(ahead.next(), Some(']') | None) {
+                self.chars.next();
+                let hi = match self.chars.next().unwrap() {
+                    '\\' => single_char(&self.escape()?)
+                        .ok_or_else(|| ParseError::new("invalid class range"))?,
+                    c => c,
+                };
+                if hi < lo {
+                    return Err(ParseError::new("class range is out of order"));
+                }
+                class = class.or(&CharClass::from(lo..=hi));
+            } else {
+                class = class.or(&CharClass::from(lo..=lo));
+            }
+        }
+        Ok(if negate { class.not() } else { class })
+    }
+
+    // Parses an escape sequence, whose `\` has been consumed.
+    fn escape(&mut self) -> Result<CharClass, ParseError> {
+        let digit = CharClass::from('0'..='9');
+        let word = CharClass::new(vec!['a'..='z', 'A'..='Z', '0'..='9', '_'..='_']);
+        let space = CharClass::new(vec!['\t'..='\r', ' '..=' ']);
+        let single = |c: char| CharClass::from(c..=c);
+        match self.chars.next() {
+            None => Err(ParseError::new("trailing backslash")),
+            Some('d') => Ok(digit),
+            Some('D') => Ok(digit.not()),
+            Some('w') => Ok(word),
+            Some('W') => Ok(word.not()),
+            Some('s') => Ok(space),
+            Some('S') => Ok(space.not()),
+            Some('n') => Ok(single('\n')),
+            Some('t') => Ok(single('\t')),
+            Some('r') => Ok(single('\r')),
+            Some('u') => {
+                if self.chars.next_if_eq(&'{').is_none() {
+                    return Err(ParseError::new("expected { after \\u"));
+                }
+                let mut hex = String::new();
+                while let Some(d) = self.chars.next_if(char::is_ascii_hexdigit) {
+                    hex.push(d);
+                }
+                if self.chars.next_if_eq(&'}').is_none() {
+                    return Err(ParseError::new("unclosed \\u{...} escape"));
+                }
+                u32::from_str_radix(&hex, 16)
+                    .ok()
+                    .and_then(char::from_u32)
+                    .map(single)
+                    .ok_or_else(|| ParseError::new(format!("invalid code point \\u{{{}}}", hex)))
+            }
+            Some(c) if c.is_ascii_alphanumeric() => {
+                Err(ParseError::new(format!("unknown escape \\{}", c)))
+            }
+            Some(c) => Ok(single(c)),
+        }
+    }
+}
+
+// The only character in `class`, if it has exactly one.
+fn single_char(class: &CharClass) -> Option<char> {
+    let ranges: Vec<_> = class.ranges().collect();
+    match ranges.as_slice() {
+        [range] if range.start() == range.end() => Some(*range.start()),
+        _ => None,
+    }
+}
+
+// An NFA under construction, with `None` labels for epsilon transitions.
+#[derive(Default)]
+struct Thompson {
+    states: u32,
+    transitions: Vec<(u32, Option<CharClass>, u32)>,
+}
+
+impl Thompson {
+    fn state(&mut self) -> u32 {
+        self.states += 1;
+        self.states - 1
+    }
+
+    // Adds states and transitions that match `regex` starting from `start`, and returns the
+    // state where a match ends.
+    fn build(&mut self, regex: &Regex, start: u32) -> u32 {
+        match regex {
+            Regex::Empty => start,
+            Regex::Class(class) => {
+                let end = self.state();
+                self.transitions.push((start, Some(class.clone()), end));
+                end
+            }
+            Regex::Concat(subs) => subs.iter().fold(start, |q, sub| self.build(sub, q)),
+            Regex::Alternation(subs) => {
+                let end = self.state();
+                for sub in subs {
+                    let q = self.state();
+                    self.transitions.push((start, None, q));
+                    let q = self.build(sub, q);
+                    self.transitions.push((q, None, end));
+                }
+                end
+            }
+            Regex::Repetition { min, max, sub } => {
+                let mut q = start;
+                for _ in 0..*min {
+                    q = self.build(sub, q);
+                }
+                let end = self.state();
+                self.transitions.push((q, None, end));
+                match max {
+                    None => {
+                        // Each further repetition returns to where the loop started.
+                        let body = self.state();
+                        self.transitions.push((q, None, body));
+                        let back = self.build(sub, body);
+                        self.transitions.push((back, None, q));
+                    }
+                    Some(max) => {
+                        for _ in *min..*max {
+                            let body = self.state();
+                            self.transitions.push((q, None, body));
+                            q = self.build(sub, body);
+                            self.transitions.push((q, None, end));
+                        }
+                    }
+                }
+                end
+            }
+        }
+    }
+}
+
+// All states reachable from `start` by epsilon transitions alone, sorted so that it can be used
+// as a key.
+fn closure(outflows: &HashMap<u32, Vec<(Option<CharClass>, u32)>>, start: Vec<u32>) -> Vec<u32> {
+    let mut seen = HashSet::default();
+    let mut stack = start;
+    while let Some(q) = stack.pop() {
+        if !seen.insert(q) {
+            continue;
+        }
+        for (label, dst) in outflows.get(&q).into_iter().flatten() {
+            if label.is_none() {
+                stack.push(*dst);
+            }
+        }
+    }
+    let mut states: Vec<u32> = seen.into_iter().collect();
+    states.sort_unstable();
+    states
+}
+
+#[cfg(test)]
+mod test {
+    use super::Regex;
+    use crate::range::CharClass;
+    use crate::sfa::Predicate;
+
+    fn chars(s: &str) -> Vec<char> {
+        s.chars().collect()
+    }
+
+    #[test]
+    fn compile_identifier() {
+        // [a-zA-Z_][a-zA-Z0-9_]*
+        let start = CharClass::new(vec!['a'..='z', 'A'..='Z', '_'..='_']);
+        let rest = CharClass::new(vec!['a'..='z', 'A'..='Z', '0'..='9', '_'..='_']);
+        let regex = Regex::Concat(vec![
+            Regex::Class(start),
+            Regex::Repetition {
+                min: 0,
+                max: None,
+                sub: Box::new(Regex::Class(rest)),
+            },
+        ]);
+        let dfa = regex.to_dfa();
+        assert!(dfa.accepts(&chars("_foo9")));
+        assert!(!dfa.accepts(&chars("9foo")));
+        assert!(!dfa.accepts(&chars("")));
+    }
+
+    #[test]
+    fn compile_bounded_repetition_and_alternation() {
+        // (ab|λ){2,3}
+        let regex = Regex::Repetition {
+            min: 2,
+            max: Some(3),
+            sub: Box::new(Regex::Alternation(vec![
+                Regex::literal("ab"),
+                Regex::literal("λ"),
+            ])),
+        };
+        let dfa = regex.to_dfa();
+        assert!(dfa.accepts(&chars("abλ")));
+        assert!(dfa.accepts(&chars("λλab")));
+        assert!(!dfa.accepts(&chars("ab")));
+        assert!(!dfa.accepts(&chars("abababab")));
+        assert!(!dfa.accepts(&chars("aλ")));
+        assert!(Regex::Empty.to_dfa().accepts(&[]));
+    }
+
+    #[test]
+    fn parse_patterns() {
+        let dfa = Regex::parse("ab(c|d)*").unwrap().to_dfa();
+        assert!(dfa.accepts(&chars("abcddc")));
+        assert!(!dfa.accepts(&chars("abe")));
+        let dfa = Regex::parse(r"[a-c\d]{2,3}\.x?").unwrap().to_dfa();
+        assert!(dfa.accepts(&chars("a9.")));
+        assert!(dfa.accepts(&chars("cab.x")));
+        assert!(!dfa.accepts(&chars("d9.")));
+        assert!(!dfa.accepts(&chars("abca.")));
+        let dfa = Regex::parse("[^-a]+|(?:)").unwrap().to_dfa();
+        assert!(dfa.accepts(&chars("")));
+        assert!(dfa.accepts(&chars("bz")));
+        assert!(!dfa.accepts(&chars("b-")));
+
+        assert!(Regex::parse("a{1000}").is_ok());
+        let lambda = Regex::Class(CharClass::from('λ'..='λ'));
+        assert_eq!(Regex::parse(r"\u{3bb}").unwrap(), lambda);
+        for bad in [
+            "(a",
+            "a{3,1}",
+            "*",
+            "[a",
+            "^a",
+            "\\q",
+            "a)",
+            "a{1001}",
+            "a{2,4000000000}",
+            "\\u41",
+            "\\u{}",
+            "\\u{d800}",
+            "\\u{110000}",
+        ] {
+            assert!(Regex::parse(bad).is_err(), "{}", bad);
+        }
+    }
+
+    #[test]
+    fn classes_print_as_patterns() {
+        let newline = CharClass::from('\n'..='\n');
+        for class in [
+            CharClass::from('\u{7}'..='\u{7}'),
+            CharClass::from('\u{a0}'..='\u{a0}'),
+            CharClass::from('\0'..='\u{1f}'),
+            CharClass::new(vec!['\t'..='\t', ']'..='^', '-'..='-', '['..='[']),
+            CharClass::from('\u{85}'..='\u{2028}').not(),
+            CharClass::from('*'..='*'),
+            newline.not(),
+            CharClass::top(),
+            CharClass::bottom(),
+        ] {
+            let printed = class.to_string();
+            assert_eq!(
+                Regex::parse(&printed).unwrap(),
+                Regex::Class(class),
+                "{}",
+                printed
+            );
+        }
+    }
+}
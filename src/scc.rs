@@ -0,0 +1,294 @@
+use std::fmt::{Debug, Display, Write};
+use std::hash::Hash;
+
+use crate::hash::{HashMap, HashSet};
+use crate::DFA;
+
+// A strongly connected component of a DFA's transition graph.
+#[derive(Debug, Clone)]
+pub struct Scc<S> {
+    pub states: Vec<S>,
+    // Whether any of the states is accepting.
+    pub accepting: bool,
+    // Whether the component contains a cycle, i.e., it has more than one state or a self-loop.
+    pub cyclic: bool,
+}
+
+// The DAG of a DFA's strongly connected components, from `DFA::condensation`.
+#[derive(Debug, Clone)]
+pub struct Condensation<S, E> {
+    // In reverse topological order, as `sccs` returns them, so every edge goes from a component
+    // to an earlier one.
+    pub components: Vec<Scc<S>>,
+    // The component that holds the initial state.
+    pub initial: usize,
+    // For each pair of components `(src, dst)` with transitions between them, the distinct labels
+    // of those transitions. Sorted by `src` and then `dst`.
+    pub edges: Vec<(usize, usize, Vec<E>)>,
+}
+
+// Edges with more labels than this are drawn with the first few and a count of the rest.
+const MAX_DOT_LABELS: usize = 4;
+
+impl<S, E> Condensation<S, E>
+where
+    S: Eq + Hash + Copy + Debug,
+    E: Eq + Hash + Copy + Debug,
+{
+    // Draws the DAG in Graphviz DOT. Each component is a box labeled with its number and size,
+    // drawn with a double border if it has an accepting state and rounded if it is cyclic.
+    pub fn to_dot(&self) -> String
+    where
+        E: Display,
+    {
+        let mut out = String::from("digraph condensation {\n  rankdir=LR;\n  node [shape=box];\n");
+        for (i, scc) in self.components.iter().enumerate() {
+            let n = scc.states.len();
+            let noun = if n == 1 { "state" } else { "states" };
+            let mut attrs = format!("label=\"C{}\\n{} {}\"", i, n, noun);
+            if scc.accepting {
+                attrs += ", peripheries=2";
+            }
+            if scc.cyclic {
+                attrs += ", style=rounded";
+            }
+            writeln!(out, "  c{} [{}];", i, attrs).unwrap();
+        }
+        out += "  __start [shape=point];\n";
+        writeln!(out, "  __start -> c{};", self.initial).unwrap();
+        for (src, dst, labels) in &self.edges {
+            let mut names: Vec<String> = labels.iter().map(|e| e.to_string()).collect();
+            names.sort();
+            names.dedup();
+            if names.len() > MAX_DOT_LABELS {
+                let rest = names.len() - MAX_DOT_LABELS;
+                names.truncate(MAX_DOT_LABELS);
+                names.push(format!("(+{})", rest));
+            }
+            let label: String = names
+                .join(", ")
+                .chars()
+                .flat_map(char::escape_default)
+                .collect();
+            writeln!(out, "  c{} -> c{} [label=\"{}\"];", src, dst, label).unwrap();
+        }
+        out + "}\n"
+    }
+}
+
+impl<S, E> DFA<S, E>
+where
+    S: Eq + Hash + Copy + Debug,
+    E: Eq + Hash + Copy + Debug,
+{
+    // Decomposes the transition graph into strongly connected components, using Tarjan's
+    // algorithm. Components come out in reverse topological order: every transition either stays
+    // within a component or leads to one that appears earlier in the list.
+    pub fn sccs(&self) -> Vec<Scc<S>> {
+        let outflows = self.transitions.by_a();
+        let successors = |q: &S| -> Vec<S> {
+            outflows
+                .get(q)
+                .into_iter()
+                .flatten()
+                .map(|&(_, dst)| dst)
+                .collect()
+        };
+
+        let mut index: HashMap<S, usize> = HashMap::default();
+        let mut lowlink: HashMap<S, usize> = HashMap::default();
+        let mut on_stack: HashMap<S, bool> = HashMap::default();
+        let mut stack: Vec<S> = Vec::new();
+        let mut sccs = Vec::new();
+        for root in self.states() {
+            if index.contains_key(&root) {
+                continue;
+            }
+            // An explicit call stack of (state, successors, next successor to visit).
+            let mut calls = vec![(root, successors(&root), 0)];
+            index.insert(root, index.len());
+            lowlink.insert(root, index[&root]);
+            stack.push(root);
+            on_stack.insert(root, true);
+            while let Some((q, next, i)) = calls.last_mut() {
+                let q = *q;
+                if let Some(&r) = next.get(*i) {
+                    *i += 1;
+                    if !index.contains_key(&r) {
+                        index.insert(r, index.len());
+                        lowlink.insert(r, index[&r]);
+                        stack.push(r);
+                        on_stack.insert(r, true);
+                        calls.push((r, successors(&r), 0));
+                    } else if on_stack[&r] {
+                        let low = lowlink[&q].min(index[&r]);
+                        lowlink.insert(q, low);
+                    }
+                    continue;
+                }
+                calls.pop();
+                if let Some(&(parent, _, _)) = calls.last() {
+                    let low = lowlink[&parent].min(lowlink[&q]);
+                    lowlink.insert(parent, low);
+                }
+                if lowlink[&q] == index[&q] {
+                    let mut states = Vec::new();
+                    loop {
+                        let r = stack.pop().unwrap();
+                        on_stack.insert(r, false);
+                        states.push(r);
+                        if r == q {
+                            break;
+                        }
+                    }
+                    let accepting = states.iter().any(|r| self.final_states.contains(r));
+                    let cyclic = states.len() > 1 || successors(&q).contains(&q);
+                    sccs.push(Scc {
+                        states,
+                        accepting,
+                        cyclic,
+                    });
+                }
+            }
+        }
+        sccs
+    }
+
+    // Collapses every strongly connected component to a single node, which gives a map of the
+    // DFA's overall structure even when it is too big to draw in full.
+    pub fn condensation(&self) -> Condensation<S, E> {
+        let components = self.sccs();
+        let mut component: HashMap<S, usize> = HashMap::default();
+        for (i, scc) in components.iter().enumerate() {
+            for &q in &scc.states {
+                component.insert(q, i);
+            }
+        }
+        // Several states of one component often lead to the next on the same label.
+        let mut edges: HashMap<(usize, usize), HashSet<E>> = HashMap::default();
+        for (src, label, dst) in self.transitions() {
+            let (src, dst) = (component[&src], component[&dst]);
+            if src != dst {
+                edges.entry((src, dst)).or_default().insert(label);
+            }
+        }
+        let mut edges: Vec<(usize, usize, Vec<E>)> = edges
+            .into_iter()
+            .map(|((src, dst), labels)| (src, dst, labels.into_iter().collect()))
+            .collect();
+        edges.sort_unstable_by_key(|&(src, dst, _)| (src, dst));
+        Condensation {
+            initial: component[&self.initial_state],
+            components,
+            edges,
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::DFA;
+
+    #[test]
+    fn condensation() {
+        // {0, 1} leads to {2} on 'a' (from both states) and 'b', and to {3} on 'c'; {2} leads to
+        // {3}.
+        let input: DFA<u32, char> = DFA::new(
+            0,
+            vec![2],
+            vec![
+                (0, 'x', 1),
+                (1, 'x', 0),
+                (0, 'a', 2),
+                (1, 'a', 2),
+                (1, 'b', 2),
+                (1, 'c', 3),
+                (2, 'a', 3),
+                (3, 'a', 3),
+            ],
+        );
+        let dag = input.condensation();
+        assert_eq!(dag.components.len(), 3);
+        let id = |q: u32| {
+            dag.components
+                .iter()
+                .position(|scc| scc.states.contains(&q))
+                .unwrap()
+        };
+        assert_eq!(dag.initial, id(0));
+        assert_eq!(id(0), id(1));
+        assert_eq!(dag.edges.len(), 3);
+        let (_, _, labels) = dag
+            .edges
+            .iter()
+            .find(|&&(src, dst, _)| (src, dst) == (id(0), id(2)))
+            .unwrap();
+        assert_eq!(labels.len(), 2);
+        for &(src, dst, _) in &dag.edges {
+            assert!(dst < src);
+        }
+
+        let dot = dag.to_dot();
+        assert!(dot.contains(&format!(
+            "  c{} [label=\"C{}\\n2 states\", style=rounded];",
+            id(0),
+            id(0)
+        )));
+        assert!(dot.contains(&format!(
+            "  c{} [label=\"C{}\\n1 state\", peripheries=2];",
+            id(2),
+            id(2)
+        )));
+        assert!(dot.contains(&format!("  c{} -> c{} [label=\"a, b\"];", id(0), id(2))));
+        assert!(dot.contains(&format!("  __start -> c{};", id(0))));
+    }
+
+    #[test]
+    fn sccs_wikipedia() {
+        let input: DFA<u32, u8> = DFA::new(
+            0,
+            vec![2, 3, 4],
+            vec![
+                (0, 0, 1),
+                (0, 1, 2),
+                (1, 0, 0),
+                (1, 1, 3),
+                (2, 0, 4),
+                (2, 1, 5),
+                (3, 0, 4),
+                (3, 1, 5),
+                (4, 0, 4),
+                (4, 1, 5),
+                (5, 0, 5),
+                (5, 1, 5),
+            ],
+        );
+        let sccs = input.sccs();
+        let mut summary: Vec<(Vec<u32>, bool, bool)> = sccs
+            .iter()
+            .map(|scc| {
+                let mut states = scc.states.clone();
+                states.sort();
+                (states, scc.accepting, scc.cyclic)
+            })
+            .collect();
+        // {5} is a sink, so it must come before {4}, which must come before {2} and {3}.
+        let position = |q: u32| sccs.iter().position(|scc| scc.states.contains(&q)).unwrap();
+        assert!(position(5) < position(4));
+        assert!(position(4) < position(2));
+        assert!(position(4) < position(3));
+        assert!(position(3) < position(0));
+
+        summary.sort();
+        assert_eq!(
+            summary,
+            vec![
+                (vec![0, 1], false, true),
+                (vec![2], true, false),
+                (vec![3], true, false),
+                (vec![4], true, true),
+                (vec![5], false, true),
+            ]
+        );
+    }
+}
@@ -0,0 +1,246 @@
+use std::convert::TryInto;
+use std::fmt::Debug;
+use std::hash::Hash;
+
+use crate::hash::{HashMap, HashSet};
+use crate::DFA;
+
+// A byte DFA compiled into a dense table for scanning haystacks, reporting where the first match
+// anywhere in the haystack ends. States are numbered so that all the accepting states come last,
+// which makes the acceptance check a single comparison.
+#[derive(Debug, Clone)]
+pub struct Searcher {
+    // Row `q`, at `256 * q`, holds the states that `q` moves to on each byte. Every entry is a
+    // valid state, which `step` relies on.
+    table: Box<[u32]>,
+    start: u32,
+    first_accepting: u32,
+    // When every match must begin with one particular byte, the scan skips straight to the next
+    // occurrence of it whenever it is back in the start state. This only helps when the start state
+    // leaves on that one byte; patterns that can begin several ways get no skipping.
+    prefilter: Option<u8>,
+    // A byte, other than `prefilter`, that every match contains. A haystack without it is rejected
+    // before the scan. When there are several, this is the one least likely to turn up in text.
+    required: Option<u8>,
+}
+
+impl<S> DFA<S, u8>
+where
+    S: Eq + Hash + Copy + Debug,
+{
+    pub fn searcher(&self) -> Searcher {
+        let scanner = self.to_nfa(true).determinize();
+        let mut order: Vec<u32> = scanner.states().into_iter().collect();
+        order.sort_unstable_by_key(|q| (scanner.final_states.contains(q), *q));
+        let ids: HashMap<u32, u32> = order
+            .iter()
+            .enumerate()
+            .map(|(i, &q)| (q, i as u32))
+            .collect();
+
+        // The initial state loops on every byte that the DFA knows, so a missing transition means a
+        // byte that no match can contain, and the scan starts over.
+        let start = ids[&scanner.initial_state];
+        let mut table = vec![start; 256 * order.len()].into_boxed_slice();
+        for &(src, label, dst) in &scanner.transitions {
+            table[256 * ids[&src] as usize + usize::from(label)] = ids[&dst];
+        }
+        assert!(table.iter().all(|&q| (q as usize) < order.len()));
+        let first_accepting = (order.len() - scanner.final_states.len()) as u32;
+
+        let row = &table[256 * start as usize..][..256];
+        let mut leaving = (0..=255u8).filter(|&b| row[usize::from(b)] != start);
+        let prefilter = match (leaving.next(), leaving.next()) {
+            (Some(b), None) if start < first_accepting => Some(b),
+            _ => None,
+        };
+        let required = self
+            .required_bytes()
+            .into_iter()
+            .filter(|&b| Some(b) != prefilter)
+            .min_by_key(|&b| (commonness(b), b));
+        Searcher {
+            table,
+            start,
+            first_accepting,
+            prefilter,
+            required,
+        }
+    }
+
+    // The bytes that every accepted word contains: those without which no accepting state can be
+    // reached.
+    fn required_bytes(&self) -> Vec<u8> {
+        let transitions = self.transitions.indexed();
+        let labels: HashSet<u8> = self.transitions.by_b().into_keys().collect();
+        labels
+            .into_iter()
+            .filter(|&b| {
+                let mut seen: HashSet<S> = HashSet::default();
+                let mut stack = vec![self.initial_state];
+                while let Some(q) = stack.pop() {
+                    if !seen.insert(q) {
+                        continue;
+                    }
+                    if self.final_states.contains(&q) {
+                        return false;
+                    }
+                    for &(label, dst) in transitions.successors(&q) {
+                        if label != b {
+                            stack.push(dst);
+                        }
+                    }
+                }
+                true
+            })
+            .collect()
+    }
+}
+
+// A rough guess at how often `b` turns up in text: lowercase letters and spaces most often, then
+// capitals and digits, then other printable ASCII, and anything else least.
+fn commonness(b: u8) -> u8 {
+    match b {
+        b' ' | b'a'..=b'z' => 3,
+        b'A'..=b'Z' | b'0'..=b'9' => 2,
+        b'!'..=b'~' | b'\t' | b'\n' | b'\r' => 1,
+        _ => 0,
+    }
+}
+
+impl Searcher {
+    pub fn is_match(&self, haystack: &[u8]) -> bool {
+        self.find_end(haystack).is_some()
+    }
+
+    // The end of the earliest-ending match in `haystack`, if there is one.
+    pub fn find_end(&self, haystack: &[u8]) -> Option<usize> {
+        if self.start >= self.first_accepting {
+            return Some(0);
+        }
+        if let Some(needle) = self.required {
+            memchr(needle, haystack)?;
+        }
+        let mut q = self.start;
+        let mut i = 0;
+        loop {
+            if let (true, Some(needle)) = (q == self.start, self.prefilter) {
+                i += memchr(needle, &haystack[i..])?;
+            }
+            // Step four bytes at a time, heading back to the prefilter once a chunk leaves the
+            // scan in the start state.
+            let mut chunks = haystack[i..].chunks_exact(4);
+            for chunk in &mut chunks {
+                for (k, &b) in chunk.iter().enumerate() {
+                    q = self.step(q, b);
+                    if q >= self.first_accepting {
+                        return Some(i + k + 1);
+                    }
+                }
+                i += 4;
+                if q == self.start && self.prefilter.is_some() {
+                    break;
+                }
+            }
+            if q == self.start && self.prefilter.is_some() && i + 4 <= haystack.len() {
+                continue;
+            }
+            for &b in &haystack[i..] {
+                q = self.step(q, b);
+                i += 1;
+                if q >= self.first_accepting {
+                    return Some(i);
+                }
+            }
+            return None;
+        }
+    }
+
+    fn step(&self, q: u32, b: u8) -> u32 {
+        let i = 256 * q as usize + usize::from(b);
+        debug_assert!(i < self.table.len());
+        // Safety: `q` is the start state or came out of the table, so it is a valid state, whose
+        // row of 256 entries is in bounds.
+        unsafe { *self.table.get_unchecked(i) }
+    }
+}
+
+// The position of the first `needle` in `haystack`, checking eight bytes at a time.
+fn memchr(needle: u8, haystack: &[u8]) -> Option<usize> {
+    const LO: u64 = 0x0101_0101_0101_0101;
+    const HI: u64 = 0x8080_8080_8080_8080;
+    let repeated = LO * u64::from(needle);
+    let mut chunks = haystack.chunks_exact(8);
+    for (k, chunk) in (&mut chunks).enumerate() {
+        // A byte of `word` is zero exactly where the chunk holds `needle`.
+        let word = u64::from_ne_bytes(chunk.try_into().unwrap()) ^ repeated;
+        if word.wrapping_sub(LO) & !word & HI != 0 {
+            return chunk.iter().position(|&b| b == needle).map(|p| 8 * k + p);
+        }
+    }
+    let tail = chunks.remainder();
+    let offset = haystack.len() - tail.len();
+    tail.iter().position(|&b| b == needle).map(|p| offset + p)
+}
+
+#[cfg(test)]
+mod test {
+    use super::memchr;
+    use crate::DFA;
+
+    #[test]
+    fn memchr_matches_naive_search() {
+        let haystack: Vec<u8> = (0..100u8).map(|i| i.wrapping_mul(37) % 29).collect();
+        for needle in 0..32u8 {
+            let expected = haystack.iter().position(|&b| b == needle);
+            assert_eq!(memchr(needle, &haystack), expected);
+        }
+    }
+
+    #[test]
+    fn find_first_match() {
+        // Matches "ERROR" followed by a digit.
+        let mut transitions: Vec<(u32, u8, u32)> = b"ERROR"
+            .iter()
+            .enumerate()
+            .map(|(i, &b)| (i as u32, b, i as u32 + 1))
+            .collect();
+        transitions.extend((b'0'..=b'9').map(|d| (5, d, 6)));
+        let dfa = DFA::new(0, vec![6], transitions);
+        let searcher = dfa.searcher();
+        assert_eq!(searcher.prefilter, Some(b'E'));
+
+        let log = b"INFO ok\nWARN ERR\nERRORS\nERROR 1\nERRERROR7 trailing";
+        assert_eq!(searcher.find_end(log), Some(log.len() - 9));
+        assert!(!searcher.is_match(b"ERROR ERRO4 EERROR"));
+        assert!(searcher.is_match(b"EERROR4"));
+        assert_eq!(searcher.find_end(b""), None);
+
+        // Without a unique first byte there is no prefilter, but the scan is the same.
+        let either = DFA::new(0, vec![1], vec![(0, b'x', 1), (0, b'y', 1)]);
+        let searcher = either.searcher();
+        assert_eq!(searcher.prefilter, None);
+        assert_eq!(searcher.find_end(b"abcdefgy"), Some(8));
+    }
+
+    #[test]
+    fn required_byte() {
+        // Matches a run of lowercase letters followed by '@'.
+        let mut transitions: Vec<(u32, u8, u32)> = (b'a'..=b'z').map(|b| (0, b, 1)).collect();
+        transitions.extend((b'a'..=b'z').map(|b| (1, b, 1)));
+        transitions.push((1, b'@', 2));
+        let searcher = DFA::new(0, vec![2], transitions).searcher();
+        assert_eq!(searcher.prefilter, None);
+        assert_eq!(searcher.required, Some(b'@'));
+        assert_eq!(searcher.find_end(b"mail to: bob@example"), Some(13));
+        assert!(!searcher.is_match(b"no at sign here"));
+        assert!(!searcher.is_match(b"@ alone"));
+
+        // Of the bytes every match contains, the rarest in text is picked.
+        let word = DFA::new(0, vec![3], vec![(0, b'a', 1), (1, b'#', 2), (2, b'Z', 3)]);
+        let searcher = word.searcher();
+        assert_eq!(searcher.prefilter, Some(b'a'));
+        assert_eq!(searcher.required, Some(b'#'));
+        assert_eq!(searcher.find_end(b"xxa#Z"), Some(5));
+    }
+}
@@ -0,0 +1,382 @@
+use std::collections::VecDeque;
+use std::fmt::Debug;
+use std::hash::Hash;
+
+use crate::hash::{HashMap, HashSet};
+use crate::DFA;
+
+// A boolean algebra of predicates over symbols, used to label the transitions of an `SFA`.
+pub trait Predicate: Clone + Eq + Debug {
+    type Symbol;
+
+    // The predicate that holds for every symbol.
+    fn top() -> Self;
+    // The predicate that holds for no symbol.
+    fn bottom() -> Self;
+    fn and(&self, other: &Self) -> Self;
+    fn or(&self, other: &Self) -> Self;
+    fn not(&self) -> Self;
+    fn contains(&self, symbol: &Self::Symbol) -> bool;
+
+    fn is_satisfiable(&self) -> bool {
+        *self != Self::bottom()
+    }
+}
+
+// A set of bytes, as a 256-bit bitmap.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct ByteSet([u128; 2]);
+
+impl ByteSet {
+    pub fn range(lo: u8, hi: u8) -> ByteSet {
+        let mut set = ByteSet::bottom();
+        for b in lo..=hi {
+            set.0[usize::from(b >> 7)] |= 1 << (b & 127);
+        }
+        set
+    }
+}
+
+impl Predicate for ByteSet {
+    type Symbol = u8;
+
+    fn top() -> Self {
+        ByteSet([u128::MAX; 2])
+    }
+    fn bottom() -> Self {
+        ByteSet([0; 2])
+    }
+    fn and(&self, other: &Self) -> Self {
+        ByteSet([self.0[0] & other.0[0], self.0[1] & other.0[1]])
+    }
+    fn or(&self, other: &Self) -> Self {
+        ByteSet([self.0[0] | other.0[0], self.0[1] | other.0[1]])
+    }
+    fn not(&self) -> Self {
+        ByteSet([!self.0[0], !self.0[1]])
+    }
+    fn contains(&self, &b: &u8) -> bool {
+        self.0[usize::from(b >> 7)] & (1 << (b & 127)) != 0
+    }
+}
+
+// A symbolic finite automaton: a DFA whose transitions are labeled by predicates rather than by
+// individual symbols, so that huge alphabets stay manageable. The predicates on a state's
+// outgoing transitions must not overlap. A symbol that satisfies none of them is rejected.
+#[derive(Debug, Clone)]
+pub struct SFA<S, P> {
+    pub(crate) initial_state: S,
+    pub(crate) final_states: HashSet<S>,
+    pub(crate) transitions: Vec<(S, P, S)>,
+}
+
+impl<S, P> SFA<S, P>
+where
+    S: Eq + Hash + Copy + Debug,
+    P: Predicate,
+{
+    pub fn new(
+        initial_state: S,
+        final_states: impl IntoIterator<Item = S>,
+        transitions: impl IntoIterator<Item = (S, P, S)>,
+    ) -> SFA<S, P> {
+        SFA {
+            initial_state,
+            final_states: final_states.into_iter().collect(),
+            transitions: transitions.into_iter().collect(),
+        }
+    }
+
+    pub fn accepts(&self, word: &[P::Symbol]) -> bool {
+        let mut q = self.initial_state;
+        for symbol in word {
+            let next = self
+                .transitions
+                .iter()
+                .find(|(src, pred, _)| *src == q && pred.contains(symbol));
+            q = match next {
+                Some(&(_, _, dst)) => dst,
+                None => return false,
+            };
+        }
+        self.final_states.contains(&q)
+    }
+
+    // Runs both automata side by side, accepting a word when `accept` says so given whether each
+    // automaton accepts it. For example, `|a, b| a && !b` gives the difference.
+    pub fn product<S2>(
+        &self,
+        other: &SFA<S2, P>,
+        accept: impl Fn(bool, bool) -> bool,
+    ) -> SFA<u32, P>
+    where
+        S2: Eq + Hash + Copy + Debug,
+    {
+        let lhs = self.complete();
+        let rhs = other.complete();
+        let lhs_out = outflows(&lhs.transitions);
+        let rhs_out = outflows(&rhs.transitions);
+
+        let start = (lhs.initial_state, rhs.initial_state);
+        let mut ids: HashMap<(u32, u32), u32> = HashMap::default();
+        let mut queue = VecDeque::new();
+        let mut final_states = Vec::new();
+        let mut transitions = Vec::new();
+        ids.insert(start, 0);
+        queue.push_back(start);
+        while let Some((p, q)) = queue.pop_front() {
+            let src = ids[&(p, q)];
+            if accept(lhs.final_states.contains(&p), rhs.final_states.contains(&q)) {
+                final_states.push(src);
+            }
+            for (a, p2) in lhs_out.get(&p).into_iter().flatten() {
+                for (b, q2) in rhs_out.get(&q).into_iter().flatten() {
+                    let both = a.and(b);
+                    if !both.is_satisfiable() {
+                        continue;
+                    }
+                    let next = (*p2, *q2);
+                    let n = ids.len() as u32;
+                    let dst = *ids.entry(next).or_insert_with(|| {
+                        queue.push_back(next);
+                        n
+                    });
+                    transitions.push((src, both, dst));
+                }
+            }
+        }
+        SFA::new(0, final_states, transitions)
+    }
+
+    pub fn intersection<S2>(&self, other: &SFA<S2, P>) -> SFA<u32, P>
+    where
+        S2: Eq + Hash + Copy + Debug,
+    {
+        self.product(other, |a, b| a && b)
+    }
+
+    pub fn union<S2>(&self, other: &SFA<S2, P>) -> SFA<u32, P>
+    where
+        S2: Eq + Hash + Copy + Debug,
+    {
+        self.product(other, |a, b| a || b)
+    }
+
+    // Accepts exactly the words that this rejects.
+    pub fn complement(&self) -> SFA<u32, P> {
+        let mut complete = self.complete();
+        let states: HashSet<u32> = complete
+            .transitions
+            .iter()
+            .flat_map(|&(src, _, dst)| vec![src, dst])
+            .collect();
+        complete.final_states = states.difference(&complete.final_states).copied().collect();
+        complete
+    }
+
+    // Builds the minimal SFA for the same language. The predicates are split into minterms (the
+    // satisfiable regions that no predicate cuts in two), each minterm is treated as a plain
+    // symbol so that `DFA::minimize` applies, and each state's minterms are then merged back per
+    // destination. States that cannot lead to acceptance are dropped.
+    pub fn minimize(&self) -> SFA<u32, P> {
+        let (dfa, minterms) = self.to_dfa();
+        let minimal = match dfa.prune_unreachable() {
+            None => return SFA::new(0, vec![], vec![]),
+            Some(pruned) => pruned.minimize(),
+        };
+
+        // Number the states in the order a breadth-first search reaches them, taking minterms in
+        // order, so that the result is the same whichever states `minimize` kept.
+        let mut outflows = minimal.transitions.by_a();
+        let mut ids: HashMap<S, u32> = HashMap::default();
+        let mut queue = VecDeque::from([minimal.initial_state]);
+        ids.insert(minimal.initial_state, 0);
+        while let Some(src) = queue.pop_front() {
+            let edges = outflows.entry(src).or_default();
+            edges.sort_unstable_by_key(|&(i, _)| i);
+            for &(_, dst) in edges.iter() {
+                let n = ids.len() as u32;
+                ids.entry(dst).or_insert_with(|| {
+                    queue.push_back(dst);
+                    n
+                });
+            }
+        }
+        let mut merged: HashMap<(u32, u32), P> = HashMap::default();
+        for (src, i, dst) in minimal.transitions {
+            let pred = merged
+                .entry((ids[&src], ids[&dst]))
+                .or_insert_with(P::bottom);
+            *pred = pred.or(&minterms[i]);
+        }
+        let mut transitions: Vec<(u32, P, u32)> = merged
+            .into_iter()
+            .map(|((src, dst), pred)| (src, pred, dst))
+            .collect();
+        transitions.sort_unstable_by_key(|&(src, _, dst)| (src, dst));
+        SFA::new(0, minimal.final_states.iter().map(|q| ids[q]), transitions)
+    }
+
+    // An equivalent DFA over minterms: the symbol `i` stands for every symbol in the `i`th of the
+    // returned minterms, which cover all symbols between them.
+    pub fn to_dfa(&self) -> (DFA<S, usize>, Vec<P>) {
+        let minterms = minterms(self.transitions.iter().map(|(_, pred, _)| pred));
+        let mut transitions = Vec::new();
+        for (src, pred, dst) in &self.transitions {
+            for (i, minterm) in minterms.iter().enumerate() {
+                if minterm.and(pred).is_satisfiable() {
+                    transitions.push((*src, i, *dst));
+                }
+            }
+        }
+        let dfa = DFA::new(
+            self.initial_state,
+            self.final_states.iter().copied(),
+            transitions,
+        );
+        (dfa, minterms)
+    }
+
+    // Renumbers the states from 0, and adds a rejecting sink state so that every state has a
+    // transition for every symbol.
+    fn complete(&self) -> SFA<u32, P> {
+        let mut ids: HashMap<S, u32> = HashMap::default();
+        let mut id = |q: S| {
+            let n = ids.len() as u32;
+            *ids.entry(q).or_insert(n)
+        };
+        let initial_state = id(self.initial_state);
+        let mut transitions: Vec<(u32, P, u32)> = self
+            .transitions
+            .iter()
+            .map(|(src, pred, dst)| (id(*src), pred.clone(), id(*dst)))
+            .collect();
+        let final_states: HashSet<u32> = self.final_states.iter().map(|&q| id(q)).collect();
+
+        let sink = ids.len() as u32;
+        let mut covered: Vec<P> = vec![P::bottom(); sink as usize + 1];
+        for (src, pred, _) in &transitions {
+            covered[*src as usize] = covered[*src as usize].or(pred);
+        }
+        for (q, pred) in covered.into_iter().enumerate() {
+            let rest = pred.not();
+            if rest.is_satisfiable() {
+                transitions.push((q as u32, rest, sink));
+            }
+        }
+        SFA {
+            initial_state,
+            final_states,
+            transitions,
+        }
+    }
+}
+
+fn outflows<P: Clone>(transitions: &[(u32, P, u32)]) -> HashMap<u32, Vec<(P, u32)>> {
+    let mut outflows: HashMap<u32, Vec<(P, u32)>> = HashMap::default();
+    for (src, pred, dst) in transitions {
+        outflows.entry(*src).or_default().push((pred.clone(), *dst));
+    }
+    outflows
+}
+
+// Splits the symbols into the coarsest regions that every predicate either contains entirely or
+// avoids entirely.
+pub(crate) fn minterms<'a, P: Predicate + 'a>(predicates: impl Iterator<Item = &'a P>) -> Vec<P> {
+    let mut minterms = vec![P::top()];
+    let mut seen = Vec::new();
+    for pred in predicates {
+        if seen.contains(pred) {
+            continue;
+        }
+        seen.push(pred.clone());
+        let negated = pred.not();
+        minterms = minterms
+            .into_iter()
+            .flat_map(|m| vec![m.and(pred), m.and(&negated)])
+            .filter(|m| m.is_satisfiable())
+            .collect();
+    }
+    minterms
+}
+
+#[cfg(test)]
+mod test {
+    use super::{ByteSet, Predicate, SFA};
+
+    // Accepts identifiers: a lowercase letter followed by lowercase letters and digits.
+    fn identifier() -> SFA<u32, ByteSet> {
+        let letter = ByteSet::range(b'a', b'z');
+        let alnum = letter.or(&ByteSet::range(b'0', b'9'));
+        SFA::new(0, vec![1], vec![(0, letter, 1), (1, alnum, 1)])
+    }
+
+    #[test]
+    fn to_dfa_over_minterms() {
+        let (dfa, minterms) = identifier().to_dfa();
+        assert_eq!(minterms.len(), 3);
+        let symbol = |b: u8| minterms.iter().position(|m| m.contains(&b)).unwrap();
+        assert!(dfa.accepts(&[symbol(b'x'), symbol(b'9')]));
+        assert!(!dfa.accepts(&[symbol(b'9')]));
+        assert!(!dfa.accepts(&[symbol(b'x'), symbol(b'-')]));
+    }
+
+    #[test]
+    fn boolean_operations() {
+        let ident = identifier();
+        assert!(ident.accepts(b"abc1"));
+        assert!(!ident.accepts(b"1abc"));
+        assert!(!ident.accepts(b""));
+
+        let complement = ident.complement();
+        assert!(!complement.accepts(b"abc1"));
+        assert!(complement.accepts(b"1abc"));
+        assert!(complement.accepts(b""));
+
+        // Keywords are identifiers too.
+        let keyword: SFA<u32, ByteSet> = SFA::new(
+            0,
+            vec![2],
+            vec![
+                (0, ByteSet::range(b'i', b'i'), 1),
+                (1, ByteSet::range(b'f', b'f'), 2),
+            ],
+        );
+        let both = ident.intersection(&keyword);
+        assert!(both.accepts(b"if"));
+        assert!(!both.accepts(b"ifx"));
+        let non_keywords = ident.product(&keyword, |a, b| a && !b);
+        assert!(non_keywords.accepts(b"ifx"));
+        assert!(!non_keywords.accepts(b"if"));
+        assert!(ident.union(&keyword).accepts(b"x9"));
+    }
+
+    #[test]
+    fn minimize_merges_predicates() {
+        // The same identifier language, with the second state needlessly duplicated and the
+        // alphanumeric predicate split into pieces.
+        let letter = ByteSet::range(b'a', b'z');
+        let digit = ByteSet::range(b'0', b'9');
+        let sfa: SFA<u32, ByteSet> = SFA::new(
+            0,
+            vec![1, 2],
+            vec![
+                (0, letter, 1),
+                (1, letter, 2),
+                (1, digit, 1),
+                (2, digit, 2),
+                (2, letter, 1),
+            ],
+        );
+        let minimal = sfa.minimize();
+        assert_eq!(minimal.transitions.len(), 2);
+        assert!(minimal.accepts(b"a1b2"));
+        assert!(!minimal.accepts(b"1a"));
+        let self_loop = minimal
+            .transitions
+            .iter()
+            .find(|(src, _, dst)| src == dst)
+            .unwrap();
+        assert_eq!(self_loop.1, letter.or(&digit));
+    }
+}
@@ -0,0 +1,144 @@
+use std::fmt::Debug;
+use std::hash::Hash;
+
+use crate::hash::{HashMap, HashSet, IntoStd};
+use crate::nfa::NFA;
+
+impl<S, E> NFA<S, E>
+where
+    S: Eq + Hash + Copy + Debug,
+    E: Eq + Hash + Copy + Debug,
+{
+    // Computes the forward simulation preorder: every pair `(q, p)` such that `p` simulates `q`.
+    // That is, if `q` accepts then so does `p`, and every transition out of `q` can be matched by
+    // a transition out of `p` with the same label, into a state that simulates where `q` went.
+    // Whatever `q` accepts, `p` accepts too. Epsilon transitions are matched like any other
+    // label, so this is most precise on epsilon-free NFAs.
+    pub fn simulation(&self) -> std::collections::HashSet<(S, S)> {
+        self.simulation_with(self)
+    }
+
+    // Like `simulation`, but between the states of two automata: every pair `(q, p)` such that
+    // `p` (a state of `other`) simulates `q` (a state of this one).
+    pub fn simulation_with<S2>(&self, other: &NFA<S2, E>) -> std::collections::HashSet<(S, S2)>
+    where
+        S2: Eq + Hash + Copy + Debug,
+    {
+        let lhs = self.transitions.by_a();
+        let rhs = other.transitions.by_a();
+        let mut relation: HashSet<(S, S2)> = HashSet::default();
+        for q in self.states() {
+            for p in other.states() {
+                if !self.final_states.contains(&q) || other.final_states.contains(&p) {
+                    relation.insert((q, p));
+                }
+            }
+        }
+        // Remove pairs that break the transfer condition until none do.
+        loop {
+            let broken: Vec<(S, S2)> = relation
+                .iter()
+                .copied()
+                .filter(|(q, p)| {
+                    lhs.get(q).into_iter().flatten().any(|(label, q2)| {
+                        !rhs.get(p)
+                            .into_iter()
+                            .flatten()
+                            .any(|(l, p2)| l == label && relation.contains(&(*q2, *p2)))
+                    })
+                })
+                .collect();
+            if broken.is_empty() {
+                return relation.into_std();
+            }
+            for pair in broken {
+                relation.remove(&pair);
+            }
+        }
+    }
+
+    // Merges every set of states that simulate each other, which keeps the language the same.
+    // This is much cheaper than determinizing, and often shrinks an NFA a lot first.
+    pub fn reduce_by_simulation(&self) -> NFA<S, E> {
+        let relation = self.simulation();
+        let mut representative: HashMap<S, S> = HashMap::default();
+        let mut representatives: Vec<S> = Vec::new();
+        for q in self.states() {
+            let r = representatives
+                .iter()
+                .copied()
+                .find(|&r| relation.contains(&(q, r)) && relation.contains(&(r, q)));
+            match r {
+                Some(r) => {
+                    representative.insert(q, r);
+                }
+                None => {
+                    representative.insert(q, q);
+                    representatives.push(q);
+                }
+            }
+        }
+        let mut transitions: Vec<(S, Option<E>, S)> = (&self.transitions)
+            .into_iter()
+            .map(|&(src, label, dst)| (representative[&src], label, representative[&dst]))
+            .collect();
+        let mut seen = HashSet::default();
+        transitions.retain(|t| seen.insert(*t));
+        NFA::with_initial_states(
+            self.initial_states.iter().map(|q| representative[q]),
+            self.final_states.iter().map(|q| representative[q]),
+            transitions,
+        )
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::nfa::NFA;
+
+    #[test]
+    fn simulation_preorder() {
+        // Both 1 and 2 accept a+, but 3 only accepts "a".
+        let nfa: NFA<u32, char> = NFA::new(
+            0,
+            vec![1, 2, 3],
+            vec![
+                (0, Some('a'), 1),
+                (0, Some('a'), 2),
+                (0, Some('a'), 3),
+                (1, Some('a'), 1),
+                (2, Some('a'), 1),
+            ],
+        );
+        let relation = nfa.simulation();
+        assert!(relation.contains(&(1, 2)));
+        assert!(relation.contains(&(2, 1)));
+        assert!(relation.contains(&(3, 1)));
+        assert!(!relation.contains(&(1, 3)));
+        assert!(relation.contains(&(0, 1)));
+        assert!(!relation.contains(&(1, 0)));
+
+        let reduced = nfa.reduce_by_simulation();
+        assert_eq!(reduced.states().len(), 3);
+        assert!(reduced.accepts(&['a', 'a', 'a']));
+        assert!(!reduced.accepts(&[]));
+    }
+
+    #[test]
+    fn simulation_between_automata() {
+        // "ab" is simulated by a+b+, but not the other way around.
+        let ab: NFA<u32, char> = NFA::new(0, vec![2], vec![(0, Some('a'), 1), (1, Some('b'), 2)]);
+        let plus: NFA<&str, char> = NFA::new(
+            "start",
+            vec!["b"],
+            vec![
+                ("start", Some('a'), "a"),
+                ("a", Some('a'), "a"),
+                ("a", Some('b'), "b"),
+                ("b", Some('b'), "b"),
+            ],
+        );
+        assert!(ab.simulation_with(&plus).contains(&(0, "start")));
+        assert!(!plus.simulation_with(&ab).contains(&("start", 0)));
+    }
+}
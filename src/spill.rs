@@ -0,0 +1,454 @@
+// Tables too big to hold in memory, kept on disk as sorted runs and merged on the way back in.
+// Tuples are pushed into a `Spiller`, which sorts each batch of `run_len` of them and writes it to
+// its own file. When it finishes, it merges runs into longer ones, `fan_in` at a time, until no
+// more than `fan_in` are left, so that no more files than that are ever open at once. The
+// resulting `SpilledTable` merges those, so that tuples come back sorted and distinct, and can be
+// grouped by their first column while only holding one tuple per run (and one group) in memory.
+// To group by another column, spill the tuples with that column first.
+
+use std::cmp::Reverse;
+use std::collections::BinaryHeap;
+use std::fmt::Debug;
+use std::fs::{self, File};
+use std::hash::Hash;
+use std::io::{self, BufRead, BufReader, BufWriter, Read, Write};
+use std::iter::Peekable;
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+use crate::hash::HashSet;
+use crate::DFA;
+
+// How many runs a `Spiller` merges at once, unless told otherwise: well under the usual limit
+// on open files.
+const FAN_IN: usize = 64;
+
+// A value with a fixed binary encoding, so that it can be written to a run and read back.
+pub trait Record: Sized + Copy {
+    fn write_to(&self, out: &mut impl Write) -> io::Result<()>;
+    fn read_from(input: &mut impl Read) -> io::Result<Self>;
+}
+
+macro_rules! int_record {
+    ($($t:ty),*) => {$(
+        impl Record for $t {
+            fn write_to(&self, out: &mut impl Write) -> io::Result<()> {
+                out.write_all(&self.to_le_bytes())
+            }
+            fn read_from(input: &mut impl Read) -> io::Result<Self> {
+                let mut bytes = [0; std::mem::size_of::<$t>()];
+                input.read_exact(&mut bytes)?;
+                Ok(<$t>::from_le_bytes(bytes))
+            }
+        }
+    )*};
+}
+int_record!(u8, u16, u32, u64, usize, i32, i64);
+
+impl Record for char {
+    fn write_to(&self, out: &mut impl Write) -> io::Result<()> {
+        u32::from(*self).write_to(out)
+    }
+    fn read_from(input: &mut impl Read) -> io::Result<Self> {
+        let n = u32::read_from(input)?;
+        char::from_u32(n).ok_or_else(|| io::Error::new(io::ErrorKind::InvalidData, "not a char"))
+    }
+}
+
+impl<A: Record, B: Record, C: Record> Record for (A, B, C) {
+    fn write_to(&self, out: &mut impl Write) -> io::Result<()> {
+        self.0.write_to(out)?;
+        self.1.write_to(out)?;
+        self.2.write_to(out)
+    }
+    fn read_from(input: &mut impl Read) -> io::Result<Self> {
+        Ok((
+            A::read_from(input)?,
+            B::read_from(input)?,
+            C::read_from(input)?,
+        ))
+    }
+}
+
+// Tells apart the runs of every spiller in this process.
+static NEXT_RUN: AtomicUsize = AtomicUsize::new(0);
+
+// Collects tuples into sorted runs on disk.
+pub struct Spiller<A, B, C> {
+    dir: PathBuf,
+    run_len: usize,
+    fan_in: usize,
+    buffer: Vec<(A, B, C)>,
+    runs: Vec<PathBuf>,
+}
+
+impl<A, B, C> Spiller<A, B, C>
+where
+    A: Record + Ord,
+    B: Record + Ord,
+    C: Record + Ord,
+{
+    // Writes runs of up to `run_len` tuples into `dir`, which must exist.
+    pub fn new(dir: impl Into<PathBuf>, run_len: usize) -> Spiller<A, B, C> {
+        Spiller::with_fan_in(dir, run_len, FAN_IN)
+    }
+
+    // Like `new`, but merges `fan_in` runs at a time instead of the default 64.
+    pub fn with_fan_in(dir: impl Into<PathBuf>, run_len: usize, fan_in: usize) -> Spiller<A, B, C> {
+        assert!(run_len > 0, "runs must hold at least one tuple");
+        assert!(fan_in > 1, "merging must combine at least two runs");
+        Spiller {
+            dir: dir.into(),
+            run_len,
+            fan_in,
+            buffer: Vec::new(),
+            runs: Vec::new(),
+        }
+    }
+
+    pub fn push(&mut self, tuple: (A, B, C)) -> io::Result<()> {
+        self.buffer.push(tuple);
+        if self.buffer.len() >= self.run_len {
+            self.flush()?;
+        }
+        Ok(())
+    }
+
+    pub fn finish(mut self) -> io::Result<SpilledTable<A, B, C>> {
+        self.flush()?;
+        while self.runs.len() > self.fan_in {
+            let runs = std::mem::take(&mut self.runs);
+            let merged = runs
+                .chunks(self.fan_in)
+                .try_for_each(|chunk| self.write_run(merge(chunk)?));
+            remove(&runs);
+            merged?;
+        }
+        Ok(SpilledTable {
+            runs: std::mem::take(&mut self.runs),
+            tuples: std::marker::PhantomData,
+        })
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        if self.buffer.is_empty() {
+            return Ok(());
+        }
+        let mut buffer = std::mem::take(&mut self.buffer);
+        buffer.sort_unstable();
+        buffer.dedup();
+        self.write_run(buffer.into_iter().map(Ok))
+    }
+
+    // Writes sorted tuples to a new run.
+    fn write_run(&mut self, tuples: impl Iterator<Item = io::Result<(A, B, C)>>) -> io::Result<()> {
+        let id = NEXT_RUN.fetch_add(1, Ordering::Relaxed);
+        let path = self
+            .dir
+            .join(format!("dfa-utils-{}-{}.run", std::process::id(), id));
+        let mut out = BufWriter::new(File::create(&path)?);
+        self.runs.push(path);
+        for tuple in tuples {
+            tuple?.write_to(&mut out)?;
+        }
+        out.flush()
+    }
+}
+
+impl<A, B, C> Drop for Spiller<A, B, C> {
+    fn drop(&mut self) {
+        remove(&self.runs);
+    }
+}
+
+// The sorted runs written by a `Spiller`, which are deleted when this is dropped.
+pub struct SpilledTable<A, B, C> {
+    runs: Vec<PathBuf>,
+    tuples: std::marker::PhantomData<(A, B, C)>,
+}
+
+impl<A, B, C> SpilledTable<A, B, C>
+where
+    A: Record + Ord,
+    B: Record + Ord,
+    C: Record + Ord,
+{
+    // Every tuple, sorted and without repeats.
+    pub fn iter(&self) -> io::Result<Merge<A, B, C>> {
+        merge(&self.runs)
+    }
+
+    // The runs of tuples that share a first column, in order, along with that column, like
+    // `SortedTable::groups`.
+    pub fn groups(&self) -> io::Result<Groups<A, B, C>> {
+        Ok(Groups {
+            tuples: self.iter()?.peekable(),
+        })
+    }
+}
+
+impl<S, E> SpilledTable<S, E, S>
+where
+    S: Record + Ord + Hash + Debug,
+    E: Record + Ord + Hash + Debug,
+{
+    // Loads the transitions of a DFA, spilled as `(src, label, dst)`, keeping only the ones
+    // `DFA::prune_unreachable` would keep, or `None` if the language is empty. The states to keep
+    // are found by streaming `groups` until nothing changes, once per step of the longest
+    // shortest path, so only sets of states are held in memory until the pruned DFA is built.
+    pub fn prune(
+        &self,
+        initial_state: S,
+        final_states: impl IntoIterator<Item = S>,
+    ) -> io::Result<Option<DFA<S, E>>> {
+        let mut reachable: HashSet<S> = HashSet::default();
+        reachable.insert(initial_state);
+        self.until_stable(&mut reachable, |reachable, src, edges| {
+            if reachable.contains(&src) {
+                reachable.extend(edges.iter().map(|&(_, dst)| dst));
+            }
+        })?;
+        let final_states: Vec<S> = final_states
+            .into_iter()
+            .filter(|q| reachable.contains(q))
+            .collect();
+        let mut live: HashSet<S> = final_states.iter().copied().collect();
+        self.until_stable(&mut live, |live, src, edges| {
+            if reachable.contains(&src) && edges.iter().any(|(_, dst)| live.contains(dst)) {
+                live.insert(src);
+            }
+        })?;
+        if !live.contains(&initial_state) {
+            return Ok(None);
+        }
+
+        let mut transitions = Vec::new();
+        for group in self.groups()? {
+            let (src, edges) = group?;
+            if live.contains(&src) {
+                let kept = edges.into_iter().filter(|(_, dst)| live.contains(dst));
+                transitions.extend(kept.map(|(label, dst)| (src, label, dst)));
+            }
+        }
+        Ok(Some(DFA::new(
+            initial_state,
+            final_states.into_iter().filter(|q| live.contains(q)),
+            transitions,
+        )))
+    }
+
+    // Streams `groups` through `step` until a pass leaves `states` the same size.
+    fn until_stable(
+        &self,
+        states: &mut HashSet<S>,
+        step: impl Fn(&mut HashSet<S>, S, &[(E, S)]),
+    ) -> io::Result<()> {
+        loop {
+            let before = states.len();
+            for group in self.groups()? {
+                let (src, edges) = group?;
+                step(states, src, &edges);
+            }
+            if states.len() == before {
+                return Ok(());
+            }
+        }
+    }
+}
+
+impl<A, B, C> Drop for SpilledTable<A, B, C> {
+    fn drop(&mut self) {
+        remove(&self.runs);
+    }
+}
+
+// Opens every run in `runs` and merges them.
+fn merge<A, B, C>(runs: &[PathBuf]) -> io::Result<Merge<A, B, C>>
+where
+    A: Record + Ord,
+    B: Record + Ord,
+    C: Record + Ord,
+{
+    let mut readers = Vec::new();
+    let mut heap = BinaryHeap::new();
+    for (i, path) in runs.iter().enumerate() {
+        let mut reader = BufReader::new(File::open(path)?);
+        if let Some(tuple) = read_next(&mut reader)? {
+            heap.push(Reverse((tuple, i)));
+        }
+        readers.push(reader);
+    }
+    Ok(Merge {
+        readers,
+        heap,
+        last: None,
+    })
+}
+
+fn remove(runs: &[PathBuf]) {
+    for path in runs {
+        // There is nothing useful to do if this fails, and the file is in a scratch directory.
+        let _ = fs::remove_file(path);
+    }
+}
+
+// Reads the next tuple of a run, or `None` at its end.
+fn read_next<T: Record>(reader: &mut BufReader<File>) -> io::Result<Option<T>> {
+    if reader.fill_buf()?.is_empty() {
+        return Ok(None);
+    }
+    T::read_from(reader).map(Some)
+}
+
+// The next tuple of each run, smallest first, along with the run it came from.
+type Heads<A, B, C> = BinaryHeap<Reverse<((A, B, C), usize)>>;
+
+// A merge of sorted runs.
+pub struct Merge<A, B, C> {
+    readers: Vec<BufReader<File>>,
+    heap: Heads<A, B, C>,
+    last: Option<(A, B, C)>,
+}
+
+impl<A, B, C> Iterator for Merge<A, B, C>
+where
+    A: Record + Ord,
+    B: Record + Ord,
+    C: Record + Ord,
+{
+    type Item = io::Result<(A, B, C)>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        while let Some(Reverse((tuple, i))) = self.heap.pop() {
+            match read_next(&mut self.readers[i]) {
+                Ok(Some(next)) => self.heap.push(Reverse((next, i))),
+                Ok(None) => {}
+                Err(e) => return Some(Err(e)),
+            }
+            // The same tuple may be in several runs.
+            if self.last != Some(tuple) {
+                self.last = Some(tuple);
+                return Some(Ok(tuple));
+            }
+        }
+        None
+    }
+}
+
+pub struct Groups<A, B, C>
+where
+    A: Record + Ord,
+    B: Record + Ord,
+    C: Record + Ord,
+{
+    tuples: Peekable<Merge<A, B, C>>,
+}
+
+impl<A, B, C> Iterator for Groups<A, B, C>
+where
+    A: Record + Ord,
+    B: Record + Ord,
+    C: Record + Ord,
+{
+    type Item = io::Result<(A, Vec<(B, C)>)>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let (a, b, c) = match self.tuples.next()? {
+            Ok(tuple) => tuple,
+            Err(e) => return Some(Err(e)),
+        };
+        let mut group = vec![(b, c)];
+        while let Some(Ok((next, _, _))) = self.tuples.peek() {
+            if *next != a {
+                break;
+            }
+            let (_, b, c) = self.tuples.next().unwrap().unwrap();
+            group.push((b, c));
+        }
+        Some(Ok((a, group)))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::Spiller;
+    use crate::DFA;
+
+    #[test]
+    fn merge_runs() {
+        let dir = std::env::temp_dir();
+        let mut spiller = Spiller::new(&dir, 3);
+        let tuples = [
+            (2u32, 'b', 0u32),
+            (0, 'a', 1),
+            (2, 'a', 1),
+            (1, 'a', 2),
+            (0, 'a', 1),
+            (0, 'b', 2),
+            (2, 'a', 1),
+        ];
+        for &tuple in &tuples {
+            spiller.push(tuple).unwrap();
+        }
+        let table = spiller.finish().unwrap();
+        assert_eq!(table.runs.len(), 3);
+
+        let mut expected = tuples.to_vec();
+        expected.sort_unstable();
+        expected.dedup();
+        let merged: Vec<_> = table.iter().unwrap().map(Result::unwrap).collect();
+        assert_eq!(merged, expected);
+
+        let groups: Vec<_> = table.groups().unwrap().map(Result::unwrap).collect();
+        assert_eq!(
+            groups,
+            vec![
+                (0, vec![('a', 1), ('b', 2)]),
+                (1, vec![('a', 2)]),
+                (2, vec![('a', 1), ('b', 0)]),
+            ]
+        );
+
+        let runs = table.runs.clone();
+        drop(table);
+        assert!(runs.iter().all(|path| !path.exists()));
+    }
+
+    #[test]
+    fn merge_in_passes() {
+        let dir = std::env::temp_dir();
+        let mut spiller = Spiller::with_fan_in(&dir, 1, 2);
+        let tuples: Vec<(u32, u8, u32)> = (0..9).rev().map(|i| (i % 4, 0, i)).collect();
+        for &tuple in &tuples {
+            spiller.push(tuple).unwrap();
+        }
+        let table = spiller.finish().unwrap();
+        assert!(table.runs.len() <= 2);
+
+        let mut expected = tuples;
+        expected.sort_unstable();
+        let merged: Vec<_> = table.iter().unwrap().map(Result::unwrap).collect();
+        assert_eq!(merged, expected);
+    }
+
+    #[test]
+    fn prune() {
+        // 0 -a-> 1 -a-> 2, where 2 accepts; 1 -b-> 3 is dead and 4 -a-> 2 is unreachable.
+        let transitions = [
+            (0u32, 'a', 1u32),
+            (1, 'a', 2),
+            (1, 'b', 3),
+            (3, 'a', 3),
+            (4, 'a', 2),
+        ];
+        let dfa = DFA::new(0, vec![2], transitions.to_vec());
+        let mut spiller = Spiller::new(std::env::temp_dir(), 2);
+        for &tuple in transitions.iter().rev() {
+            spiller.push(tuple).unwrap();
+        }
+        let table = spiller.finish().unwrap();
+        let pruned = table.prune(0, vec![2]).unwrap().unwrap();
+        assert_eq!(pruned, dfa.prune_unreachable().unwrap());
+        assert!(table.prune(0, vec![4]).unwrap().is_none());
+    }
+}
@@ -0,0 +1,219 @@
+use std::cell::OnceCell;
+use std::fmt::Debug;
+use std::hash::Hash;
+
+use crate::hash::HashMap;
+use crate::DFA;
+
+struct State<E> {
+    // The length of the longest substring that leads to this state.
+    len: usize,
+    // The suffix link. Only the root has none.
+    link: Option<usize>,
+    next: HashMap<E, usize>,
+    // Clones are created when splitting a state, and do not mark the end of a new prefix.
+    cloned: bool,
+}
+
+// The suffix automaton (a.k.a. DAWG) of a text: the minimal DFA that accepts every suffix of the
+// text. Every substring of the text can be read from the initial state, so it doubles as an index
+// for substring queries. Symbols can be appended one at a time.
+pub struct SuffixAutomaton<E> {
+    states: Vec<State<E>>,
+    // The state reached by the whole text so far.
+    last: usize,
+    // The number of times each state's substrings occur in the text, computed on demand.
+    occurrences: OnceCell<Vec<usize>>,
+}
+
+impl<E> SuffixAutomaton<E>
+where
+    E: Eq + Hash + Copy + Debug,
+{
+    pub fn new() -> SuffixAutomaton<E> {
+        SuffixAutomaton {
+            states: vec![State {
+                len: 0,
+                link: None,
+                next: HashMap::default(),
+                cloned: false,
+            }],
+            last: 0,
+            occurrences: OnceCell::new(),
+        }
+    }
+
+    pub fn from_text(text: impl IntoIterator<Item = E>) -> SuffixAutomaton<E> {
+        let mut automaton = SuffixAutomaton::new();
+        for symbol in text {
+            automaton.push(symbol);
+        }
+        automaton
+    }
+
+    // Appends `symbol` to the text. This takes amortized constant time.
+    pub fn push(&mut self, symbol: E) {
+        self.occurrences = OnceCell::new();
+        let cur = self.states.len();
+        self.states.push(State {
+            len: self.states[self.last].len + 1,
+            link: None,
+            next: HashMap::default(),
+            cloned: false,
+        });
+
+        let mut p = Some(self.last);
+        while let Some(q) = p {
+            if self.states[q].next.contains_key(&symbol) {
+                break;
+            }
+            self.states[q].next.insert(symbol, cur);
+            p = self.states[q].link;
+        }
+        self.states[cur].link = Some(match p {
+            None => 0,
+            Some(p) => {
+                let q = self.states[p].next[&symbol];
+                if self.states[p].len + 1 == self.states[q].len {
+                    q
+                } else {
+                    let clone = self.states.len();
+                    self.states.push(State {
+                        len: self.states[p].len + 1,
+                        link: self.states[q].link,
+                        next: self.states[q].next.clone(),
+                        cloned: true,
+                    });
+                    let mut p = Some(p);
+                    while let Some(r) = p {
+                        if self.states[r].next.get(&symbol) != Some(&q) {
+                            break;
+                        }
+                        self.states[r].next.insert(symbol, clone);
+                        p = self.states[r].link;
+                    }
+                    self.states[q].link = Some(clone);
+                    clone
+                }
+            }
+        });
+        self.last = cur;
+    }
+
+    // The length of the text.
+    pub fn len(&self) -> usize {
+        self.states[self.last].len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    // Whether `pattern` is a substring of the text.
+    pub fn contains(&self, pattern: &[E]) -> bool {
+        self.find(pattern).is_some()
+    }
+
+    // Counts the (possibly overlapping) occurrences of `pattern` in the text. The empty pattern
+    // occurs once at every position, including the end.
+    // The first query after the text changes takes time linear in the length of the text.
+    pub fn occurrences(&self, pattern: &[E]) -> usize {
+        if pattern.is_empty() {
+            return self.len() + 1;
+        }
+        match self.find(pattern) {
+            Some(q) => self.occurrences.get_or_init(|| self.count_occurrences())[q],
+            None => 0,
+        }
+    }
+
+    // Converts this into a plain DFA accepting every suffix of the text. States are numbered as
+    // they are internally, so the initial state is 0.
+    pub fn to_dfa(&self) -> DFA<u32, E> {
+        let mut final_states = Vec::new();
+        let mut p = Some(self.last);
+        while let Some(q) = p {
+            final_states.push(q as u32);
+            p = self.states[q].link;
+        }
+        let transitions = self.states.iter().enumerate().flat_map(|(src, state)| {
+            state
+                .next
+                .iter()
+                .map(move |(&label, &dst)| (src as u32, label, dst as u32))
+        });
+        DFA::new(0, final_states, transitions)
+    }
+
+    fn find(&self, pattern: &[E]) -> Option<usize> {
+        let mut q = 0;
+        for symbol in pattern {
+            q = *self.states[q].next.get(symbol)?;
+        }
+        Some(q)
+    }
+
+    fn count_occurrences(&self) -> Vec<usize> {
+        let mut counts: Vec<usize> = self
+            .states
+            .iter()
+            .map(|state| if state.cloned { 0 } else { 1 })
+            .collect();
+        // Every state's end positions are inherited by its suffix link, so propagate counts from
+        // the longest states to the shortest.
+        let mut order: Vec<usize> = (1..self.states.len()).collect();
+        order.sort_unstable_by_key(|&q| std::cmp::Reverse(self.states[q].len));
+        for q in order {
+            if let Some(link) = self.states[q].link {
+                counts[link] += counts[q];
+            }
+        }
+        counts
+    }
+}
+
+impl<E> Default for SuffixAutomaton<E>
+where
+    E: Eq + Hash + Copy + Debug,
+{
+    fn default() -> Self {
+        SuffixAutomaton::new()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::SuffixAutomaton;
+
+    #[test]
+    fn substring_queries() {
+        let text: Vec<char> = "abcbcab".chars().collect();
+        let automaton = SuffixAutomaton::from_text(text.iter().copied());
+        for i in 0..=text.len() {
+            for j in i..=text.len() {
+                let pattern = &text[i..j];
+                let expected = (0..=text.len() - pattern.len())
+                    .filter(|&k| &text[k..k + pattern.len()] == pattern)
+                    .count();
+                assert_eq!(automaton.occurrences(pattern), expected, "{:?}", pattern);
+                assert!(automaton.contains(pattern));
+            }
+        }
+        assert!(!automaton.contains(&['c', 'c']));
+        assert_eq!(automaton.occurrences(&['b', 'a']), 0);
+    }
+
+    #[test]
+    fn to_dfa_is_minimal() {
+        let automaton = SuffixAutomaton::from_text("abcbcab".chars());
+        let dfa = automaton.to_dfa();
+        let suffixes: Vec<String> = dfa.words(None).map(|w| w.into_iter().collect()).collect();
+        assert_eq!(
+            suffixes,
+            vec!["", "b", "ab", "cab", "bcab", "cbcab", "bcbcab", "abcbcab"]
+        );
+
+        let minified = dfa.clone().prune_unreachable().unwrap().minimize();
+        assert_eq!(minified.transitions.len(), dfa.transitions.len());
+    }
+}
@@ -0,0 +1,117 @@
+use std::collections::{hash_map::Entry, VecDeque};
+use std::fmt::Debug;
+use std::hash::Hash;
+
+use crate::hash::{HashMap, HashSet};
+use crate::DFA;
+
+type Pair<S> = (S, S);
+
+impl<S, E> DFA<S, E>
+where
+    S: Eq + Hash + Copy + Debug,
+    E: Eq + Hash + Copy + Debug,
+{
+    // Finds a reset word: a word that drives every state to the same state. This uses the greedy
+    // pair-merging algorithm, so the word is not necessarily the shortest one.
+    // Returns `None` if the DFA is not complete (some state is missing a transition for some
+    // label), or if no such word exists.
+    pub fn synchronizing_word(&self) -> Option<Vec<E>> {
+        let delta = self.delta();
+        let states = self.states();
+        let alphabet: HashSet<E> = self.transitions.by_b().into_keys().collect();
+        if delta.len() != states.len() * alphabet.len() {
+            return None;
+        }
+
+        let mut word = Vec::new();
+        let mut current: HashSet<S> = states;
+        while current.len() > 1 {
+            let mut iter = current.iter();
+            let (&p, &q) = (iter.next()?, iter.next()?);
+            let merge = merging_word(&delta, &alphabet, p, q)?;
+            current = current
+                .into_iter()
+                .map(|r| merge.iter().fold(r, |r, &label| delta[&(r, label)]))
+                .collect();
+            word.extend(merge);
+        }
+        Some(word)
+    }
+}
+
+// A shortest word that takes `p` and `q` to the same state, found by BFS over pairs of states.
+fn merging_word<S, E>(
+    delta: &HashMap<(S, E), S>,
+    alphabet: &HashSet<E>,
+    p: S,
+    q: S,
+) -> Option<Vec<E>>
+where
+    S: Eq + Hash + Copy,
+    E: Eq + Hash + Copy,
+{
+    let mut parents: HashMap<Pair<S>, Option<(Pair<S>, E)>> = HashMap::default();
+    let mut queue = VecDeque::new();
+    parents.insert((p, q), None);
+    queue.push_back((p, q));
+    while let Some((p, q)) = queue.pop_front() {
+        if p == q {
+            let mut word = Vec::new();
+            let mut cur = (p, q);
+            while let Some((prev, label)) = parents[&cur] {
+                word.push(label);
+                cur = prev;
+            }
+            word.reverse();
+            return Some(word);
+        }
+        for &label in alphabet {
+            let next = (delta[&(p, label)], delta[&(q, label)]);
+            if let Entry::Vacant(e) = parents.entry(next) {
+                e.insert(Some(((p, q), label)));
+                queue.push_back(next);
+            }
+        }
+    }
+    None
+}
+
+#[cfg(test)]
+mod test {
+    use crate::DFA;
+
+    #[test]
+    fn synchronizing_cerny() {
+        // The Cerny automaton with 4 states: `a` rotates, and `b` merges state 0 into state 1.
+        let mut transitions = Vec::new();
+        for q in 0..4u32 {
+            transitions.push((q, 'a', (q + 1) % 4));
+            transitions.push((q, 'b', if q == 0 { 1 } else { q }));
+        }
+        let input: DFA<u32, char> = DFA::new(0, vec![0], transitions);
+        let word = input.synchronizing_word().unwrap();
+        let delta = input.delta();
+        let ends: Vec<u32> = (0..4)
+            .map(|q| word.iter().fold(q, |q, &label| delta[&(q, label)]))
+            .collect();
+        assert!(
+            ends.iter().all(|&q| q == ends[0]),
+            "{:?} ends at {:?}",
+            word,
+            ends
+        );
+        // The shortest reset word has length (n - 1)^2.
+        assert!(word.len() >= 9);
+    }
+
+    #[test]
+    fn synchronizing_impossible() {
+        // A permutation automaton can never merge states.
+        let input: DFA<u32, char> = DFA::new(0, vec![0], vec![(0, 'a', 1), (1, 'a', 0)]);
+        assert_eq!(input.synchronizing_word(), None);
+        // An incomplete automaton is rejected.
+        let input: DFA<u32, char> = DFA::new(0, vec![0], vec![(0, 'a', 1), (1, 'b', 1)]);
+        assert_eq!(input.synchronizing_word(), None);
+    }
+}
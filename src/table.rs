@@ -1,4 +1,8 @@
-use std::{collections::HashMap, hash::Hash, iter::FromIterator};
+use std::{
+    cell::OnceCell, collections::VecDeque, hash::Hash, iter::FromIterator, ops::RangeBounds,
+};
+
+use crate::hash::{HashMap, HashSet};
 
 #[derive(Debug, Clone)]
 pub struct Table<A, B, C> {
@@ -44,6 +48,34 @@ where
     pub fn len(&self) -> usize {
         self.tuples.len()
     }
+    // Drops repeated tuples, keeping the first of each.
+    pub fn dedup(&mut self) {
+        let mut seen: HashSet<(A, B, C)> = HashSet::default();
+        self.tuples.retain(|&t| seen.insert(t));
+    }
+    // Sorts and deduplicates the tuples, for lookups by binary search instead of hashing.
+    pub fn sorted(mut self) -> SortedTable<A, B, C>
+    where
+        A: Ord,
+        B: Ord,
+        C: Ord,
+    {
+        self.tuples.sort_unstable();
+        self.tuples.dedup();
+        SortedTable {
+            tuples: self.tuples,
+        }
+    }
+    // Sorts by the last column instead, which comes first in the result.
+    pub fn sorted_by_c(self) -> SortedTable<C, A, B>
+    where
+        A: Ord,
+        B: Ord,
+        C: Ord,
+    {
+        let tuples = self.tuples.into_iter().map(|(a, b, c)| (c, a, b));
+        Table::from_iter(tuples).sorted()
+    }
     pub fn by_a(&self) -> HashMap<A, Vec<(B, C)>> {
         group_by_to(&self.tuples, |&(a, _, _)| a, |&(_, b, c)| (b, c))
     }
@@ -53,6 +85,265 @@ where
     pub fn by_c(&self) -> HashMap<C, Vec<(A, B)>> {
         group_by_to(&self.tuples, |&(_, _, c)| c, |&(a, b, _)| (a, b))
     }
+    // Wraps the table to keep its groupings once built, for callers that need several lookups.
+    pub fn indexed(&self) -> IndexedTable<'_, A, B, C> {
+        IndexedTable {
+            table: self,
+            by_a: OnceCell::new(),
+            by_b: OnceCell::new(),
+            by_c: OnceCell::new(),
+        }
+    }
+    // Joins the last column of this table to the first column of `other`: each `(a, b, c)` and
+    // `(c, d, e)` make `(a, (b, d), e)`. For transitions, these are the two-step paths.
+    pub fn join_ca<D, E>(&self, other: &Table<C, D, E>) -> Table<A, (B, D), E>
+    where
+        D: Eq + Hash + Copy,
+        E: Eq + Hash + Copy,
+    {
+        let rhs = other.by_a();
+        let mut tuples = Vec::new();
+        for &(a, b, c) in &self.tuples {
+            for &(d, e) in rhs.get(&c).into_iter().flatten() {
+                tuples.push((a, (b, d), e));
+            }
+        }
+        Table { tuples }
+    }
+    // Joins the middle columns of the two tables: each `(a, b, c)` and `(d, b, e)` make
+    // `((a, d), b, (c, e))`. For transitions, these are the transitions of the product of the
+    // two automata, over every pair of states.
+    pub fn join_b<D, E>(&self, other: &Table<D, B, E>) -> Table<(A, D), B, (C, E)>
+    where
+        D: Eq + Hash + Copy,
+        E: Eq + Hash + Copy,
+    {
+        let rhs = other.by_b();
+        let mut tuples = Vec::new();
+        for &(a, b, c) in &self.tuples {
+            for &(d, e) in rhs.get(&b).into_iter().flatten() {
+                tuples.push(((a, d), b, (c, e)));
+            }
+        }
+        Table { tuples }
+    }
+    // Joins the middle columns as `join_b` does, but only for the pairs of first-column values
+    // reachable from `start`, where each joined `(c, e)` leads on to the pair `next(c, e)`. For
+    // transitions, with `next` pairing up the targets, these are the transitions of the product
+    // of the two automata that can be reached from `start`, in the order they are reached.
+    pub fn join_b_from<D, E>(
+        &self,
+        other: &Table<D, B, E>,
+        start: (A, D),
+        next: impl Fn(C, E) -> (A, D),
+    ) -> Table<(A, D), B, (C, E)>
+    where
+        D: Eq + Hash + Copy,
+        E: Eq + Hash + Copy,
+    {
+        let lhs = self.by_a();
+        let mut rhs: HashMap<(D, B), Vec<E>> = HashMap::default();
+        for &(d, b, e) in &other.tuples {
+            rhs.entry((d, b)).or_default().push(e);
+        }
+        let mut seen: HashSet<(A, D)> = HashSet::default();
+        let mut queue = VecDeque::new();
+        seen.insert(start);
+        queue.push_back(start);
+        let mut tuples = Vec::new();
+        while let Some((a, d)) = queue.pop_front() {
+            for &(b, c) in lhs.get(&a).into_iter().flatten() {
+                for &e in rhs.get(&(d, b)).into_iter().flatten() {
+                    let pair = next(c, e);
+                    if seen.insert(pair) {
+                        queue.push_back(pair);
+                    }
+                    tuples.push(((a, d), b, (c, e)));
+                }
+            }
+        }
+        Table { tuples }
+    }
+}
+
+// A table together with its groupings by each column, so that lookups don't rebuild them. Each
+// grouping is built the first time it is used, so the ones that aren't used cost nothing.
+#[derive(Debug, Clone)]
+pub struct IndexedTable<'a, A, B, C> {
+    table: &'a Table<A, B, C>,
+    by_a: OnceCell<HashMap<A, Vec<(B, C)>>>,
+    by_b: OnceCell<HashMap<B, Vec<(A, C)>>>,
+    by_c: OnceCell<HashMap<C, Vec<(A, B)>>>,
+}
+
+impl<'a, A, B, C> IndexedTable<'a, A, B, C>
+where
+    A: Eq + Hash + Copy,
+    B: Eq + Hash + Copy,
+    C: Eq + Hash + Copy,
+{
+    pub fn table(&self) -> &'a Table<A, B, C> {
+        self.table
+    }
+    // The `(b, c)` of every tuple whose first column is `a`.
+    pub fn successors(&self, a: &A) -> &[(B, C)] {
+        self.by_a().get(a).map_or(&[], Vec::as_slice)
+    }
+    // The `(a, c)` of every tuple whose second column is `b`.
+    pub fn with_b(&self, b: &B) -> &[(A, C)] {
+        self.by_b().get(b).map_or(&[], Vec::as_slice)
+    }
+    // The `(a, b)` of every tuple whose last column is `c`.
+    pub fn predecessors(&self, c: &C) -> &[(A, B)] {
+        self.by_c().get(c).map_or(&[], Vec::as_slice)
+    }
+    pub fn by_a(&self) -> &HashMap<A, Vec<(B, C)>> {
+        self.by_a.get_or_init(|| self.table.by_a())
+    }
+    pub fn by_b(&self) -> &HashMap<B, Vec<(A, C)>> {
+        self.by_b.get_or_init(|| self.table.by_b())
+    }
+    pub fn by_c(&self) -> &HashMap<C, Vec<(A, B)>> {
+        self.by_c.get_or_init(|| self.table.by_c())
+    }
+}
+
+// A table with a fourth column, for edges that carry two things between their endpoints, such as
+// a transducer's `(src, input, output, dst)` or a weighted automaton's `(src, label, weight, dst)`.
+// Only the endpoints are used as keys, so the middle columns need not be hashable.
+#[derive(Debug, Clone)]
+pub struct Table4<A, B, C, D> {
+    tuples: Vec<(A, B, C, D)>,
+}
+impl<A, B, C, D> From<Vec<(A, B, C, D)>> for Table4<A, B, C, D> {
+    fn from(tuples: Vec<(A, B, C, D)>) -> Self {
+        Table4 { tuples }
+    }
+}
+impl<A, B, C, D> IntoIterator for Table4<A, B, C, D> {
+    type Item = (A, B, C, D);
+
+    type IntoIter = std::vec::IntoIter<(A, B, C, D)>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.tuples.into_iter()
+    }
+}
+impl<'a, A, B, C, D> IntoIterator for &'a Table4<A, B, C, D> {
+    type Item = &'a (A, B, C, D);
+
+    type IntoIter = std::slice::Iter<'a, (A, B, C, D)>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.tuples.iter()
+    }
+}
+impl<A, B, C, D> FromIterator<(A, B, C, D)> for Table4<A, B, C, D> {
+    fn from_iter<T: IntoIterator<Item = (A, B, C, D)>>(iter: T) -> Self {
+        Table4 {
+            tuples: iter.into_iter().collect(),
+        }
+    }
+}
+
+impl<A, B, C, D> Table4<A, B, C, D>
+where
+    A: Eq + Hash + Copy,
+    B: Copy,
+    C: Copy,
+    D: Eq + Hash + Copy,
+{
+    pub fn len(&self) -> usize {
+        self.tuples.len()
+    }
+    pub fn by_a(&self) -> HashMap<A, Vec<(B, C, D)>> {
+        group_by_to(&self.tuples, |&(a, _, _, _)| a, |&(_, b, c, d)| (b, c, d))
+    }
+    pub fn by_d(&self) -> HashMap<D, Vec<(A, B, C)>> {
+        group_by_to(&self.tuples, |&(_, _, _, d)| d, |&(a, b, c, _)| (a, b, c))
+    }
+    // Drops the third column, for the underlying relation between endpoints.
+    pub fn without_c(&self) -> Table<A, B, D>
+    where
+        B: Eq + Hash,
+    {
+        self.tuples.iter().map(|&(a, b, _, d)| (a, b, d)).collect()
+    }
+}
+
+// A table whose tuples are kept sorted and distinct, so that the tuples with a given first
+// column are a contiguous run found in `O(log n)`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SortedTable<A, B, C> {
+    tuples: Vec<(A, B, C)>,
+}
+
+impl<A, B, C> SortedTable<A, B, C>
+where
+    A: Ord + Copy,
+    B: Ord + Copy,
+    C: Ord + Copy,
+{
+    pub fn len(&self) -> usize {
+        self.tuples.len()
+    }
+    pub fn is_empty(&self) -> bool {
+        self.tuples.is_empty()
+    }
+    pub fn tuples(&self) -> &[(A, B, C)] {
+        &self.tuples
+    }
+    // Adds a tuple in its place, and returns whether it was new.
+    pub fn insert(&mut self, tuple: (A, B, C)) -> bool {
+        match self.tuples.binary_search(&tuple) {
+            Ok(_) => false,
+            Err(i) => {
+                self.tuples.insert(i, tuple);
+                true
+            }
+        }
+    }
+    pub fn contains(&self, tuple: &(A, B, C)) -> bool {
+        self.tuples.binary_search(tuple).is_ok()
+    }
+    // Every tuple whose first column is `a`.
+    pub fn successors(&self, a: &A) -> &[(A, B, C)] {
+        self.range(a..=a)
+    }
+    // The runs of tuples that share a first column, in order, along with that column. Unlike
+    // `Table::by_a`, this builds nothing, so it is the way to group a table too big to copy.
+    pub fn groups(&self) -> impl Iterator<Item = (A, &[(A, B, C)])> + '_ {
+        self.tuples
+            .chunk_by(|(x, _, _), (y, _, _)| x == y)
+            .map(|run| (run[0].0, run))
+    }
+    // Every tuple whose first two columns are `a` and `b`.
+    pub fn lookup(&self, a: &A, b: &B) -> &[(A, B, C)] {
+        let run = self.successors(a);
+        let start = run.partition_point(|(_, x, _)| x < b);
+        let end = run.partition_point(|(_, x, _)| x <= b);
+        &run[start..end]
+    }
+    // Every tuple whose first column is in `range`.
+    pub fn range(&self, range: impl RangeBounds<A>) -> &[(A, B, C)] {
+        use std::ops::Bound::*;
+        let start = match range.start_bound() {
+            Included(a) => self.tuples.partition_point(|(x, _, _)| x < a),
+            Excluded(a) => self.tuples.partition_point(|(x, _, _)| x <= a),
+            Unbounded => 0,
+        };
+        let end = match range.end_bound() {
+            Included(a) => self.tuples.partition_point(|(x, _, _)| x <= a),
+            Excluded(a) => self.tuples.partition_point(|(x, _, _)| x < a),
+            Unbounded => self.tuples.len(),
+        };
+        &self.tuples[start..end.max(start)]
+    }
+    pub fn into_table(self) -> Table<A, B, C> {
+        Table {
+            tuples: self.tuples,
+        }
+    }
 }
 
 fn group_by_to<T, K: Eq + Hash, V>(
@@ -60,7 +351,7 @@ fn group_by_to<T, K: Eq + Hash, V>(
     key_fn: impl Fn(&T) -> K,
     value_fn: impl Fn(&T) -> V,
 ) -> HashMap<K, Vec<V>> {
-    let mut output: HashMap<K, Vec<V>> = HashMap::new();
+    let mut output: HashMap<K, Vec<V>> = HashMap::default();
     for t in input {
         let k = key_fn(t);
         let v = value_fn(t);
@@ -68,3 +359,94 @@ fn group_by_to<T, K: Eq + Hash, V>(
     }
     output
 }
+
+#[cfg(test)]
+mod test {
+    use super::{Table, Table4};
+
+    #[test]
+    fn indexed_lookups() {
+        let table: Table<u32, char, u32> = vec![(0, 'a', 1), (0, 'b', 2), (1, 'a', 2)].into();
+        let index = table.indexed();
+        assert_eq!(index.successors(&0), &[('a', 1), ('b', 2)]);
+        assert_eq!(index.successors(&2), &[]);
+        assert_eq!(index.predecessors(&2), &[(0, 'b'), (1, 'a')]);
+        assert_eq!(index.with_b(&'a'), &[(0, 1), (1, 2)]);
+        assert_eq!(index.by_a().len(), 2);
+        assert_eq!(index.table().len(), 3);
+    }
+
+    #[test]
+    fn joins() {
+        let lhs: Table<u32, char, u32> = vec![(0, 'a', 1), (0, 'b', 2), (1, 'a', 2)].into();
+        let rhs: Table<u32, char, u32> = vec![(1, 'x', 5), (2, 'a', 6), (2, 'y', 7)].into();
+
+        let mut paths: Vec<_> = lhs.join_ca(&rhs).into_iter().collect();
+        paths.sort_unstable();
+        assert_eq!(
+            paths,
+            vec![
+                (0, ('a', 'x'), 5),
+                (0, ('b', 'a'), 6),
+                (0, ('b', 'y'), 7),
+                (1, ('a', 'a'), 6),
+                (1, ('a', 'y'), 7),
+            ]
+        );
+
+        let mut product: Vec<_> = lhs.join_b(&rhs).into_iter().collect();
+        product.sort_unstable();
+        assert_eq!(product, vec![((0, 2), 'a', (1, 6)), ((1, 2), 'a', (2, 6))]);
+
+        // From (0, 2), only (1, 6) can be reached, and nothing leaves it.
+        let reachable: Vec<_> = lhs
+            .join_b_from(&rhs, (0, 2), |c, e| (c, e))
+            .into_iter()
+            .collect();
+        assert_eq!(reachable, vec![((0, 2), 'a', (1, 6))]);
+        assert_eq!(lhs.join_b_from(&rhs, (0, 1), |c, e| (c, e)).len(), 0);
+    }
+
+    #[test]
+    fn sorted_lookups() {
+        let mut table: Table<u32, char, u32> = vec![
+            (1, 'b', 2),
+            (0, 'a', 1),
+            (1, 'a', 2),
+            (0, 'a', 1),
+            (3, 'a', 0),
+        ]
+        .into();
+        let sorted = table.clone().sorted();
+        assert_eq!(sorted.len(), 4);
+        assert_eq!(sorted.successors(&1), &[(1, 'a', 2), (1, 'b', 2)]);
+        assert_eq!(sorted.successors(&2), &[]);
+        assert_eq!(sorted.lookup(&1, &'b'), &[(1, 'b', 2)]);
+        assert_eq!(sorted.range(1..), &sorted.tuples()[1..]);
+        assert_eq!(sorted.range(..1).len(), 1);
+        assert!(sorted.contains(&(3, 'a', 0)));
+
+        let mut sorted = sorted;
+        assert!(sorted.insert((2, 'a', 2)));
+        assert!(!sorted.insert((2, 'a', 2)));
+        assert_eq!(sorted.successors(&2), &[(2, 'a', 2)]);
+
+        let groups: Vec<_> = sorted.groups().map(|(a, run)| (a, run.len())).collect();
+        assert_eq!(groups, vec![(0, 1), (1, 2), (2, 1), (3, 1)]);
+        assert_eq!(table.clone().sorted_by_c().successors(&2).len(), 2);
+
+        table.dedup();
+        assert_eq!(table.len(), 4);
+        assert_eq!(table.into_iter().next(), Some((1, 'b', 2)));
+    }
+
+    #[test]
+    fn four_columns() {
+        let table: Table4<u32, char, f64, u32> =
+            vec![(0, 'a', 0.5, 1), (0, 'b', 1.5, 1), (1, 'a', 2.0, 0)].into();
+        assert_eq!(table.len(), 3);
+        assert_eq!(table.by_a()[&0], vec![('a', 0.5, 1), ('b', 1.5, 1)]);
+        assert_eq!(table.by_d()[&0], vec![(1, 'a', 2.0)]);
+        assert_eq!(table.without_c().by_b()[&'a'], vec![(0, 1), (1, 0)]);
+    }
+}
@@ -0,0 +1,454 @@
+// Helpers for property-testing code that uses this crate (and the crate itself): random
+// automata with tunable shape, and slow but obviously correct reference implementations.
+//
+// This crate has no dependencies, so it does not implement proptest's own `Strategy` and
+// `Arbitrary` traits. The `strategy` feature adds look-alikes instead: a `Strategy` that generates
+// and shrinks values, `Arbitrary` impls for `DFA<u32, u32>` and `NFA<u32, u32>`, and `for_all`,
+// a minimal runner. Wrapping `Strategy::new_value` and `Strategy::shrink` in a proptest
+// `ValueTree` is enough to use the generators from proptest itself.
+
+use std::fmt::Debug;
+use std::hash::Hash;
+
+use crate::hash::{HashMap, HashSet};
+use crate::nfa::NFA;
+use crate::DFA;
+
+// Parameters for random automata. States are numbered from 0 (the initial state) and symbols
+// from 0 to `alphabet_size - 1`. There must be at least one state.
+#[derive(Debug, Clone, PartialEq)]
+pub struct RandomAutomata {
+    pub states: u32,
+    pub alphabet_size: u32,
+    // For DFAs, the chance that each (state, symbol) pair has a transition. For NFAs, the
+    // expected number of transitions for each (state, symbol) pair.
+    pub density: f64,
+    // The chance that each state is accepting.
+    pub accept_fraction: f64,
+}
+
+impl Default for RandomAutomata {
+    fn default() -> RandomAutomata {
+        RandomAutomata {
+            states: 8,
+            alphabet_size: 2,
+            density: 1.0,
+            accept_fraction: 0.5,
+        }
+    }
+}
+
+impl RandomAutomata {
+    // A random DFA, whose transitions each lead to a uniformly random state. `rng` must produce
+    // uniformly distributed `u64`s.
+    pub fn dfa(&self, rng: &mut impl FnMut() -> u64) -> DFA<u32, u32> {
+        self.check();
+        let mut transitions = Vec::new();
+        for src in 0..self.states {
+            for label in 0..self.alphabet_size {
+                if chance(rng, self.density) {
+                    transitions.push((src, label, (rng() % u64::from(self.states)) as u32));
+                }
+            }
+        }
+        DFA::new(0, self.accepting(rng), transitions)
+    }
+
+    // A random NFA without epsilon transitions, with each possible transition present
+    // independently.
+    pub fn nfa(&self, rng: &mut impl FnMut() -> u64) -> NFA<u32, u32> {
+        self.check();
+        let p = self.density / f64::from(self.states);
+        let mut transitions = Vec::new();
+        for src in 0..self.states {
+            for label in 0..self.alphabet_size {
+                for dst in 0..self.states {
+                    if chance(rng, p) {
+                        transitions.push((src, Some(label), dst));
+                    }
+                }
+            }
+        }
+        NFA::new(0, self.accepting(rng), transitions)
+    }
+
+    // Random DFAs with these parameters, as a `Strategy`.
+    #[cfg(feature = "strategy")]
+    pub fn dfas(self) -> Dfas {
+        self.check();
+        Dfas(self)
+    }
+
+    // Random NFAs with these parameters, as a `Strategy`.
+    #[cfg(feature = "strategy")]
+    pub fn nfas(self) -> Nfas {
+        self.check();
+        Nfas(self)
+    }
+
+    fn check(&self) {
+        assert!(
+            self.states > 0,
+            "random automata need at least the initial state"
+        );
+    }
+
+    fn accepting(&self, rng: &mut impl FnMut() -> u64) -> Vec<u32> {
+        (0..self.states)
+            .filter(|_| chance(rng, self.accept_fraction))
+            .collect()
+    }
+}
+
+// A way to generate random values of some type, and to shrink a value that fails a test towards
+// a simpler one that still might.
+#[cfg(feature = "strategy")]
+pub trait Strategy {
+    type Value;
+
+    // A random value. `rng` must produce uniformly distributed `u64`s.
+    fn new_value(&self, rng: &mut dyn FnMut() -> u64) -> Self::Value;
+
+    // Values that are each a little simpler than `value`. Empty once `value` can't be simplified.
+    fn shrink(&self, value: &Self::Value) -> Vec<Self::Value>;
+}
+
+// Types with a default `Strategy`.
+#[cfg(feature = "strategy")]
+pub trait Arbitrary: Sized {
+    type Strategy: Strategy<Value = Self>;
+
+    fn arbitrary() -> Self::Strategy;
+}
+
+// The `Strategy` behind `RandomAutomata::dfas`. Shrinking drops one transition or one accepting
+// state at a time.
+#[cfg(feature = "strategy")]
+#[derive(Debug, Clone, PartialEq)]
+pub struct Dfas(RandomAutomata);
+
+#[cfg(feature = "strategy")]
+impl Strategy for Dfas {
+    type Value = DFA<u32, u32>;
+
+    fn new_value(&self, mut rng: &mut dyn FnMut() -> u64) -> DFA<u32, u32> {
+        self.0.dfa(&mut rng)
+    }
+
+    fn shrink(&self, dfa: &DFA<u32, u32>) -> Vec<DFA<u32, u32>> {
+        let transitions: Vec<(u32, u32, u32)> = dfa.transitions.clone().into_iter().collect();
+        let final_states: Vec<u32> = dfa.final_states.iter().copied().collect();
+        shrunk(&final_states, &transitions)
+            .map(|(final_states, transitions)| DFA::new(0, final_states, transitions))
+            .collect()
+    }
+}
+
+#[cfg(feature = "strategy")]
+impl Arbitrary for DFA<u32, u32> {
+    type Strategy = Dfas;
+
+    fn arbitrary() -> Dfas {
+        RandomAutomata::default().dfas()
+    }
+}
+
+// The `Strategy` behind `RandomAutomata::nfas`. Shrinking drops one transition or one accepting
+// state at a time.
+#[cfg(feature = "strategy")]
+#[derive(Debug, Clone, PartialEq)]
+pub struct Nfas(RandomAutomata);
+
+#[cfg(feature = "strategy")]
+impl Strategy for Nfas {
+    type Value = NFA<u32, u32>;
+
+    fn new_value(&self, mut rng: &mut dyn FnMut() -> u64) -> NFA<u32, u32> {
+        self.0.nfa(&mut rng)
+    }
+
+    fn shrink(&self, nfa: &NFA<u32, u32>) -> Vec<NFA<u32, u32>> {
+        let transitions: Vec<(u32, Option<u32>, u32)> =
+            nfa.transitions.clone().into_iter().collect();
+        let final_states: Vec<u32> = nfa.final_states.iter().copied().collect();
+        shrunk(&final_states, &transitions)
+            .map(|(final_states, transitions)| NFA::new(0, final_states, transitions))
+            .collect()
+    }
+}
+
+#[cfg(feature = "strategy")]
+impl Arbitrary for NFA<u32, u32> {
+    type Strategy = Nfas;
+
+    fn arbitrary() -> Nfas {
+        RandomAutomata::default().nfas()
+    }
+}
+
+// Each way of dropping one transition, then each way of dropping one accepting state.
+#[cfg(feature = "strategy")]
+fn shrunk<'a, T: Copy>(
+    final_states: &'a [u32],
+    transitions: &'a [T],
+) -> impl Iterator<Item = (Vec<u32>, Vec<T>)> + 'a {
+    fn without<T: Copy>(items: &[T], i: usize) -> Vec<T> {
+        [&items[..i], &items[i + 1..]].concat()
+    }
+    let fewer_transitions =
+        (0..transitions.len()).map(move |i| (final_states.to_vec(), without(transitions, i)));
+    let fewer_finals =
+        (0..final_states.len()).map(move |i| (without(final_states, i), transitions.to_vec()));
+    fewer_transitions.chain(fewer_finals)
+}
+
+// Checks `property` against `cases` values from `strategy`, generated from `seed`. On the first
+// failure, shrinks the failing value for as long as a simpler one also fails, and returns it.
+#[cfg(feature = "strategy")]
+pub fn for_all<S: Strategy>(
+    strategy: &S,
+    cases: u32,
+    seed: u64,
+    mut property: impl FnMut(&S::Value) -> bool,
+) -> Result<(), S::Value> {
+    let mut rng = seeded_rng(seed);
+    for _ in 0..cases {
+        let mut value = strategy.new_value(&mut rng);
+        if property(&value) {
+            continue;
+        }
+        while let Some(simpler) = strategy
+            .shrink(&value)
+            .into_iter()
+            .find(|simpler| !property(simpler))
+        {
+            value = simpler;
+        }
+        return Err(value);
+    }
+    Ok(())
+}
+
+// A small, fast xorshift generator, for reproducible tests. Not suitable for anything that
+// needs unpredictability.
+pub fn seeded_rng(seed: u64) -> impl FnMut() -> u64 {
+    // Xorshift gets stuck at 0.
+    let mut state = seed | 1;
+    move || {
+        state ^= state << 13;
+        state ^= state >> 7;
+        state ^= state << 17;
+        state
+    }
+}
+
+// Minimizes `dfa` with the textbook table-filling algorithm, which takes O(n^2) space and up to
+// O(n^3) time but is hard to get wrong. Gives the same result as `prune_unreachable` followed by
+// `minimize`, up to the choice of representative states, so the two can be differential-tested.
+pub fn reference_minimize<S, E>(dfa: &DFA<S, E>) -> Option<DFA<S, E>>
+where
+    S: Eq + Hash + Copy + Debug,
+    E: Eq + Hash + Copy + Debug,
+{
+    let delta = dfa.delta();
+    let alphabet: HashSet<E> = dfa.transitions.by_b().into_keys().collect();
+    let mut states: Vec<S> = vec![dfa.initial_state];
+    let mut i = 0;
+    while i < states.len() {
+        for &label in &alphabet {
+            if let Some(&dst) = delta.get(&(states[i], label)) {
+                if !states.contains(&dst) {
+                    states.push(dst);
+                }
+            }
+        }
+        i += 1;
+    }
+    // Index `n` is a rejecting sink that stands in for missing transitions.
+    let n = states.len();
+    let index: HashMap<S, usize> = states.iter().enumerate().map(|(i, &q)| (q, i)).collect();
+    let step = |i: usize, label: E| -> usize {
+        states
+            .get(i)
+            .and_then(|q| delta.get(&(*q, label)))
+            .map_or(n, |dst| index[dst])
+    };
+    let accepting = |i: usize| states.get(i).is_some_and(|q| dfa.final_states.contains(q));
+
+    let mut distinct = vec![vec![false; n + 1]; n + 1];
+    for (i, row) in distinct.iter_mut().enumerate() {
+        for (j, cell) in row.iter_mut().enumerate() {
+            *cell = accepting(i) != accepting(j);
+        }
+    }
+    let mut changed = true;
+    while changed {
+        changed = false;
+        for i in 0..=n {
+            for j in 0..=n {
+                if !distinct[i][j] && alphabet.iter().any(|&a| distinct[step(i, a)][step(j, a)]) {
+                    distinct[i][j] = true;
+                    changed = true;
+                }
+            }
+        }
+    }
+
+    // States equivalent to the sink are dead, and dropped.
+    if !distinct[0][n] {
+        return None;
+    }
+    let representative = |i: usize| states[(0..=i).find(|&j| !distinct[i][j]).unwrap()];
+    let mut transitions = Vec::new();
+    for (i, &q) in states.iter().enumerate() {
+        if representative(i) != q {
+            continue;
+        }
+        for &label in &alphabet {
+            let t = step(i, label);
+            if distinct[t][n] {
+                transitions.push((q, label, representative(t)));
+            }
+        }
+    }
+    let final_states = (0..n).filter(|&i| accepting(i)).map(representative);
+    Some(DFA::new(dfa.initial_state, final_states, transitions))
+}
+
+// True with probability `p`.
+fn chance(rng: &mut impl FnMut() -> u64, p: f64) -> bool {
+    ((rng() >> 11) as f64) / ((1u64 << 53) as f64) < p
+}
+
+#[cfg(test)]
+mod test {
+    use super::{reference_minimize, seeded_rng, RandomAutomata};
+
+    // Every word over `alphabet_size` symbols of at most `max_len` symbols.
+    fn all_words(alphabet_size: u32, max_len: usize) -> Vec<Vec<u32>> {
+        let mut words = vec![Vec::new()];
+        let mut layer = vec![Vec::new()];
+        for _ in 0..max_len {
+            layer = layer
+                .iter()
+                .flat_map(|w: &Vec<u32>| {
+                    (0..alphabet_size).map(move |a| [w.as_slice(), &[a]].concat())
+                })
+                .collect();
+            words.extend(layer.iter().cloned());
+        }
+        words
+    }
+
+    #[test]
+    #[should_panic(expected = "at least the initial state")]
+    fn no_states() {
+        let params = RandomAutomata {
+            states: 0,
+            ..RandomAutomata::default()
+        };
+        params.dfa(&mut seeded_rng(7));
+    }
+
+    #[cfg(feature = "strategy")]
+    #[test]
+    fn strategies_shrink_counterexamples() {
+        use super::{for_all, Arbitrary, Strategy};
+        use crate::nfa::NFA;
+        use crate::DFA;
+
+        let dfas = DFA::<u32, u32>::arbitrary();
+        let empty_word = |dfa: &DFA<u32, u32>| dfa.accepts(&[]) == dfa.final_states.contains(&0);
+        assert_eq!(for_all(&dfas, 50, 3, empty_word), Ok(()));
+        // Most random DFAs accept some word of one symbol, and the smallest counterexample is the
+        // single transition and accepting state that make it so.
+        let minimal = for_all(&dfas, 50, 3, |dfa| !dfa.accepts(&[1])).unwrap_err();
+        assert_eq!(minimal.transitions.len(), 1);
+        assert_eq!(minimal.final_states.len(), 1);
+        assert!(minimal.accepts(&[1]));
+        assert!(dfas.shrink(&DFA::new(0, vec![], vec![])).is_empty());
+
+        let nfas = RandomAutomata {
+            states: 4,
+            ..RandomAutomata::default()
+        }
+        .nfas();
+        let minimal = for_all(&nfas, 50, 3, |nfa| !nfa.accepts(&[0, 0])).unwrap_err();
+        assert!(minimal.transitions.len() <= 2);
+        assert!(minimal.accepts(&[0, 0]));
+        assert_eq!(
+            NFA::<u32, u32>::arbitrary(),
+            RandomAutomata::default().nfas()
+        );
+    }
+
+    #[test]
+    fn generators_respect_parameters() {
+        let mut rng = seeded_rng(7);
+        let params = RandomAutomata {
+            states: 20,
+            alphabet_size: 3,
+            density: 0.5,
+            accept_fraction: 0.25,
+        };
+        let dfa = params.dfa(&mut rng);
+        assert!(dfa.transitions.len() > 10 && dfa.transitions.len() < 50);
+        assert!(dfa.final_states.len() < 12);
+        let nfa = params.nfa(&mut rng);
+        assert!(nfa.transitions.len() > 10 && nfa.transitions.len() < 50);
+    }
+
+    #[test]
+    fn minimize_and_determinize_preserve_languages() {
+        let mut rng = seeded_rng(42);
+        let params = RandomAutomata {
+            density: 0.8,
+            ..RandomAutomata::default()
+        };
+        let words = all_words(params.alphabet_size, 6);
+        for _ in 0..50 {
+            let dfa = params.dfa(&mut rng);
+            if let Some(pruned) = dfa.clone().prune_unreachable() {
+                if pruned.transitions.len() > 0 {
+                    let minimal = pruned.minimize();
+                    for word in &words {
+                        assert_eq!(dfa.accepts(word), minimal.accepts(word), "{:?}", dfa);
+                    }
+                }
+            }
+            let nfa = params.nfa(&mut rng);
+            let determinized = nfa.determinize();
+            for word in &words {
+                assert_eq!(nfa.accepts(word), determinized.accepts(word), "{:?}", nfa);
+            }
+        }
+    }
+
+    #[test]
+    fn reference_minimizer_agrees() {
+        let mut rng = seeded_rng(1234);
+        for states in 1..12 {
+            let params = RandomAutomata {
+                states,
+                alphabet_size: 3,
+                density: 0.7,
+                accept_fraction: 0.3,
+            };
+            for _ in 0..20 {
+                let dfa = params.dfa(&mut rng);
+                let reference = reference_minimize(&dfa);
+                let fast = dfa
+                    .clone()
+                    .prune_unreachable()
+                    .map(|pruned| pruned.minimize());
+                match (reference, fast) {
+                    (None, None) => {}
+                    (Some(reference), Some(fast)) => {
+                        assert!(reference.isomorphic(&fast).is_some(), "{:?}", dfa);
+                    }
+                    (reference, fast) => panic!("{:?} vs {:?} for {:?}", reference, fast, dfa),
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,68 @@
+use std::fmt::Debug;
+use std::hash::Hash;
+
+use crate::hash::HashMap;
+use crate::DFA;
+
+impl<E> DFA<u32, E>
+where
+    E: Eq + Hash + Copy + Debug,
+{
+    // Builds the prefix-tree acceptor for a finite set of words: a tree-shaped DFA with one state
+    // per distinct prefix, rooted at state 0. Duplicate words are fine.
+    pub fn from_words<W>(words: impl IntoIterator<Item = W>) -> DFA<u32, E>
+    where
+        W: IntoIterator<Item = E>,
+    {
+        let mut children: HashMap<(u32, E), u32> = HashMap::default();
+        let mut final_states = Vec::new();
+        let mut next_id = 1;
+        for word in words {
+            let mut q = 0;
+            for label in word {
+                q = *children.entry((q, label)).or_insert_with(|| {
+                    next_id += 1;
+                    next_id - 1
+                });
+            }
+            final_states.push(q);
+        }
+        DFA::new(
+            0,
+            final_states,
+            children
+                .into_iter()
+                .map(|((src, label), dst)| (src, label, dst)),
+        )
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::DFA;
+
+    #[test]
+    fn from_words_builds_trie() {
+        let dfa = DFA::from_words(
+            vec!["tap", "taps", "top", "tops", "tap"]
+                .into_iter()
+                .map(|w| w.chars()),
+        );
+        // Prefixes: "", t, ta, tap, taps, to, top, tops
+        assert_eq!(dfa.transitions.len(), 7);
+        assert_eq!(dfa.final_states.len(), 4);
+        let words: Vec<String> = dfa.words(None).map(|w| w.into_iter().collect()).collect();
+        assert_eq!(words, vec!["tap", "top", "taps", "tops"]);
+
+        let minified = dfa.prune_unreachable().unwrap().minimize();
+        assert_eq!(minified.transitions.len(), 5);
+    }
+
+    #[test]
+    fn from_words_empty() {
+        let dfa: DFA<u32, char> = DFA::from_words(Vec::<Vec<char>>::new());
+        assert_eq!(dfa.shortest_word(), None);
+        let dfa: DFA<u32, char> = DFA::from_words(vec![vec![]]);
+        assert_eq!(dfa.shortest_word(), Some(vec![]));
+    }
+}
@@ -0,0 +1,183 @@
+// Compiling, minimizing and running automata for JavaScript, with automata passed as JSON in the
+// format of `Format::Json`. The `dfa_*` functions are exported with the C ABI, so that a
+// WebAssembly host can call them directly: it copies its input into memory from `dfa_alloc`, and
+// after each call reads the result (or error message) from `dfa_output_ptr` and
+// `dfa_output_len`.
+
+use std::cell::RefCell;
+
+use crate::format::{parse_json_word, Format, NamedDFA};
+use crate::{CharClass, Predicate, Regex};
+
+// Compiles a regex to its minimal automaton, labeled by character classes.
+pub fn compile(pattern: &str) -> Result<String, String> {
+    let regex = Regex::parse(pattern).map_err(|e| e.to_string())?;
+    Ok(NamedDFA::from_sfa(&regex.to_dfa().minimize()).render(Format::Json))
+}
+
+pub fn minimize(automaton: &str) -> Result<String, String> {
+    let named = NamedDFA::parse(automaton, Format::Json).map_err(|e| e.to_string())?;
+    Ok(named.minimize().render(Format::Json))
+}
+
+// Whether the automaton accepts `word`, a JSON array of label names. The labels of a compiled
+// pattern are character classes, so an item that is a single character, rather than a label,
+// is read as the label whose class contains it, as the command line's `match` does.
+pub fn accepts(automaton: &str, word: &str) -> Result<bool, String> {
+    let named = NamedDFA::parse(automaton, Format::Json).map_err(|e| e.to_string())?;
+    let word = parse_json_word(word).map_err(|e| e.to_string())?;
+    let classes: Vec<Option<CharClass>> = named.labels.iter().map(|name| class(name)).collect();
+    let word: Vec<&str> = word
+        .iter()
+        .map(|item| {
+            let mut chars = item.chars();
+            match (chars.next(), chars.next()) {
+                (Some(c), None) if !named.labels.contains(item) => named
+                    .labels
+                    .iter()
+                    .zip(&classes)
+                    .find(|(_, class)| class.as_ref().is_some_and(|class| class.contains(&c)))
+                    .map_or(item.as_str(), |(name, _)| name.as_str()),
+                _ => item.as_str(),
+            }
+        })
+        .collect();
+    Ok(named.accepts(&word))
+}
+
+// The character class that a label names, if it is written the way `CharClass` displays one.
+fn class(label: &str) -> Option<CharClass> {
+    let sfa = Regex::parse(label).ok()?.to_dfa().minimize();
+    match sfa.transitions.as_slice() {
+        [(src, class, dst)]
+            if *src == sfa.initial_state
+                && sfa.final_states.len() == 1
+                && sfa.final_states.contains(dst)
+                && class.to_string() == label =>
+        {
+            Some(class.clone())
+        }
+        _ => None,
+    }
+}
+
+thread_local! {
+    // The result of the latest call.
+    static OUTPUT: RefCell<Vec<u8>> = const { RefCell::new(Vec::new()) };
+}
+
+// Stores the result of a call, and returns 0 for success or -1 for an error.
+fn finish(result: Result<String, String>) -> i32 {
+    let (status, text) = match result {
+        Ok(text) => (0, text),
+        Err(message) => (-1, message),
+    };
+    OUTPUT.with(|output| *output.borrow_mut() = text.into_bytes());
+    status
+}
+
+// Reads a string that the host wrote into memory from `dfa_alloc`.
+//
+// Safety: `ptr` must point to `len` initialized bytes.
+unsafe fn input(ptr: *const u8, len: usize) -> Result<String, String> {
+    let bytes = std::slice::from_raw_parts(ptr, len);
+    String::from_utf8(bytes.to_vec()).map_err(|e| e.to_string())
+}
+
+#[no_mangle]
+pub extern "C" fn dfa_alloc(len: usize) -> *mut u8 {
+    let mut buffer = vec![0u8; len].into_boxed_slice();
+    let ptr = buffer.as_mut_ptr();
+    std::mem::forget(buffer);
+    ptr
+}
+
+// Safety: `ptr` and `len` must come from a single call to `dfa_alloc`.
+#[no_mangle]
+pub unsafe extern "C" fn dfa_free(ptr: *mut u8, len: usize) {
+    drop(Box::from_raw(std::ptr::slice_from_raw_parts_mut(ptr, len)));
+}
+
+#[no_mangle]
+pub extern "C" fn dfa_output_ptr() -> *const u8 {
+    OUTPUT.with(|output| output.borrow().as_ptr())
+}
+
+#[no_mangle]
+pub extern "C" fn dfa_output_len() -> usize {
+    OUTPUT.with(|output| output.borrow().len())
+}
+
+// Safety: the pointers must point to the given number of initialized bytes.
+#[no_mangle]
+pub unsafe extern "C" fn dfa_compile(ptr: *const u8, len: usize) -> i32 {
+    finish(input(ptr, len).and_then(|pattern| compile(&pattern)))
+}
+
+// Safety: as for `dfa_compile`.
+#[no_mangle]
+pub unsafe extern "C" fn dfa_minimize(ptr: *const u8, len: usize) -> i32 {
+    finish(input(ptr, len).and_then(|automaton| minimize(&automaton)))
+}
+
+// Returns 1 if the word is accepted, 0 if not, and -1 for an error.
+//
+// Safety: as for `dfa_compile`.
+#[no_mangle]
+pub unsafe extern "C" fn dfa_accepts(
+    automaton: *const u8,
+    automaton_len: usize,
+    word: *const u8,
+    word_len: usize,
+) -> i32 {
+    let result = input(automaton, automaton_len)
+        .and_then(|automaton| Ok((automaton, input(word, word_len)?)))
+        .and_then(|(automaton, word)| accepts(&automaton, &word));
+    match result {
+        Ok(accepted) => {
+            finish(Ok(String::new()));
+            i32::from(accepted)
+        }
+        Err(message) => finish(Err(message)),
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::{accepts, compile, minimize};
+
+    #[test]
+    fn json_round_trip() {
+        let automaton = compile("ab(c|d)*").unwrap();
+        assert_eq!(accepts(&automaton, r#"["a", "b", "[c-d]"]"#), Ok(true));
+        assert_eq!(accepts(&automaton, r#"["a"]"#), Ok(false));
+        assert_eq!(accepts(&automaton, r#"["z"]"#), Ok(false));
+        // Characters stand for the classes that contain them.
+        assert_eq!(accepts(&automaton, r#"["a", "b", "c", "d"]"#), Ok(true));
+        assert_eq!(accepts(&automaton, r#"["a", "b", "e"]"#), Ok(false));
+        assert!(accepts(&automaton, "a").is_err());
+        assert!(compile("(").is_err());
+
+        let redundant = r#"{"initial": 0, "accepting": [1, 2], "transitions": [[0, "x", 1], [1, "x", 2], [2, "x", 2]]}"#;
+        let minimal = minimize(redundant).unwrap();
+        assert_eq!(minimal.matches("\"x\"").count(), 2);
+        assert_eq!(accepts(&minimal, r#"["x", "x", "x"]"#), Ok(true));
+    }
+
+    #[test]
+    fn exported_functions() {
+        let pattern = "a+";
+        let ptr = super::dfa_alloc(pattern.len());
+        unsafe {
+            std::ptr::copy_nonoverlapping(pattern.as_ptr(), ptr, pattern.len());
+            assert_eq!(super::dfa_compile(ptr, pattern.len()), 0);
+            super::dfa_free(ptr, pattern.len());
+        }
+        let output =
+            unsafe { std::slice::from_raw_parts(super::dfa_output_ptr(), super::dfa_output_len()) };
+        assert_eq!(
+            String::from_utf8(output.to_vec()).unwrap(),
+            compile("a+").unwrap()
+        );
+    }
+}
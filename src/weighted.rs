@@ -0,0 +1,318 @@
+use std::collections::VecDeque;
+use std::fmt::Debug;
+use std::hash::Hash;
+
+use crate::hash::{HashMap, HashSet, IntoStd};
+use crate::table::Table;
+
+// Weights combine along a path with `times`, and across alternative paths with `plus`.
+pub trait Semiring: Copy + PartialEq + Debug {
+    // The identity for `plus`, and an annihilator for `times`.
+    fn zero() -> Self;
+    // The identity for `times`.
+    fn one() -> Self;
+    fn plus(self, other: Self) -> Self;
+    fn times(self, other: Self) -> Self;
+}
+
+// A semiring where non-zero weights can be divided out, which is what weight pushing needs.
+pub trait DivisibleSemiring: Semiring {
+    // Returns `x` such that `divisor.times(x) == self`. `divisor` is never zero.
+    fn divide(self, divisor: Self) -> Self;
+}
+
+// The (min, +) semiring, where a path's weight is the sum of its weights and alternatives
+// take the minimum. Zero is positive infinity.
+#[derive(Debug, Clone, Copy, PartialEq, PartialOrd)]
+pub struct Tropical(pub f64);
+
+impl Semiring for Tropical {
+    fn zero() -> Self {
+        Tropical(f64::INFINITY)
+    }
+    fn one() -> Self {
+        Tropical(0.0)
+    }
+    fn plus(self, other: Self) -> Self {
+        Tropical(self.0.min(other.0))
+    }
+    fn times(self, other: Self) -> Self {
+        Tropical(self.0 + other.0)
+    }
+}
+
+impl DivisibleSemiring for Tropical {
+    fn divide(self, divisor: Self) -> Self {
+        Tropical(self.0 - divisor.0)
+    }
+}
+
+// The ordinary (+, *) semiring over the reals, e.g. for probabilities.
+impl Semiring for f64 {
+    fn zero() -> Self {
+        0.0
+    }
+    fn one() -> Self {
+        1.0
+    }
+    fn plus(self, other: Self) -> Self {
+        self + other
+    }
+    fn times(self, other: Self) -> Self {
+        self * other
+    }
+}
+
+impl DivisibleSemiring for f64 {
+    fn divide(self, divisor: Self) -> Self {
+        self / divisor
+    }
+}
+
+// A DFA whose transitions and accepting states carry weights from a semiring. The weight of an
+// accepted word is the initial weight, times the weights of its transitions, times the final
+// weight of the state it ends in. Rejected words weigh zero.
+#[derive(Debug, Clone)]
+pub struct WeightedDFA<S, E, W> {
+    initial_state: S,
+    initial_weight: W,
+    final_weights: HashMap<S, W>,
+    transitions: Table<S, E, S>,
+    weights: HashMap<(S, E), W>,
+}
+
+impl<S, E, W> WeightedDFA<S, E, W>
+where
+    S: Eq + Hash + Copy + Debug,
+    E: Eq + Hash + Copy + Debug,
+    W: Semiring,
+{
+    // Each transition is `(src, label, weight, dst)`. The initial weight is one.
+    pub fn new(
+        initial_state: S,
+        final_weights: impl IntoIterator<Item = (S, W)>,
+        transitions: impl IntoIterator<Item = (S, E, W, S)>,
+    ) -> WeightedDFA<S, E, W> {
+        let mut weights = HashMap::default();
+        let transitions = transitions
+            .into_iter()
+            .map(|(src, label, weight, dst)| {
+                weights.insert((src, label), weight);
+                (src, label, dst)
+            })
+            .collect();
+        WeightedDFA {
+            initial_state,
+            initial_weight: W::one(),
+            final_weights: final_weights.into_iter().collect(),
+            transitions,
+            weights,
+        }
+    }
+
+    pub fn weight(&self, word: &[E]) -> W {
+        let delta: HashMap<(S, E), S> = (&self.transitions)
+            .into_iter()
+            .map(|&(src, label, dst)| ((src, label), dst))
+            .collect();
+        let mut q = self.initial_state;
+        let mut weight = self.initial_weight;
+        for &label in word {
+            let dst = match delta.get(&(q, label)) {
+                Some(&dst) => dst,
+                None => return W::zero(),
+            };
+            weight = weight.times(self.weights[&(q, label)]);
+            q = dst;
+        }
+        match self.final_weights.get(&q) {
+            Some(&w) => weight.times(w),
+            None => W::zero(),
+        }
+    }
+
+    // For every reachable state, the sum over all paths from the initial state of the path's
+    // weight (including the initial weight). In the tropical semiring, that's the cost of the
+    // cheapest path.
+    // This uses Mohri's generic single-source algorithm, which only terminates if the weights of
+    // cycles eventually stop changing the sums, e.g. for non-negative tropical weights.
+    pub fn shortest_distance(&self) -> std::collections::HashMap<S, W> {
+        let mut edges: HashMap<S, Vec<(W, S)>> = HashMap::default();
+        for &(src, label, dst) in &self.transitions {
+            edges
+                .entry(src)
+                .or_default()
+                .push((self.weights[&(src, label)], dst));
+        }
+        relax(
+            vec![(self.initial_state, self.initial_weight)],
+            &edges,
+            |d, w| d.times(w),
+        )
+        .into_std()
+    }
+
+    // The weight of each state's future: the sum over all paths from the state to an accepting
+    // state of the path's weight times the final weight.
+    // Like `shortest_distance`, this only terminates for well-behaved weights.
+    pub fn distance_to_final(&self) -> std::collections::HashMap<S, W> {
+        let mut edges: HashMap<S, Vec<(W, S)>> = HashMap::default();
+        for &(src, label, dst) in &self.transitions {
+            edges
+                .entry(dst)
+                .or_default()
+                .push((self.weights[&(src, label)], src));
+        }
+        let finals = self.final_weights.iter().map(|(&q, &w)| (q, w)).collect();
+        relax(finals, &edges, |d, w| w.times(d)).into_std()
+    }
+}
+
+impl<S, E, W> WeightedDFA<S, E, W>
+where
+    S: Eq + Hash + Copy + Debug,
+    E: Eq + Hash + Copy + Debug,
+    W: DivisibleSemiring,
+{
+    // Moves weight as far towards the initial state as possible, without changing the weight of
+    // any word. In the tropical semiring, every state's cheapest way to finish then costs 0, and
+    // in the real semiring, every state's outgoing weights then sum to 1. States that cannot
+    // reach an accepting state are dropped.
+    pub fn push_weights(&self) -> WeightedDFA<S, E, W> {
+        let potential = self.distance_to_final();
+        let live = |q: &S| potential.get(q).is_some_and(|&w| w != W::zero());
+
+        let mut transitions = Vec::new();
+        let mut weights = HashMap::default();
+        for &(src, label, dst) in &self.transitions {
+            if live(&src) && live(&dst) {
+                let w = self.weights[&(src, label)].times(potential[&dst]);
+                weights.insert((src, label), w.divide(potential[&src]));
+                transitions.push((src, label, dst));
+            }
+        }
+        let final_weights = self
+            .final_weights
+            .iter()
+            .filter(|(q, _)| live(q))
+            .map(|(&q, &w)| (q, w.divide(potential[&q])))
+            .collect();
+        let initial_weight = match potential.get(&self.initial_state) {
+            Some(&w) => self.initial_weight.times(w),
+            None => W::zero(),
+        };
+        WeightedDFA {
+            initial_state: self.initial_state,
+            initial_weight,
+            final_weights,
+            transitions: Table::from(transitions),
+            weights,
+        }
+    }
+}
+
+// Mohri's generic shortest-distance algorithm: repeatedly pushes newly-arrived weight along
+// `edges` until nothing changes. `extend(d, w)` extends a distance `d` by an edge weight `w`.
+fn relax<S, W>(
+    sources: Vec<(S, W)>,
+    edges: &HashMap<S, Vec<(W, S)>>,
+    extend: impl Fn(W, W) -> W,
+) -> HashMap<S, W>
+where
+    S: Eq + Hash + Copy,
+    W: Semiring,
+{
+    let mut distance: HashMap<S, W> = HashMap::default();
+    // The weight that has arrived at each state but has not yet been pushed further.
+    let mut pending: HashMap<S, W> = HashMap::default();
+    let mut queue = VecDeque::new();
+    let mut queued = HashSet::default();
+    for (q, w) in sources {
+        let d = distance.entry(q).or_insert_with(W::zero);
+        *d = d.plus(w);
+        let r = pending.entry(q).or_insert_with(W::zero);
+        *r = r.plus(w);
+        if queued.insert(q) {
+            queue.push_back(q);
+        }
+    }
+    while let Some(q) = queue.pop_front() {
+        queued.remove(&q);
+        let r = pending.insert(q, W::zero()).unwrap_or_else(W::zero);
+        for &(w, next) in edges.get(&q).into_iter().flatten() {
+            let arriving = extend(r, w);
+            let d = distance.get(&next).copied().unwrap_or_else(W::zero);
+            let updated = d.plus(arriving);
+            if updated != d {
+                distance.insert(next, updated);
+                let p = pending.entry(next).or_insert_with(W::zero);
+                *p = p.plus(arriving);
+                if queued.insert(next) {
+                    queue.push_back(next);
+                }
+            }
+        }
+    }
+    distance
+}
+
+#[cfg(test)]
+mod test {
+    use super::{Tropical, WeightedDFA};
+
+    // A costed protocol: 'a' is cheap but must be repeated, 'b' is an expensive shortcut.
+    fn protocol() -> WeightedDFA<u32, char, Tropical> {
+        WeightedDFA::new(
+            0,
+            vec![(2, Tropical(1.0))],
+            vec![
+                (0, 'a', Tropical(1.0), 1),
+                (1, 'a', Tropical(1.0), 2),
+                (0, 'b', Tropical(5.0), 2),
+                (2, 'a', Tropical(0.5), 2),
+                (1, 'c', Tropical(0.0), 3),
+            ],
+        )
+    }
+
+    #[test]
+    fn tropical_weights_and_distances() {
+        let dfa = protocol();
+        assert_eq!(dfa.weight(&['a', 'a']), Tropical(3.0));
+        assert_eq!(dfa.weight(&['b', 'a']), Tropical(6.5));
+        assert_eq!(dfa.weight(&['a']), Tropical(f64::INFINITY));
+        assert_eq!(dfa.weight(&['a', 'c']), Tropical(f64::INFINITY));
+
+        let distance = dfa.shortest_distance();
+        assert_eq!(distance[&2], Tropical(2.0));
+        assert_eq!(distance[&3], Tropical(1.0));
+        let potential = dfa.distance_to_final();
+        assert_eq!(potential[&0], Tropical(3.0));
+        assert!(!potential.contains_key(&3));
+    }
+
+    #[test]
+    fn push_weights_preserves_word_weights() {
+        let dfa = protocol();
+        let pushed = dfa.push_weights();
+        assert_eq!(pushed.initial_weight, Tropical(3.0));
+        assert_eq!(pushed.weights[&(0, 'a')], Tropical(0.0));
+        assert_eq!(pushed.weights[&(0, 'b')], Tropical(3.0));
+        for word in [
+            vec!['a', 'a'],
+            vec!['b', 'a', 'a'],
+            vec!['a', 'c'],
+            vec!['b'],
+        ] {
+            assert_eq!(pushed.weight(&word), dfa.weight(&word));
+        }
+
+        // In the real semiring, pushing normalizes each state's outgoing weights.
+        let real: WeightedDFA<u32, char, f64> =
+            WeightedDFA::new(0, vec![(1, 1.0)], vec![(0, 'a', 2.0, 1), (0, 'b', 6.0, 1)]);
+        let pushed = real.push_weights();
+        assert_eq!(pushed.weights[&(0, 'a')], 0.25);
+        assert_eq!(pushed.weights[&(0, 'b')], 0.75);
+        assert_eq!(pushed.weight(&['b']), 6.0);
+    }
+}
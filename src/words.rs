@@ -0,0 +1,606 @@
+use std::collections::{hash_map::Entry, VecDeque};
+use std::fmt::Debug;
+use std::hash::Hash;
+
+use crate::hash::{HashMap, HashSet};
+use crate::DFA;
+
+impl<S, E> DFA<S, E>
+where
+    S: Eq + Hash + Copy + Debug,
+    E: Eq + Hash + Copy + Debug,
+{
+    // Returns a shortest word accepted by this DFA, or `None` if the language is empty.
+    // When there are several shortest words, which one is returned is unspecified.
+    pub fn shortest_word(&self) -> Option<Vec<E>> {
+        self.shortest_path(self.initial_state, |q| self.final_states.contains(q))
+            .map(|(_, word)| word)
+    }
+
+    // Finds a shortest word leading from `start` to a state satisfying `is_target`, and returns
+    // that state alongside the word.
+    pub(crate) fn shortest_path(
+        &self,
+        start: S,
+        is_target: impl Fn(&S) -> bool,
+    ) -> Option<(S, Vec<E>)> {
+        let outflows = self.transitions.by_a();
+
+        // For every discovered state, remember the (state, label) that we came from.
+        let mut parents: HashMap<S, Option<(S, E)>> = HashMap::default();
+        let mut queue = VecDeque::new();
+        parents.insert(start, None);
+        queue.push_back(start);
+        while let Some(src) = queue.pop_front() {
+            if is_target(&src) {
+                let mut word = Vec::new();
+                let mut cur = src;
+                while let Some((prev, label)) = parents[&cur] {
+                    word.push(label);
+                    cur = prev;
+                }
+                word.reverse();
+                return Some((src, word));
+            }
+            if let Some(neighbors) = outflows.get(&src) {
+                for &(label, dst) in neighbors {
+                    if let Entry::Vacant(e) = parents.entry(dst) {
+                        e.insert(Some((src, label)));
+                        queue.push_back(dst);
+                    }
+                }
+            }
+        }
+        None
+    }
+
+    // Counts the accepted words of length exactly `len`. For large `len`, this switches from
+    // dynamic programming (O(len) steps over every transition) to matrix exponentiation
+    // (O(log len) products of a matrix with one row per state).
+    // Panics if the count does not fit in a `u128`.
+    pub fn count_words_at(&self, len: usize) -> u128 {
+        let states = self.states().len() as u128;
+        let steps = u128::from(usize::BITS - len.leading_zeros());
+        if states.pow(3) * steps * 2 < len as u128 * self.transitions.len() as u128 {
+            if let Some(n) = self.count_words_by_matrix(len as u64) {
+                return n;
+            }
+        }
+
+        // Only keep a single row of the count table at a time.
+        let inflows = self.transitions.by_c();
+        let mut counts: HashMap<S, u128> = self.final_states.iter().map(|&q| (q, 1)).collect();
+        for _ in 0..len {
+            let mut next: HashMap<S, u128> = HashMap::default();
+            for (&dst, &n) in &counts {
+                for &(src, _) in inflows.get(&dst).into_iter().flatten() {
+                    let c = next.entry(src).or_insert(0);
+                    *c = c.checked_add(n).expect("word count overflows u128");
+                }
+            }
+            counts = next;
+        }
+        counts.get(&self.initial_state).copied().unwrap_or(0)
+    }
+
+    // Picks an accepted word of length `len` uniformly at random, or returns `None` if there are
+    // no such words. `rng` must produce uniformly distributed `u64`s.
+    // Panics if the number of accepted words of length `len` does not fit in a `u128`.
+    pub fn sample(&self, len: usize, mut rng: impl FnMut() -> u64) -> Option<Vec<E>> {
+        let counts = self.count_table(len);
+        self.walk(&counts, len, &mut rng)
+    }
+
+    // Picks an accepted word of length at most `max_len` uniformly at random, or returns `None`
+    // if there are no such words. Longer words are more likely only because there are more of
+    // them.
+    // Panics if the number of accepted words up to `max_len` does not fit in a `u128`.
+    pub fn sample_up_to(&self, max_len: usize, mut rng: impl FnMut() -> u64) -> Option<Vec<E>> {
+        let counts = self.count_table(max_len);
+        let at = |len: usize| counts[len].get(&self.initial_state).copied().unwrap_or(0);
+        let total = (0..=max_len).try_fold(0u128, |total, len| total.checked_add(at(len)));
+        let total = total.expect("word count overflows u128");
+        if total == 0 {
+            return None;
+        }
+        let mut x = random_below(&mut rng, total);
+        for len in 0..=max_len {
+            if x < at(len) {
+                return self.walk(&counts, len, &mut rng);
+            }
+            x -= at(len);
+        }
+        unreachable!()
+    }
+
+    // Picks one of the accepted words of length `len`, given `count_table(k)` for some `k >= len`.
+    fn walk(
+        &self,
+        counts: &[HashMap<S, u128>],
+        len: usize,
+        rng: &mut impl FnMut() -> u64,
+    ) -> Option<Vec<E>> {
+        let outflows = self.transitions.by_a();
+        let mut word = Vec::with_capacity(len);
+        let mut src = self.initial_state;
+        for remaining in (1..=len).rev() {
+            let total = counts[remaining].get(&src).copied().unwrap_or(0);
+            if total == 0 {
+                return None;
+            }
+            // Each edge is picked in proportion to the number of words that it leads to.
+            let mut x = random_below(rng, total);
+            for &(label, dst) in &outflows[&src] {
+                let n = counts[remaining - 1].get(&dst).copied().unwrap_or(0);
+                if x < n {
+                    word.push(label);
+                    src = dst;
+                    break;
+                }
+                x -= n;
+            }
+        }
+        if !self.final_states.contains(&src) {
+            return None;
+        }
+        Some(word)
+    }
+
+    // `count_table(len)[k][q]` is the number of words of length `k` accepted starting from `q`.
+    // States that accept no words of length `k` are omitted.
+    fn count_table(&self, len: usize) -> Vec<HashMap<S, u128>> {
+        self.checked_count_table(len)
+            .expect("word count overflows u128")
+    }
+
+    // `count_table`, or `None` if some count does not fit in a `u128`.
+    fn checked_count_table(&self, len: usize) -> Option<Vec<HashMap<S, u128>>> {
+        let inflows = self.transitions.by_c();
+        let mut counts: Vec<HashMap<S, u128>> =
+            vec![self.final_states.iter().map(|&q| (q, 1)).collect()];
+        for k in 0..len {
+            counts.push(count_longer(&inflows, &counts[k])?);
+        }
+        Some(counts)
+    }
+}
+
+// Given the number of words of some length accepted from each state, counts those one symbol
+// longer. Returns `None` if a count does not fit in a `u128`.
+fn count_longer<S, E>(
+    inflows: &HashMap<S, Vec<(S, E)>>,
+    counts: &HashMap<S, u128>,
+) -> Option<HashMap<S, u128>>
+where
+    S: Eq + Hash + Copy,
+{
+    let mut next: HashMap<S, u128> = HashMap::default();
+    for (&dst, &n) in counts {
+        for &(src, _) in inflows.get(&dst).into_iter().flatten() {
+            let c = next.entry(src).or_insert(0);
+            *c = c.checked_add(n)?;
+        }
+    }
+    Some(next)
+}
+
+// Picks a uniformly random number in `0..n` using rejection sampling.
+fn random_below(rng: &mut impl FnMut() -> u64, n: u128) -> u128 {
+    let limit = u128::MAX - u128::MAX % n;
+    loop {
+        let x = (u128::from(rng()) << 64) | u128::from(rng());
+        if x < limit {
+            return x % n;
+        }
+    }
+}
+
+impl<S, E> DFA<S, E>
+where
+    S: Eq + Hash + Copy + Debug,
+    E: Eq + Hash + Copy + Debug + Ord,
+{
+    // Lazily yields every accepted word in length-lexicographic order: shorter words first, and
+    // words of the same length ordered by `E`. If `max_len` is set, no longer words are produced.
+    // For infinite languages without a `max_len`, the iterator never ends.
+    pub fn words(&self, max_len: Option<usize>) -> Words<'_, S, E> {
+        let mut outflows = self.transitions.by_a();
+        for edges in outflows.values_mut() {
+            edges.sort_by_key(|&(label, _)| label);
+        }
+        let inflows = self.transitions.by_c();
+        let coaccessible = self.coaccessible();
+
+        let frontier = Some(self.initial_state)
+            .into_iter()
+            .filter(|q| coaccessible.contains(q))
+            .collect();
+        let mut words = Words {
+            dfa: self,
+            outflows,
+            inflows,
+            coaccessible,
+            alive: vec![self.final_states.clone()],
+            frontier,
+            max_len,
+            len: 0,
+            stack: Vec::new(),
+            word: Vec::new(),
+        };
+        words.start();
+        words
+    }
+
+    // Returns the position of `word` among all accepted words, in the length-lexicographic order
+    // that `words` produces them, or `None` if `word` is not accepted. Positions are `u128`s, so
+    // this also returns `None` if counting the words up to `word.len()` overflows one.
+    pub fn rank(&self, word: &[E]) -> Option<u128> {
+        let outflows = self.transitions.by_a();
+        let counts = self.checked_count_table(word.len())?;
+        let count = |len: usize, q: &S| counts[len].get(q).copied().unwrap_or(0);
+
+        // Every shorter accepted word comes first.
+        let mut rank = (0..word.len()).try_fold(0u128, |rank, len| {
+            rank.checked_add(count(len, &self.initial_state))
+        })?;
+        let mut src = self.initial_state;
+        for (i, &label) in word.iter().enumerate() {
+            let remaining = word.len() - i - 1;
+            let mut next = None;
+            for &(e, dst) in outflows.get(&src)? {
+                if e < label {
+                    rank = rank.checked_add(count(remaining, &dst))?;
+                } else if e == label {
+                    next = Some(dst);
+                }
+            }
+            src = next?;
+        }
+        if !self.final_states.contains(&src) {
+            return None;
+        }
+        Some(rank)
+    }
+
+    // The inverse of `rank`: returns the accepted word at position `index`, or `None` if there
+    // are not that many accepted words. Also returns `None` if counting the words up to the
+    // length of the answer overflows a `u128`.
+    pub fn unrank(&self, mut index: u128) -> Option<Vec<E>> {
+        let mut outflows = self.transitions.by_a();
+        for edges in outflows.values_mut() {
+            edges.sort_by_key(|&(label, _)| label);
+        }
+        let inflows = self.transitions.by_c();
+        // A language with a word of some length from `n` to `2n - 1`, for `n` states, is
+        // infinite; otherwise it has no words of length `n` or more.
+        let n = self.states().len();
+        let mut infinite = false;
+        let mut counts: Vec<HashMap<S, u128>> =
+            vec![self.final_states.iter().map(|&q| (q, 1)).collect()];
+        let len = loop {
+            let len = counts.len() - 1;
+            let here = counts[len].get(&self.initial_state).copied().unwrap_or(0);
+            if index < here {
+                break len;
+            }
+            index -= here;
+            infinite |= len >= n && here > 0;
+            if len + 1 >= 2 * n && !infinite {
+                return None;
+            }
+            counts.push(count_longer(&inflows, &counts[len])?);
+        };
+
+        let mut word = Vec::with_capacity(len);
+        let mut src = self.initial_state;
+        for remaining in (0..len).rev() {
+            for &(label, dst) in &outflows[&src] {
+                let n = counts[remaining].get(&dst).copied().unwrap_or(0);
+                if index < n {
+                    word.push(label);
+                    src = dst;
+                    break;
+                }
+                index -= n;
+            }
+        }
+        Some(word)
+    }
+}
+
+// Iterator over the words of a DFA, created by `DFA::words`.
+pub struct Words<'a, S, E> {
+    dfa: &'a DFA<S, E>,
+    outflows: HashMap<S, Vec<(E, S)>>,
+    inflows: HashMap<S, Vec<(S, E)>>,
+    coaccessible: HashSet<S>,
+    // `alive[k]` holds the states that accept at least one word of length exactly `k`.
+    alive: Vec<HashSet<S>>,
+    // The co-accessible states reachable from the initial state by words of length `len`.
+    // Once this is empty, there are no more words to produce.
+    frontier: HashSet<S>,
+    max_len: Option<usize>,
+    len: usize,
+    // A depth-first search over words of length `len`, paired with the next edge to explore.
+    stack: Vec<(S, usize)>,
+    word: Vec<E>,
+}
+
+impl<'a, S, E> Words<'a, S, E>
+where
+    S: Eq + Hash + Copy + Debug,
+    E: Eq + Hash + Copy + Debug + Ord,
+{
+    // Sets up the depth-first search for words of length `self.len`.
+    fn start(&mut self) {
+        if self.alive[self.len].contains(&self.dfa.initial_state) {
+            self.stack.push((self.dfa.initial_state, 0));
+        }
+    }
+
+    // Moves on to the next word length, returning false if there can be no more words.
+    fn advance(&mut self) -> bool {
+        if self.frontier.is_empty() || self.max_len.is_some_and(|m| self.len >= m) {
+            return false;
+        }
+        let mut frontier = HashSet::default();
+        for src in &self.frontier {
+            if let Some(edges) = self.outflows.get(src) {
+                for &(_, dst) in edges {
+                    if self.coaccessible.contains(&dst) {
+                        frontier.insert(dst);
+                    }
+                }
+            }
+        }
+        let mut alive = HashSet::default();
+        for dst in &self.alive[self.len] {
+            if let Some(edges) = self.inflows.get(dst) {
+                for &(src, _) in edges {
+                    alive.insert(src);
+                }
+            }
+        }
+        self.frontier = frontier;
+        self.alive.push(alive);
+        self.len += 1;
+        self.start();
+        true
+    }
+}
+
+impl<'a, S, E> Iterator for Words<'a, S, E>
+where
+    S: Eq + Hash + Copy + Debug,
+    E: Eq + Hash + Copy + Debug + Ord,
+{
+    type Item = Vec<E>;
+
+    fn next(&mut self) -> Option<Vec<E>> {
+        loop {
+            let depth = self.word.len();
+            let (src, next) = match self.stack.last_mut() {
+                Some(top) => top,
+                None => {
+                    if !self.advance() {
+                        return None;
+                    }
+                    continue;
+                }
+            };
+            if depth == self.len {
+                let src = *src;
+                self.stack.pop();
+                let found = self.word.clone();
+                self.word.pop();
+                if self.dfa.final_states.contains(&src) {
+                    return Some(found);
+                }
+                continue;
+            }
+            let remaining = &self.alive[self.len - depth - 1];
+            let edges = self.outflows.get(src).map_or(&[][..], |v| &v[..]);
+            match (*next..edges.len()).find(|&i| remaining.contains(&edges[i].1)) {
+                Some(i) => {
+                    *next = i + 1;
+                    let (label, dst) = edges[i];
+                    self.stack.push((dst, 0));
+                    self.word.push(label);
+                }
+                None => {
+                    self.stack.pop();
+                    self.word.pop();
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::hash::HashMap;
+
+    use crate::DFA;
+
+    #[test]
+    fn shortest_word_wikipedia() {
+        let input: DFA<u32, u8> = DFA::new(
+            0,
+            vec![2, 3, 4],
+            vec![
+                (0, 0, 1),
+                (0, 1, 2),
+                (1, 0, 0),
+                (1, 1, 3),
+                (2, 0, 4),
+                (2, 1, 5),
+                (3, 0, 4),
+                (3, 1, 5),
+                (4, 0, 4),
+                (4, 1, 5),
+                (5, 0, 5),
+                (5, 1, 5),
+            ],
+        );
+        assert_eq!(input.shortest_word(), Some(vec![1]));
+    }
+
+    #[test]
+    fn shortest_word_empty_string() {
+        let input: DFA<u32, u8> = DFA::new(0, vec![0], vec![(0, 0, 1)]);
+        assert_eq!(input.shortest_word(), Some(vec![]));
+    }
+
+    #[test]
+    fn words_in_length_lex_order() {
+        // Accepts words over {0, 1} that end in a 1.
+        let input: DFA<u32, u8> =
+            DFA::new(0, vec![1], vec![(0, 0, 0), (0, 1, 1), (1, 0, 0), (1, 1, 1)]);
+        let words: Vec<Vec<u8>> = input.words(Some(3)).collect();
+        assert_eq!(
+            words,
+            vec![
+                vec![1],
+                vec![0, 1],
+                vec![1, 1],
+                vec![0, 0, 1],
+                vec![0, 1, 1],
+                vec![1, 0, 1],
+                vec![1, 1, 1],
+            ]
+        );
+        assert_eq!(input.words(None).nth(100).map(|w| w.len()), Some(7));
+    }
+
+    #[test]
+    fn words_of_finite_language() {
+        let input: DFA<u32, u8> = DFA::new(
+            0,
+            vec![0, 2],
+            vec![(0, 1, 1), (1, 0, 2), (1, 1, 2), (2, 0, 3)],
+        );
+        let words: Vec<Vec<u8>> = input.words(None).collect();
+        assert_eq!(words, vec![vec![], vec![1, 0], vec![1, 1]]);
+    }
+
+    #[test]
+    fn sample_is_uniform() {
+        // Accepts words over {0, 1} that end in a 1.
+        let input: DFA<u32, u8> =
+            DFA::new(0, vec![1], vec![(0, 0, 0), (0, 1, 1), (1, 0, 0), (1, 1, 1)]);
+        assert_eq!(input.count_words_at(3), 4);
+
+        let mut seed: u64 = 0x2545_f491_4f6c_dd1d;
+        let mut rng = || {
+            seed ^= seed << 13;
+            seed ^= seed >> 7;
+            seed ^= seed << 17;
+            seed
+        };
+        let mut histogram: HashMap<Vec<u8>, usize> = HashMap::default();
+        for _ in 0..4000 {
+            *histogram
+                .entry(input.sample(3, &mut rng).unwrap())
+                .or_default() += 1;
+        }
+        assert_eq!(histogram.len(), 4);
+        for (word, n) in histogram {
+            assert_eq!(word.last(), Some(&1));
+            assert!(
+                (900..1100).contains(&n),
+                "{:?} was sampled {} times",
+                word,
+                n
+            );
+        }
+    }
+
+    #[test]
+    fn rank_and_unrank_roundtrip() {
+        let input: DFA<u32, u8> = DFA::new(
+            0,
+            vec![2, 3, 4],
+            vec![
+                (0, 0, 1),
+                (0, 1, 2),
+                (1, 0, 0),
+                (1, 1, 3),
+                (2, 0, 4),
+                (2, 1, 5),
+                (3, 0, 4),
+                (3, 1, 5),
+                (4, 0, 4),
+                (4, 1, 5),
+                (5, 0, 5),
+                (5, 1, 5),
+            ],
+        );
+        let words: Vec<Vec<u8>> = input.words(Some(5)).collect();
+        for (i, word) in words.iter().enumerate() {
+            assert_eq!(input.rank(word), Some(i as u128));
+            assert_eq!(input.unrank(i as u128).as_ref(), Some(word));
+        }
+        // The language is infinite, so the next word is one symbol longer.
+        assert_eq!(input.unrank(words.len() as u128).unwrap().len(), 6);
+        assert_eq!(input.rank(&[1, 1]), None);
+
+        // "", "b" and "ab", and nothing after them.
+        let finite: DFA<u32, char> =
+            DFA::new(0, vec![0, 2], vec![(0, 'a', 1), (0, 'b', 2), (1, 'b', 2)]);
+        assert_eq!(finite.rank(&['a', 'b']), Some(2));
+        assert_eq!(finite.unrank(2), Some(vec!['a', 'b']));
+        assert_eq!(finite.unrank(3), None);
+    }
+
+    #[test]
+    fn rank_overflow() {
+        // Every byte string, of which there are 2^136 of length 17.
+        let all: DFA<u32, u8> = DFA::new(0, vec![0], (0..=u8::MAX).map(|b| (0, b, 0)));
+        assert_eq!(
+            all.rank(&[0; 15]),
+            Some((0..15).map(|n| 256u128.pow(n)).sum())
+        );
+        assert_eq!(all.rank(&[0; 17]), None);
+        assert_eq!(all.unrank(u128::MAX), None);
+    }
+
+    #[test]
+    fn sample_up_to_is_uniform() {
+        // Accepts "", "a", "aa", "ab", "ba", "bb": one word in six is empty.
+        let input: DFA<u32, char> = DFA::new(
+            0,
+            vec![0, 1, 2],
+            vec![
+                (0, 'a', 1),
+                (1, 'a', 2),
+                (1, 'b', 2),
+                (0, 'b', 3),
+                (3, 'a', 2),
+                (3, 'b', 2),
+            ],
+        );
+        let mut rng = crate::testing::seeded_rng(7);
+        let mut histogram: HashMap<Vec<char>, usize> = HashMap::default();
+        for _ in 0..6000 {
+            *histogram
+                .entry(input.sample_up_to(2, &mut rng).unwrap())
+                .or_default() += 1;
+        }
+        assert_eq!(histogram.len(), 6);
+        assert!(histogram.values().all(|n| (900..1100).contains(n)));
+        assert_eq!(input.sample_up_to(0, &mut rng), Some(vec![]));
+    }
+
+    #[test]
+    fn sample_without_words() {
+        let input: DFA<u32, u8> = DFA::new(0, vec![1], vec![(0, 0, 1)]);
+        assert_eq!(input.count_words_at(2), 0);
+        assert_eq!(input.sample(2, || 4), None);
+    }
+
+    #[test]
+    fn shortest_word_empty_language() {
+        let input: DFA<u32, u8> = DFA::new(0, vec![2], vec![(0, 0, 1), (1, 0, 0)]);
+        assert_eq!(input.shortest_word(), None);
+    }
+}